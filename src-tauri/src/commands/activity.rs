@@ -0,0 +1,191 @@
+use crate::models::ActivityEntry;
+use crate::db::Database;
+use tauri::State;
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{Local, NaiveDate, TimeZone, Utc};
+use uuid::Uuid;
+
+/// 追加一条活动记录。活动日志只追加不修改，由命令层在变更成功后写入。
+pub fn log_activity(
+    conn: &Connection,
+    entity_type: &str,
+    entity_id: &str,
+    action: &str,
+    summary: &str,
+) -> Result<(), rusqlite::Error> {
+    let project_id = entity_project_id(conn, entity_type, entity_id)?;
+
+    conn.execute(
+        "INSERT INTO activity_log (id, entity_type, entity_id, project_id, action, summary, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        params![
+            Uuid::new_v4().to_string(),
+            entity_type,
+            entity_id,
+            project_id,
+            action,
+            summary,
+            Utc::now().to_rfc3339()
+        ]
+    )?;
+
+    Ok(())
+}
+
+/// 查询实体所属的项目，项目本身归属于自己
+fn entity_project_id(conn: &Connection, entity_type: &str, entity_id: &str) -> Result<Option<String>, rusqlite::Error> {
+    let query = match entity_type {
+        "note" => "SELECT project_id FROM notes WHERE id = ?",
+        "task" => "SELECT project_id FROM tasks WHERE id = ?",
+        "milestone" => "SELECT project_id FROM milestones WHERE id = ?",
+        "project" => return Ok(Some(entity_id.to_string())),
+        _ => return Ok(None),
+    };
+
+    Ok(conn.query_row(query, params![entity_id], |row| row.get(0)).optional()?.flatten())
+}
+
+/// 查询实体的显示名称，用于生成活动描述
+pub fn entity_title(conn: &Connection, entity_type: &str, entity_id: &str) -> Result<String, rusqlite::Error> {
+    let query = match entity_type {
+        "note" => "SELECT title FROM notes WHERE id = ?",
+        "task" => "SELECT title FROM tasks WHERE id = ?",
+        "project" => "SELECT name FROM projects WHERE id = ?",
+        "milestone" => "SELECT name FROM milestones WHERE id = ?",
+        "tag" => "SELECT name FROM tags WHERE id = ?",
+        _ => return Ok(entity_id.to_string()),
+    };
+
+    Ok(conn.query_row(query, params![entity_id], |row| row.get(0))
+        .optional()?
+        .unwrap_or_else(|| entity_id.to_string()))
+}
+
+/// 将本地日期转换为 UTC 时间范围 [start, end)
+fn local_day_range(day: &str) -> Result<(String, String), String> {
+    let date = NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|_| "Invalid day format, expected YYYY-MM-DD")?;
+    let start = date.and_hms_opt(0, 0, 0).ok_or("Invalid day")?;
+    let end = start + chrono::Duration::days(1);
+
+    let to_utc = |naive: chrono::NaiveDateTime| {
+        Local.from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc).to_rfc3339())
+            .ok_or_else(|| "Invalid local time".to_string())
+    };
+
+    Ok((to_utc(start)?, to_utc(end)?))
+}
+
+fn query_activity(
+    conn: &Connection,
+    entity_type: Option<String>,
+    entity_id: Option<String>,
+    project_id: Option<String>,
+    day: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<ActivityEntry>, String> {
+    let mut query = "SELECT id, entity_type, entity_id, project_id, action, summary, created_at
+        FROM activity_log WHERE 1=1".to_string();
+
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+    if let Some(entity_type) = entity_type {
+        query.push_str(" AND entity_type = ?");
+        params_vec.push(Box::new(entity_type));
+    }
+
+    if let Some(entity_id) = entity_id {
+        query.push_str(" AND entity_id = ?");
+        params_vec.push(Box::new(entity_id));
+    }
+
+    if let Some(project_id) = project_id {
+        query.push_str(" AND project_id = ?");
+        params_vec.push(Box::new(project_id));
+    }
+
+    if let Some(day) = day {
+        let (start, end) = local_day_range(&day)?;
+        query.push_str(" AND created_at >= ? AND created_at < ?");
+        params_vec.push(Box::new(start));
+        params_vec.push(Box::new(end));
+    }
+
+    query.push_str(" ORDER BY created_at DESC LIMIT ?");
+    params_vec.push(Box::new(limit.unwrap_or(-1)));
+
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+
+    let entry_iter = stmt.query_map(
+        rusqlite::params_from_iter(params_vec.iter().map(|p| p.as_ref())),
+        |row| {
+            Ok(ActivityEntry {
+                id: row.get(0)?,
+                entity_type: row.get(1)?,
+                entity_id: row.get(2)?,
+                project_id: row.get(3)?,
+                action: row.get(4)?,
+                summary: row.get(5)?,
+                created_at: row.get::<_, String>(6)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(6, "created_at".to_string(), rusqlite::types::Type::Text))?,
+            })
+        }
+    ).map_err(|e| e.to_string())?;
+
+    let mut entries = Vec::new();
+    for entry in entry_iter {
+        entries.push(entry.map_err(|e| e.to_string())?);
+    }
+
+    Ok(entries)
+}
+
+/// 查询活动记录，可按实体、项目和本地日期（YYYY-MM-DD）筛选
+#[tauri::command]
+pub async fn get_activity_log(
+    entity_type: Option<String>,
+    entity_id: Option<String>,
+    project_id: Option<String>,
+    day: Option<String>,
+    limit: Option<i64>,
+    db: State<'_, Database>
+) -> Result<Vec<ActivityEntry>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    query_activity(&conn, entity_type, entity_id, project_id, day, limit)
+}
+
+/// 导出活动记录，`format` 可选 "json"（默认）或 "csv"，返回文件内容
+#[tauri::command]
+pub async fn export_activity_log(
+    format: Option<String>,
+    project_id: Option<String>,
+    day: Option<String>,
+    db: State<'_, Database>
+) -> Result<String, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let entries = query_activity(&conn, None, None, project_id, day, None)?;
+
+    match format.as_deref().unwrap_or("json") {
+        "json" => serde_json::to_string_pretty(&entries).map_err(|e| e.to_string()),
+        "csv" => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.write_record(["created_at", "entity_type", "entity_id", "project_id", "action", "summary"])
+                .map_err(|e| e.to_string())?;
+            for entry in entries.iter().rev() {
+                writer.write_record([
+                    entry.created_at.to_rfc3339().as_str(),
+                    entry.entity_type.as_str(),
+                    entry.entity_id.as_str(),
+                    entry.project_id.as_deref().unwrap_or(""),
+                    entry.action.as_str(),
+                    entry.summary.as_str(),
+                ]).map_err(|e| e.to_string())?;
+            }
+            let data = writer.into_inner().map_err(|e| e.to_string())?;
+            String::from_utf8(data).map_err(|e| e.to_string())
+        }
+        other => Err(format!("Unsupported export format: {}", other)),
+    }
+}
@@ -0,0 +1,252 @@
+use crate::models::{BackupInfo, BackupSettings};
+use crate::db::{self, init_database, Database};
+use tauri::{AppHandle, Manager, State};
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use rusqlite::backup::Progress;
+use chrono::{DateTime, Local, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration as StdDuration;
+
+// 自动备份设置键与默认值，间隔为 0 表示关闭自动备份
+pub const AUTO_BACKUP_INTERVAL_SETTING: &str = "auto_backup_interval_hours";
+pub const AUTO_BACKUP_KEEP_SETTING: &str = "auto_backup_keep";
+pub const DEFAULT_AUTO_BACKUP_INTERVAL_HOURS: i64 = 24;
+pub const DEFAULT_AUTO_BACKUP_KEEP: i64 = 7;
+
+// 后台检查是否需要自动备份的间隔
+const AUTO_BACKUP_CHECK_SECS: u64 = 600;
+
+const BACKUP_PREFIX: &str = "backup-";
+const BACKUP_EXTENSION: &str = "db";
+
+// 自动备份的文件名后缀，轮换时只删除带此后缀的备份
+const AUTO_BACKUP_SUFFIX: &str = "-auto";
+
+/// 生成以当前时间（精确到毫秒）命名的备份路径，与已有文件重名时追加序号
+fn backup_path(dir: &Path, suffix: &str) -> PathBuf {
+    let stamp = Local::now().format("%Y%m%d-%H%M%S-%3f").to_string();
+    let mut path = dir.join(format!("{}{}{}.{}", BACKUP_PREFIX, stamp, suffix, BACKUP_EXTENSION));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{}{}-{}{}.{}", BACKUP_PREFIX, stamp, n, suffix, BACKUP_EXTENSION));
+        n += 1;
+    }
+    path
+}
+
+fn is_auto_backup(path: &str) -> bool {
+    Path::new(path).file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.ends_with(AUTO_BACKUP_SUFFIX))
+}
+
+fn backup_info(path: &Path) -> Result<BackupInfo, String> {
+    let metadata = fs::metadata(path).map_err(|e| e.to_string())?;
+    let modified = metadata.modified().map_err(|e| e.to_string())?;
+
+    Ok(BackupInfo {
+        path: path.to_string_lossy().to_string(),
+        size: metadata.len(),
+        created_at: DateTime::<Utc>::from(modified),
+    })
+}
+
+/// 使用 SQLite 在线备份 API 生成一致的快照，应用运行时也可以安全调用。
+/// 先写入临时文件再重命名，避免留下不完整的备份。
+pub fn create_snapshot(conn: &Connection, target: &Path) -> Result<BackupInfo, String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let staging = target.with_extension("tmp");
+    conn.backup(DatabaseName::Main, &staging, None).map_err(|e| e.to_string())?;
+    fs::rename(&staging, target).map_err(|e| e.to_string())?;
+
+    backup_info(target)
+}
+
+/// 列出目录中的备份文件，最新的在前
+pub fn list_backup_files(dir: &Path) -> Result<Vec<BackupInfo>, String> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let is_backup = path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(BACKUP_PREFIX))
+            && path.extension().and_then(|ext| ext.to_str()) == Some(BACKUP_EXTENSION);
+
+        if is_backup {
+            backups.push(backup_info(&path)?);
+        }
+    }
+
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(backups)
+}
+
+/// 只保留最新的 `keep` 个自动备份，手动备份和恢复前的备份不会被删除，返回删除的文件数
+pub fn rotate_backups(dir: &Path, keep: usize) -> Result<usize, String> {
+    let backups = list_backup_files(dir)?;
+    let mut removed = 0;
+
+    for backup in backups.iter().filter(|backup| is_auto_backup(&backup.path)).skip(keep) {
+        fs::remove_file(&backup.path).map_err(|e| e.to_string())?;
+        removed += 1;
+    }
+
+    Ok(removed)
+}
+
+/// 检查备份文件是否是完整的应用数据库
+fn validate_backup(path: &Path) -> Result<(), String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Cannot open backup: {}", e))?;
+
+    let integrity: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| format!("Backup is not a valid database: {}", e))?;
+    if integrity != "ok" {
+        return Err(format!("Backup failed integrity check: {}", integrity));
+    }
+
+    let has_notes: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'notes'",
+        [],
+        |row| row.get(0)
+    ).map_err(|e| e.to_string())?;
+    if !has_notes {
+        return Err("Backup does not contain application data".to_string());
+    }
+
+    Ok(())
+}
+
+/// 从备份恢复数据库。
+///
+/// 备份先经过校验并复制到暂存文件执行迁移，然后通过备份 API 整体写入
+/// 当前数据库，连接池中的其他连接会立即看到恢复后的数据。
+/// 恢复前会自动保存一份当前数据库的备份。
+pub fn restore_snapshot(conn: &mut Connection, source: &Path, backup_dir: &Path) -> Result<(), String> {
+    validate_backup(source)?;
+
+    fs::create_dir_all(backup_dir).map_err(|e| e.to_string())?;
+    let staging = backup_dir.join("restore-staging.tmp");
+    fs::copy(source, &staging).map_err(|e| e.to_string())?;
+
+    let migrated = Connection::open(&staging)
+        .and_then(|staging_conn| init_database(&staging_conn));
+    if let Err(e) = migrated {
+        let _ = fs::remove_file(&staging);
+        return Err(format!("Failed to migrate backup: {}", e));
+    }
+
+    create_snapshot(conn, &backup_path(backup_dir, "-pre-restore"))?;
+
+    let restored = conn.restore(DatabaseName::Main, &staging, None::<fn(Progress)>);
+    let _ = fs::remove_file(&staging);
+    restored.map_err(|e| e.to_string())
+}
+
+fn load_backup_settings(conn: &Connection) -> Result<BackupSettings, rusqlite::Error> {
+    Ok(BackupSettings {
+        interval_hours: db::get_setting(conn, AUTO_BACKUP_INTERVAL_SETTING)?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_AUTO_BACKUP_INTERVAL_HOURS),
+        keep: db::get_setting(conn, AUTO_BACKUP_KEEP_SETTING)?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_AUTO_BACKUP_KEEP),
+    })
+}
+
+/// 距离上次自动备份超过设置的间隔时创建备份并轮换旧备份，手动备份不影响计划
+pub fn run_auto_backup(database: &Database) -> Result<Option<BackupInfo>, String> {
+    let conn = database.get_connection().map_err(|e| e.to_string())?;
+    let settings = load_backup_settings(&conn).map_err(|e| e.to_string())?;
+
+    if settings.interval_hours <= 0 {
+        return Ok(None);
+    }
+
+    let dir = database.backup_dir();
+    let latest = list_backup_files(&dir)?.into_iter().find(|backup| is_auto_backup(&backup.path));
+    let due = match latest {
+        Some(latest) => Utc::now() - latest.created_at >= chrono::Duration::hours(settings.interval_hours),
+        None => true,
+    };
+
+    if !due {
+        return Ok(None);
+    }
+
+    let info = create_snapshot(&conn, &backup_path(&dir, AUTO_BACKUP_SUFFIX))?;
+    rotate_backups(&dir, settings.keep.max(1) as usize)?;
+
+    Ok(Some(info))
+}
+
+/// 启动后台自动备份任务
+pub fn spawn_auto_backup(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            {
+                let database = app.state::<Database>();
+                match run_auto_backup(&database) {
+                    Ok(Some(info)) => log::info!("Automatic backup created at {}", info.path),
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Automatic backup failed: {}", e),
+                }
+            }
+            tokio::time::sleep(StdDuration::from_secs(AUTO_BACKUP_CHECK_SECS)).await;
+        }
+    });
+}
+
+/// 创建备份，未指定路径时保存到应用数据目录的 backups 子目录
+#[tauri::command]
+pub async fn create_backup(path: Option<String>, db: State<'_, Database>) -> Result<BackupInfo, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let target = match path {
+        Some(path) => PathBuf::from(path),
+        None => backup_path(&db.backup_dir(), ""),
+    };
+
+    create_snapshot(&conn, &target)
+}
+
+#[tauri::command]
+pub async fn list_backups(db: State<'_, Database>) -> Result<Vec<BackupInfo>, String> {
+    list_backup_files(&db.backup_dir())
+}
+
+#[tauri::command]
+pub async fn restore_backup(path: String, db: State<'_, Database>) -> Result<(), String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    restore_snapshot(&mut conn, Path::new(&path), &db.backup_dir())
+}
+
+#[tauri::command]
+pub async fn get_backup_settings(db: State<'_, Database>) -> Result<BackupSettings, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    load_backup_settings(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_backup_settings(interval_hours: i64, keep: i64, db: State<'_, Database>) -> Result<(), String> {
+    if interval_hours < 0 || keep < 1 {
+        return Err("Invalid backup settings".to_string());
+    }
+
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    db::set_setting(&conn, AUTO_BACKUP_INTERVAL_SETTING, &interval_hours.to_string()).map_err(|e| e.to_string())?;
+    db::set_setting(&conn, AUTO_BACKUP_KEEP_SETTING, &keep.to_string()).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
@@ -0,0 +1,934 @@
+use crate::models::{AgendaDay, AgendaItem, CalendarEvent, CalendarImportReport, CalendarSource, FreeSlot};
+use crate::db::{self, Database};
+use crate::commands::sync::content_hash;
+use tauri::{AppHandle, Manager, State};
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use uuid::Uuid;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration as StdDuration;
+
+// 议程的默认工作时间，用于计算空闲时段
+pub const AGENDA_WORK_START_SETTING: &str = "agenda_work_start";
+pub const AGENDA_WORK_END_SETTING: &str = "agenda_work_end";
+const DEFAULT_WORK_START: &str = "09:00";
+const DEFAULT_WORK_END: &str = "18:00";
+
+// 检查订阅日历文件变化的间隔
+const CALENDAR_REFRESH_SECS: u64 = 300;
+
+// 展开重复事件时从查询范围附近开始的最大周期数，防止错误的规则导致死循环
+const MAX_OCCURRENCES: usize = 5000;
+
+/// 一条 iCalendar 属性：名称、参数和值
+struct IcsProperty {
+    name: String,
+    params: HashMap<String, String>,
+    value: String,
+}
+
+/// 解析后待写入的事件
+struct ParsedEvent {
+    uid: String,
+    summary: String,
+    description: Option<String>,
+    location: Option<String>,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    all_day: bool,
+    transparent: bool,
+    recurrence: Option<String>,
+    exdates: Vec<DateTime<Utc>>,
+    recurrence_id: Option<DateTime<Utc>>,
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// 展开折叠行并拆分为属性
+fn parse_properties(text: &str) -> Vec<IcsProperty> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        if let Some(rest) = raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest);
+                continue;
+            }
+        }
+        lines.push(raw.to_string());
+    }
+
+    let mut properties = Vec::new();
+    for line in lines {
+        // 参数值可能带引号并包含冒号，只在引号外拆分
+        let mut in_quotes = false;
+        let split = line.char_indices().find(|(_, c)| {
+            if *c == '"' {
+                in_quotes = !in_quotes;
+            }
+            *c == ':' && !in_quotes
+        });
+        let Some((index, _)) = split else { continue };
+
+        let (head, value) = (&line[..index], &line[index + 1..]);
+        let mut parts = head.split(';');
+        let name = parts.next().unwrap_or("").to_uppercase();
+        let params = parts
+            .filter_map(|p| p.split_once('='))
+            .map(|(k, v)| (k.to_uppercase(), v.trim_matches('"').to_string()))
+            .collect();
+
+        properties.push(IcsProperty { name, params, value: value.to_string() });
+    }
+
+    properties
+}
+
+fn local_to_utc(naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local.from_local_datetime(&naive).earliest().map(|dt| dt.with_timezone(&Utc))
+}
+
+/// 解析日期或日期时间，返回 UTC 时间以及是否为全天。
+/// 不包含时区数据库，带 TZID 或浮动时间按本地时间处理。
+fn parse_ics_time(value: &str, params: &HashMap<String, String>) -> Option<(DateTime<Utc>, bool)> {
+    let value = value.trim();
+    if params.get("VALUE").map(String::as_str) == Some("DATE") || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((local_to_utc(date.and_hms_opt(0, 0, 0)?)?, true));
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&naive), false));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some((local_to_utc(naive)?, false))
+}
+
+/// 解析 ISO 8601 时长，例如 PT1H30M、P1D、-P1W
+fn parse_ics_duration(value: &str) -> Option<Duration> {
+    let (negative, rest) = match value.trim().strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim().trim_start_matches('+')),
+    };
+    let rest = rest.strip_prefix('P')?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (unit, in_time) {
+                    ('W', false) => Duration::weeks(n),
+                    ('D', false) => Duration::days(n),
+                    ('H', true) => Duration::hours(n),
+                    ('M', true) => Duration::minutes(n),
+                    ('S', true) => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(if negative { -total } else { total })
+}
+
+/// 从 iCalendar 文本中解析事件，返回事件和跳过原因
+fn parse_calendar(text: &str) -> (Vec<ParsedEvent>, Vec<String>) {
+    let mut events = Vec::new();
+    let mut skipped = Vec::new();
+
+    let mut current: Option<Vec<IcsProperty>> = None;
+    let mut nested = 0;
+
+    for property in parse_properties(text) {
+        let (name, value) = (property.name.clone(), property.value.to_uppercase());
+        match (name.as_str(), value.as_str()) {
+            ("BEGIN", "VEVENT") if current.is_none() => current = Some(Vec::new()),
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", "VEVENT") if nested == 0 => {
+                if let Some(props) = current.take() {
+                    match build_event(props) {
+                        Ok(Some(event)) => events.push(event),
+                        Ok(None) => {}
+                        Err(reason) => skipped.push(reason),
+                    }
+                }
+            }
+            ("END", _) if current.is_some() && nested > 0 => nested -= 1,
+            _ => {
+                // 只收集事件本身的属性，忽略嵌套的 VALARM 等组件
+                if let (Some(props), 0) = (current.as_mut(), nested) {
+                    props.push(property);
+                }
+            }
+        }
+    }
+
+    (events, skipped)
+}
+
+fn build_event(props: Vec<IcsProperty>) -> Result<Option<ParsedEvent>, String> {
+    let find = |name: &str| props.iter().find(|p| p.name == name);
+    let text = |name: &str| find(name).map(|p| unescape_text(&p.value)).filter(|v| !v.is_empty());
+
+    let summary = text("SUMMARY").unwrap_or_else(|| "(No title)".to_string());
+
+    if find("STATUS").is_some_and(|p| p.value.eq_ignore_ascii_case("CANCELLED")) {
+        return Ok(None);
+    }
+
+    let Some((starts_at, all_day)) = find("DTSTART").and_then(|p| parse_ics_time(&p.value, &p.params)) else {
+        return Err(format!("\"{}\": missing or unsupported DTSTART", summary));
+    };
+
+    let ends_at = match (find("DTEND"), find("DURATION")) {
+        (Some(end), _) => parse_ics_time(&end.value, &end.params).map(|(dt, _)| dt),
+        (None, Some(duration)) => parse_ics_duration(&duration.value).map(|d| starts_at + d),
+        (None, None) => Some(if all_day { starts_at + Duration::days(1) } else { starts_at }),
+    };
+    let Some(ends_at) = ends_at.filter(|end| *end >= starts_at) else {
+        return Err(format!("\"{}\": invalid end time", summary));
+    };
+
+    let exdates = props.iter()
+        .filter(|p| p.name == "EXDATE")
+        .flat_map(|p| p.value.split(',').filter_map(|v| parse_ics_time(v, &p.params)).map(|(dt, _)| dt).collect::<Vec<_>>())
+        .collect();
+
+    let uid = find("UID")
+        .map(|p| p.value.trim().to_string())
+        .filter(|uid| !uid.is_empty())
+        .unwrap_or_else(|| content_hash(&format!("{}|{}", summary, starts_at.to_rfc3339())));
+
+    Ok(Some(ParsedEvent {
+        uid,
+        summary,
+        description: text("DESCRIPTION"),
+        location: text("LOCATION"),
+        starts_at,
+        ends_at,
+        all_day,
+        transparent: find("TRANSP").is_some_and(|p| p.value.eq_ignore_ascii_case("TRANSPARENT")),
+        recurrence: find("RRULE").map(|p| p.value.trim().to_uppercase()),
+        exdates,
+        recurrence_id: find("RECURRENCE-ID").and_then(|p| parse_ics_time(&p.value, &p.params)).map(|(dt, _)| dt),
+    }))
+}
+
+/// 修改过的单次重复（RECURRENCE-ID）作为独立事件保存，并从原规则中排除
+fn exclude_overrides(events: &mut [ParsedEvent]) {
+    let overrides: Vec<(String, DateTime<Utc>)> = events.iter()
+        .filter_map(|e| e.recurrence_id.map(|rid| (e.uid.clone(), rid)))
+        .collect();
+    for (uid, rid) in overrides {
+        if let Some(master) = events.iter_mut().find(|e| e.uid == uid && e.recurrence_id.is_none()) {
+            master.exdates.push(rid);
+        }
+    }
+}
+
+/// 用文件内容替换日历源的全部事件
+fn store_events(conn: &Connection, source_id: &str, mut events: Vec<ParsedEvent>) -> Result<usize, rusqlite::Error> {
+    conn.execute("DELETE FROM calendar_events WHERE source_id = ?", params![source_id])?;
+
+    exclude_overrides(&mut events);
+
+    for event in &events {
+        let exdates = event.exdates.iter().map(|d| d.to_rfc3339()).collect::<Vec<_>>().join(",");
+        conn.execute(
+            "INSERT INTO calendar_events (id, source_id, uid, summary, description, location, starts_at, ends_at,
+                                          all_day, transparent, recurrence, exdates)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                Uuid::new_v4().to_string(), source_id, event.uid, event.summary, event.description, event.location,
+                event.starts_at.to_rfc3339(), event.ends_at.to_rfc3339(), event.all_day as i32,
+                event.transparent as i32, event.recurrence, exdates
+            ]
+        )?;
+    }
+
+    Ok(events.len())
+}
+
+fn load_source(conn: &Connection, id: &str) -> Result<Option<CalendarSource>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT s.id, s.name, s.path, s.subscribed, s.imported_at,
+                (SELECT COUNT(*) FROM calendar_events e WHERE e.source_id = s.id)
+         FROM calendar_sources s WHERE s.id = ?",
+        params![id],
+        source_from_row
+    ).optional()
+}
+
+fn source_from_row(row: &rusqlite::Row) -> Result<CalendarSource, rusqlite::Error> {
+    Ok(CalendarSource {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        path: row.get(2)?,
+        subscribed: row.get::<_, i32>(3)? != 0,
+        imported_at: row.get::<_, String>(4)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(4, "imported_at".to_string(), rusqlite::types::Type::Text))?,
+        event_count: row.get(5)?,
+    })
+}
+
+/// 导入日历文件到指定的日历源，文件内容未变化且 `force` 为 false 时跳过
+fn import_calendar_file(conn: &mut Connection, source_id: &str, path: &Path, force: bool) -> Result<Option<CalendarImportReport>, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    if !text.contains("BEGIN:VCALENDAR") {
+        return Err(format!("{} is not an iCalendar file", path.display()));
+    }
+
+    let hash = content_hash(&text);
+    let previous: Option<String> = conn.query_row(
+        "SELECT content_hash FROM calendar_sources WHERE id = ?",
+        params![source_id],
+        |row| row.get(0)
+    ).optional().map_err(|e| e.to_string())?.flatten();
+    if !force && previous.as_deref() == Some(hash.as_str()) {
+        return Ok(None);
+    }
+
+    let (events, skipped) = parse_calendar(&text);
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let imported = store_events(&tx, source_id, events).map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE calendar_sources SET content_hash = ?, imported_at = ? WHERE id = ?",
+        params![hash, Utc::now().to_rfc3339(), source_id]
+    ).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let source = load_source(conn, source_id).map_err(|e| e.to_string())?
+        .ok_or("Calendar not found")?;
+
+    Ok(Some(CalendarImportReport { source, imported, skipped }))
+}
+
+/// 重新导入内容有变化的订阅日历
+pub fn refresh_subscribed_calendars(conn: &mut Connection) -> Result<Vec<CalendarImportReport>, String> {
+    let sources: Vec<(String, String)> = {
+        let mut stmt = conn.prepare("SELECT id, path FROM calendar_sources WHERE subscribed = 1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    let mut reports = Vec::new();
+    for (id, path) in sources {
+        match import_calendar_file(conn, &id, Path::new(&path), false) {
+            Ok(Some(report)) => reports.push(report),
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to refresh calendar {}: {}", path, e),
+        }
+    }
+
+    Ok(reports)
+}
+
+/// 启动后台任务，定期刷新订阅的日历文件
+pub fn spawn_calendar_refresh(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(StdDuration::from_secs(CALENDAR_REFRESH_SECS)).await;
+
+            let database = app.state::<Database>();
+            if let Ok(mut conn) = database.get_connection() {
+                if let Err(e) = refresh_subscribed_calendars(&mut conn) {
+                    log::warn!("Calendar refresh failed: {}", e);
+                }
+            }
+        }
+    });
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    // BYDAY 可能带序号（例如 1MO），这里只取星期部分
+    match value.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-' || c == '+') {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn add_months(date: NaiveDateTime, months: i64) -> Option<NaiveDateTime> {
+    let total = date.year() as i64 * 12 + date.month0() as i64 + months;
+    let (year, month0) = (total.div_euclid(12) as i32, total.rem_euclid(12) as u32);
+    NaiveDate::from_ymd_opt(year, month0 + 1, date.day()).map(|d| d.and_time(date.time()))
+}
+
+/// 展开事件在 [range_start, range_end) 内的各次发生时间。
+/// 支持 DAILY/WEEKLY/MONTHLY/YEARLY 以及 INTERVAL、COUNT、UNTIL 和每周的 BYDAY，按本地时间重复。
+fn expand_occurrences(event: &CalendarEvent, range_start: DateTime<Utc>, range_end: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let Some(rule) = &event.recurrence else {
+        return vec![event.starts_at];
+    };
+
+    let parts: HashMap<&str, &str> = rule.split(';').filter_map(|p| p.split_once('=')).collect();
+    let interval = parts.get("INTERVAL").and_then(|v| v.parse::<i64>().ok()).unwrap_or(1).max(1);
+    let count = parts.get("COUNT").and_then(|v| v.parse::<usize>().ok());
+    let until = parts.get("UNTIL").and_then(|v| parse_ics_time(v, &HashMap::new())).map(|(dt, _)| dt);
+    let by_day: Vec<Weekday> = parts.get("BYDAY")
+        .map(|v| v.split(',').filter_map(parse_weekday).collect())
+        .unwrap_or_default();
+
+    let start_local = event.starts_at.with_timezone(&Local).naive_local();
+    let mut occurrences = Vec::new();
+    let mut generated = 0;
+
+    let mut emit = |naive: NaiveDateTime, occurrences: &mut Vec<DateTime<Utc>>| -> bool {
+        let Some(at) = local_to_utc(naive) else { return true };
+        if at < event.starts_at {
+            return true;
+        }
+        if until.is_some_and(|until| at > until) || count.is_some_and(|c| generated >= c) || at >= range_end {
+            return false;
+        }
+        generated += 1;
+        if !event.exdates.contains(&at) && at + (event.ends_at - event.starts_at) > range_start {
+            occurrences.push(at);
+        }
+        true
+    };
+
+    let freq = parts.get("FREQ").copied().unwrap_or("");
+
+    // 没有 COUNT 时不需要从头计数，直接跳到查询范围前的周期，早年开始的系列也能展开到当前范围。
+    // 多退一个周期，避免每周的 BYDAY 落在周期起点之前
+    let first_step = match count {
+        Some(_) => 0,
+        None => {
+            let lead_local = (range_start - (event.ends_at - event.starts_at)).with_timezone(&Local).naive_local();
+            let lead = lead_local - start_local;
+            let months = (lead_local.year() as i64 * 12 + lead_local.month0() as i64)
+                - (start_local.year() as i64 * 12 + start_local.month0() as i64);
+            let periods = match freq {
+                "DAILY" => lead.num_days(),
+                "WEEKLY" => lead.num_weeks(),
+                "MONTHLY" => months,
+                "YEARLY" => months / 12,
+                _ => 0,
+            };
+            (periods / interval - 1).max(0)
+        }
+    };
+    let max_steps = MAX_OCCURRENCES.max(count.unwrap_or(0)) as i64;
+
+    for step in first_step..first_step + max_steps {
+        let base = match freq {
+            "DAILY" => Some(start_local + Duration::days(step * interval)),
+            "WEEKLY" => Some(start_local + Duration::weeks(step * interval)),
+            "MONTHLY" => add_months(start_local, step * interval),
+            "YEARLY" => add_months(start_local, step * interval * 12),
+            _ => return vec![event.starts_at],
+        };
+        // 月份没有对应日期（例如 31 日）时跳过这一次
+        let Some(base) = base else { continue };
+
+        let keep_going = if freq == "WEEKLY" && !by_day.is_empty() {
+            let week_start = base - Duration::days(base.weekday().num_days_from_monday() as i64);
+            let mut days: Vec<NaiveDateTime> = by_day.iter()
+                .map(|day| week_start + Duration::days(day.num_days_from_monday() as i64))
+                .collect();
+            days.sort();
+            days.into_iter().all(|day| emit(day, &mut occurrences))
+        } else {
+            emit(base, &mut occurrences)
+        };
+
+        if !keep_going {
+            break;
+        }
+    }
+
+    occurrences
+}
+
+fn load_events_in_range(conn: &Connection, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<CalendarEvent>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, source_id, uid, summary, description, location, starts_at, ends_at, all_day, transparent,
+                recurrence, exdates
+         FROM calendar_events
+         WHERE starts_at < ?2 AND (ends_at > ?1 OR recurrence IS NOT NULL)
+         ORDER BY starts_at"
+    )?;
+
+    let event_iter = stmt.query_map(params![start.to_rfc3339(), end.to_rfc3339()], |row| {
+        let exdates: Option<String> = row.get(11)?;
+        Ok(CalendarEvent {
+            id: row.get(0)?,
+            source_id: row.get(1)?,
+            uid: row.get(2)?,
+            summary: row.get(3)?,
+            description: row.get(4)?,
+            location: row.get(5)?,
+            starts_at: row.get::<_, String>(6)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(6, "starts_at".to_string(), rusqlite::types::Type::Text))?,
+            ends_at: row.get::<_, String>(7)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(7, "ends_at".to_string(), rusqlite::types::Type::Text))?,
+            all_day: row.get::<_, i32>(8)? != 0,
+            transparent: row.get::<_, i32>(9)? != 0,
+            recurrence: row.get(10)?,
+            exdates: exdates.unwrap_or_default()
+                .split(',')
+                .filter_map(|d| d.parse().ok())
+                .collect(),
+        })
+    })?;
+
+    let mut events = Vec::new();
+    for event in event_iter {
+        let event = event?;
+        let length = event.ends_at - event.starts_at;
+        for at in expand_occurrences(&event, start, end) {
+            events.push(CalendarEvent {
+                starts_at: at,
+                ends_at: at + length,
+                ..event.clone()
+            });
+        }
+    }
+    events.sort_by_key(|e| e.starts_at);
+
+    Ok(events)
+}
+
+pub fn local_midnight(date: NaiveDate) -> Result<DateTime<Utc>, String> {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(local_to_utc)
+        .ok_or_else(|| "Invalid local time".to_string())
+}
+
+fn parse_clock(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| format!("Invalid time {}, expected HH:MM", value))
+}
+
+/// 设置中的默认工作时间，未设置时为 09:00-18:00
+pub fn default_work_hours(conn: &Connection) -> Result<(NaiveTime, NaiveTime), String> {
+    let work_start = db::get_setting(conn, AGENDA_WORK_START_SETTING).map_err(|e| e.to_string())?
+        .unwrap_or_else(|| DEFAULT_WORK_START.to_string());
+    let work_end = db::get_setting(conn, AGENDA_WORK_END_SETTING).map_err(|e| e.to_string())?
+        .unwrap_or_else(|| DEFAULT_WORK_END.to_string());
+    Ok((parse_clock(&work_start)?, parse_clock(&work_end)?))
+}
+
+/// 工作时间内去掉忙碌时段后剩下的空闲时段
+fn free_slots(day_start: DateTime<Utc>, day_end: DateTime<Utc>, busy: &mut [(DateTime<Utc>, DateTime<Utc>)]) -> Vec<FreeSlot> {
+    busy.sort();
+
+    let mut slots = Vec::new();
+    let mut cursor = day_start;
+    for (start, end) in busy.iter() {
+        if *end <= cursor || *start >= day_end {
+            continue;
+        }
+        if *start > cursor {
+            slots.push(FreeSlot { starts_at: cursor, ends_at: *start, minutes: (*start - cursor).num_minutes() });
+        }
+        cursor = cursor.max(*end);
+    }
+    if cursor < day_end {
+        slots.push(FreeSlot { starts_at: cursor, ends_at: day_end, minutes: (day_end - cursor).num_minutes() });
+    }
+
+    slots
+}
+
+fn build_agenda(
+    conn: &Connection,
+    start_date: NaiveDate,
+    days: i64,
+    work_start: NaiveTime,
+    work_end: NaiveTime,
+) -> Result<Vec<AgendaDay>, String> {
+    let range_start = local_midnight(start_date)?;
+    let range_end = local_midnight(start_date + Duration::days(days))?;
+
+    let events = load_events_in_range(conn, range_start, range_end).map_err(|e| e.to_string())?;
+
+    let mut task_stmt = conn.prepare(
+        "SELECT id, title, due_date, is_completed, project_id FROM tasks
+         WHERE deleted_at IS NULL AND due_date >= ? AND due_date < ?
+         ORDER BY due_date, priority"
+    ).map_err(|e| e.to_string())?;
+    let tasks = task_stmt.query_map(params![range_start.to_rfc3339(), range_end.to_rfc3339()], |row| {
+        let due: DateTime<Utc> = row.get::<_, String>(2)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(2, "due_date".to_string(), rusqlite::types::Type::Text))?;
+        // 截止时间为本地零点时视为全天
+        let all_day = Some(due.with_timezone(&Local).time()) == NaiveTime::from_hms_opt(0, 0, 0);
+        Ok(AgendaItem {
+            kind: "task".to_string(),
+            id: row.get(0)?,
+            title: row.get(1)?,
+            starts_at: due,
+            ends_at: None,
+            all_day,
+            is_completed: Some(row.get::<_, i32>(3)? != 0),
+            project_id: row.get(4)?,
+        })
+    }).map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut session_stmt = conn.prepare(
+        "SELECT s.id, COALESCE(t.title, 'Pomodoro'), s.started_at, s.ended_at, s.duration, s.is_completed, t.project_id
+         FROM pomodoro_sessions s
+         LEFT JOIN tasks t ON t.id = s.task_id
+         WHERE s.started_at >= ? AND s.started_at < ? AND s.phase = 'work'
+         ORDER BY s.started_at"
+    ).map_err(|e| e.to_string())?;
+    let sessions = session_stmt.query_map(params![range_start.to_rfc3339(), range_end.to_rfc3339()], |row| {
+        let started_at: DateTime<Utc> = row.get::<_, String>(2)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(2, "started_at".to_string(), rusqlite::types::Type::Text))?;
+        let ended_at: Option<DateTime<Utc>> = row.get::<_, Option<String>>(3)?.map(|s| s.parse()).transpose()
+            .map_err(|_| rusqlite::Error::InvalidColumnType(3, "ended_at".to_string(), rusqlite::types::Type::Text))?;
+        let duration: i64 = row.get(4)?;
+        Ok(AgendaItem {
+            kind: "pomodoro".to_string(),
+            id: row.get(0)?,
+            title: row.get(1)?,
+            starts_at: started_at,
+            ends_at: Some(ended_at.unwrap_or(started_at + Duration::seconds(duration))),
+            all_day: false,
+            is_completed: Some(row.get::<_, i32>(5)? != 0),
+            project_id: row.get(6)?,
+        })
+    }).map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut agenda = Vec::new();
+    for offset in 0..days {
+        let date = start_date + Duration::days(offset);
+        let day_start = local_midnight(date)?;
+        let day_end = local_midnight(date + Duration::days(1))?;
+        let overlaps = |start: DateTime<Utc>, end: DateTime<Utc>| start < day_end && (end > day_start || start >= day_start);
+
+        let mut items: Vec<AgendaItem> = events.iter()
+            .filter(|e| overlaps(e.starts_at, e.ends_at))
+            .map(|e| AgendaItem {
+                kind: "event".to_string(),
+                id: e.id.clone(),
+                title: e.summary.clone(),
+                starts_at: e.starts_at,
+                ends_at: Some(e.ends_at),
+                all_day: e.all_day,
+                is_completed: None,
+                project_id: None,
+            })
+            .collect();
+        items.extend(tasks.iter().filter(|t| t.starts_at >= day_start && t.starts_at < day_end).cloned());
+        items.extend(sessions.iter().filter(|s| s.starts_at >= day_start && s.starts_at < day_end).cloned());
+        items.sort_by(|a, b| b.all_day.cmp(&a.all_day).then(a.starts_at.cmp(&b.starts_at)));
+
+        // 空闲时间只扣除占用时间的非全天事件
+        let mut busy: Vec<(DateTime<Utc>, DateTime<Utc>)> = events.iter()
+            .filter(|e| !e.all_day && !e.transparent && overlaps(e.starts_at, e.ends_at))
+            .map(|e| (e.starts_at, e.ends_at))
+            .collect();
+        let work_from = local_to_utc(date.and_time(work_start)).ok_or("Invalid local time")?;
+        let work_to = local_to_utc(date.and_time(work_end)).ok_or("Invalid local time")?;
+        let free = if work_to > work_from { free_slots(work_from, work_to, &mut busy) } else { Vec::new() };
+
+        agenda.push(AgendaDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            free_minutes: free.iter().map(|slot| slot.minutes).sum(),
+            items,
+            free_slots: free,
+        });
+    }
+
+    Ok(agenda)
+}
+
+/// 导入本地 .ics 文件为只读日历。`subscribe` 为 true 时文件变化后会自动重新导入。
+/// 再次导入同一路径时替换原有事件。
+#[tauri::command]
+pub async fn import_calendar(
+    path: String,
+    name: Option<String>,
+    subscribe: Option<bool>,
+    db: State<'_, Database>
+) -> Result<CalendarImportReport, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let file = Path::new(&path);
+    if !file.is_file() {
+        return Err(format!("File not found: {}", path));
+    }
+
+    let existing: Option<String> = conn.query_row(
+        "SELECT id FROM calendar_sources WHERE path = ?",
+        params![path],
+        |row| row.get(0)
+    ).optional().map_err(|e| e.to_string())?;
+
+    let name = name.unwrap_or_else(|| {
+        file.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| path.clone())
+    });
+    let subscribed = subscribe.unwrap_or(false) as i32;
+
+    let source_id = match existing {
+        Some(id) => {
+            conn.execute(
+                "UPDATE calendar_sources SET name = ?, subscribed = ? WHERE id = ?",
+                params![name, subscribed, id]
+            ).map_err(|e| e.to_string())?;
+            id
+        }
+        None => {
+            let id = Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO calendar_sources (id, name, path, subscribed, content_hash, imported_at)
+                 VALUES (?, ?, ?, ?, NULL, ?)",
+                params![id, name, path, subscribed, Utc::now().to_rfc3339()]
+            ).map_err(|e| e.to_string())?;
+            id
+        }
+    };
+
+    import_calendar_file(&mut conn, &source_id, file, true)?
+        .ok_or_else(|| "Calendar import failed".to_string())
+}
+
+#[tauri::command]
+pub async fn get_calendars(db: State<'_, Database>) -> Result<Vec<CalendarSource>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn.prepare(
+        "SELECT s.id, s.name, s.path, s.subscribed, s.imported_at,
+                (SELECT COUNT(*) FROM calendar_events e WHERE e.source_id = s.id)
+         FROM calendar_sources s ORDER BY s.name"
+    ).map_err(|e| e.to_string())?;
+
+    let source_iter = stmt.query_map([], source_from_row).map_err(|e| e.to_string())?;
+
+    let mut sources = Vec::new();
+    for source in source_iter {
+        sources.push(source.map_err(|e| e.to_string())?);
+    }
+
+    Ok(sources)
+}
+
+#[tauri::command]
+pub async fn refresh_calendars(db: State<'_, Database>) -> Result<Vec<CalendarImportReport>, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    refresh_subscribed_calendars(&mut conn)
+}
+
+#[tauri::command]
+pub async fn remove_calendar(id: String, db: State<'_, Database>) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    conn.execute("DELETE FROM calendar_events WHERE source_id = ?", params![id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM calendar_sources WHERE id = ?", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// 查询时间范围内的日历事件，重复事件展开为各次发生
+#[tauri::command]
+pub async fn get_calendar_events(
+    start: String,
+    end: String,
+    db: State<'_, Database>
+) -> Result<Vec<CalendarEvent>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let start: DateTime<Utc> = start.parse().map_err(|_| "Invalid start time".to_string())?;
+    let end: DateTime<Utc> = end.parse().map_err(|_| "Invalid end time".to_string())?;
+
+    load_events_in_range(&conn, start, end).map_err(|e| e.to_string())
+}
+
+/// 按本地日期生成议程：日历事件、按截止时间排列的任务和番茄钟记录，以及工作时间内的空闲时段。
+/// `work_start`/`work_end` 为 HH:MM，未指定时使用设置中的工作时间。
+#[tauri::command]
+pub async fn get_agenda(
+    start_date: String,
+    days: Option<i64>,
+    work_start: Option<String>,
+    work_end: Option<String>,
+    db: State<'_, Database>
+) -> Result<Vec<AgendaDay>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let start_date = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
+        .map_err(|_| "Invalid date format, expected YYYY-MM-DD")?;
+    let days = days.unwrap_or(1).clamp(1, 62);
+
+    let work_start = match work_start {
+        Some(value) => value,
+        None => db::get_setting(&conn, AGENDA_WORK_START_SETTING).map_err(|e| e.to_string())?
+            .unwrap_or_else(|| DEFAULT_WORK_START.to_string()),
+    };
+    let work_end = match work_end {
+        Some(value) => value,
+        None => db::get_setting(&conn, AGENDA_WORK_END_SETTING).map_err(|e| e.to_string())?
+            .unwrap_or_else(|| DEFAULT_WORK_END.to_string()),
+    };
+
+    build_agenda(&conn, start_date, days, parse_clock(&work_start)?, parse_clock(&work_end)?)
+}
+
+/// 设置计算空闲时间使用的默认工作时间（HH:MM）
+#[tauri::command]
+pub async fn set_agenda_work_hours(work_start: String, work_end: String, db: State<'_, Database>) -> Result<(), String> {
+    if parse_clock(&work_start)? >= parse_clock(&work_end)? {
+        return Err("Work start must be before work end".to_string());
+    }
+
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    db::set_setting(&conn, AGENDA_WORK_START_SETTING, &work_start).map_err(|e| e.to_string())?;
+    db::set_setting(&conn, AGENDA_WORK_END_SETTING, &work_end).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        local_to_utc(NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()).unwrap()
+    }
+
+    fn event(starts_at: DateTime<Utc>, rule: &str) -> CalendarEvent {
+        CalendarEvent {
+            id: "event".to_string(),
+            source_id: "source".to_string(),
+            uid: "uid".to_string(),
+            summary: "Standup".to_string(),
+            description: None,
+            location: None,
+            starts_at,
+            ends_at: starts_at + Duration::minutes(30),
+            all_day: false,
+            transparent: false,
+            recurrence: Some(rule.to_string()),
+            exdates: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn daily_rule_uses_interval_and_count() {
+        let event = event(local(2024, 1, 1, 9), "FREQ=DAILY;INTERVAL=2;COUNT=3");
+        let occurrences = expand_occurrences(&event, local(2024, 1, 1, 0), local(2024, 2, 1, 0));
+        assert_eq!(occurrences, vec![local(2024, 1, 1, 9), local(2024, 1, 3, 9), local(2024, 1, 5, 9)]);
+    }
+
+    #[test]
+    fn daily_rule_stops_at_until() {
+        let event = event(local(2024, 1, 1, 9), "FREQ=DAILY;UNTIL=20240103");
+        let occurrences = expand_occurrences(&event, local(2024, 1, 1, 0), local(2024, 2, 1, 0));
+        assert_eq!(occurrences, vec![local(2024, 1, 1, 9), local(2024, 1, 2, 9)]);
+    }
+
+    #[test]
+    fn weekly_rule_expands_by_day() {
+        // 2024-01-01 是周一
+        let event = event(local(2024, 1, 1, 9), "FREQ=WEEKLY;BYDAY=MO,WE");
+        let occurrences = expand_occurrences(&event, local(2024, 1, 1, 0), local(2024, 1, 15, 0));
+        assert_eq!(occurrences, vec![local(2024, 1, 1, 9), local(2024, 1, 3, 9), local(2024, 1, 8, 9), local(2024, 1, 10, 9)]);
+    }
+
+    #[test]
+    fn monthly_rule_skips_missing_days() {
+        let event = event(local(2024, 1, 31, 9), "FREQ=MONTHLY");
+        let occurrences = expand_occurrences(&event, local(2024, 1, 1, 0), local(2024, 6, 1, 0));
+        assert_eq!(occurrences, vec![local(2024, 1, 31, 9), local(2024, 3, 31, 9), local(2024, 5, 31, 9)]);
+    }
+
+    #[test]
+    fn old_series_still_reaches_range() {
+        let daily = event(local(2000, 1, 1, 9), "FREQ=DAILY");
+        let occurrences = expand_occurrences(&daily, local(2030, 6, 1, 0), local(2030, 6, 3, 0));
+        assert_eq!(occurrences, vec![local(2030, 6, 1, 9), local(2030, 6, 2, 9)]);
+
+        let weekly = event(local(2000, 1, 3, 9), "FREQ=WEEKLY;BYDAY=MO,FR");
+        let occurrences = expand_occurrences(&weekly, local(2030, 6, 3, 0), local(2030, 6, 10, 0));
+        assert_eq!(occurrences, vec![local(2030, 6, 3, 9), local(2030, 6, 7, 9)]);
+    }
+
+    #[test]
+    fn occurrence_overlapping_range_start_is_included() {
+        let event = event(local(2024, 1, 1, 9), "FREQ=DAILY");
+        let occurrences = expand_occurrences(&event, local(2024, 1, 5, 9) + Duration::minutes(10), local(2024, 1, 6, 0));
+        assert_eq!(occurrences, vec![local(2024, 1, 5, 9)]);
+    }
+
+    #[test]
+    fn exdates_are_excluded() {
+        let mut event = event(local(2024, 1, 1, 9), "FREQ=DAILY;COUNT=4");
+        event.exdates = vec![local(2024, 1, 2, 9)];
+        let occurrences = expand_occurrences(&event, local(2024, 1, 1, 0), local(2024, 2, 1, 0));
+        // 被排除的日期仍计入 COUNT
+        assert_eq!(occurrences, vec![local(2024, 1, 1, 9), local(2024, 1, 3, 9), local(2024, 1, 4, 9)]);
+    }
+
+    #[test]
+    fn recurrence_id_excludes_occurrence_from_master() {
+        let text = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\nUID:weekly\r\nSUMMARY:Sync\r\nDTSTART:20240101T090000Z\r\nDURATION:PT30M\r\n\
+            RRULE:FREQ=WEEKLY\r\nEXDATE:20240115T090000Z\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:weekly\r\nSUMMARY:Sync (moved)\r\nRECURRENCE-ID:20240108T090000Z\r\n\
+            DTSTART:20240109T100000Z\r\nDTEND:20240109T103000Z\r\nEND:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let (mut events, skipped) = parse_calendar(text);
+        assert!(skipped.is_empty());
+        assert_eq!(events.len(), 2);
+
+        exclude_overrides(&mut events);
+
+        let at = |value: &str| value.parse::<DateTime<Utc>>().unwrap();
+        let master = events.iter().find(|e| e.recurrence_id.is_none()).unwrap();
+        assert_eq!(master.exdates, vec![at("2024-01-15T09:00:00Z"), at("2024-01-08T09:00:00Z")]);
+        assert_eq!(master.ends_at - master.starts_at, Duration::minutes(30));
+
+        let moved = events.iter().find(|e| e.recurrence_id.is_some()).unwrap();
+        assert_eq!(moved.recurrence_id, Some(at("2024-01-08T09:00:00Z")));
+        assert_eq!(moved.starts_at, at("2024-01-09T10:00:00Z"));
+        assert_eq!(moved.recurrence, None);
+    }
+
+    #[test]
+    fn parses_ics_durations() {
+        assert_eq!(parse_ics_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_ics_duration("P1D"), Some(Duration::days(1)));
+        assert_eq!(parse_ics_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_ics_duration("-P1W"), Some(-Duration::weeks(1)));
+        assert_eq!(parse_ics_duration("+PT15S"), Some(Duration::seconds(15)));
+        assert_eq!(parse_ics_duration("PT1D"), None);
+        assert_eq!(parse_ics_duration("1H"), None);
+    }
+}
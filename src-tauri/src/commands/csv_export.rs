@@ -0,0 +1,443 @@
+use crate::models::CsvExportReport;
+use crate::db::Database;
+use crate::commands::importers::parse_loose_date_with_time;
+use tauri::State;
+use rusqlite::{params, Connection};
+use chrono::{DateTime, Duration, Local, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// 各类导出可选的列，未指定列时按此顺序全部导出
+pub const TASK_CSV_COLUMNS: &[&str] = &[
+    "id", "title", "description", "project", "tags", "status", "priority", "due_date",
+    "created_at", "completed_at", "depth", "parent_id", "parent_title",
+];
+pub const PROJECT_CSV_COLUMNS: &[&str] = &[
+    "id", "name", "status", "due_date", "total_tasks", "completed_tasks", "pending_tasks",
+    "overdue_tasks", "completion_rate", "pomodoros", "focus_minutes", "created_at",
+];
+pub const POMODORO_CSV_COLUMNS: &[&str] = &[
+    "id", "phase", "started_at", "ended_at", "duration_minutes", "paused_minutes", "is_completed", "abandoned",
+    "abandon_reason", "interruptions", "task", "project", "notes",
+];
+
+const UTF8_BOM: &str = "\u{feff}";
+
+/// 导出时间范围，结束日期如果只写了日期则包含当天
+struct DateRange {
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+}
+
+impl DateRange {
+    fn parse(start: Option<&str>, end: Option<&str>) -> Result<Self, String> {
+        let parse = |value: &str| parse_loose_date_with_time(value).ok_or_else(|| format!("Invalid date: {}", value));
+
+        let start = start.filter(|s| !s.trim().is_empty()).map(parse).transpose()?.map(|(start, _)| start);
+        let end = match end.filter(|s| !s.trim().is_empty()).map(parse).transpose()? {
+            Some((end, false)) => Some(end + Duration::days(1)),
+            Some((end, true)) => Some(end),
+            None => None,
+        };
+
+        if let (Some(start), Some(end)) = (start, end) {
+            if start >= end {
+                return Err("start must be before end".to_string());
+            }
+        }
+
+        Ok(DateRange { start, end })
+    }
+
+    fn contains(&self, time: &DateTime<Utc>) -> bool {
+        self.start.map_or(true, |start| *time >= start) && self.end.map_or(true, |end| *time < end)
+    }
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    value.parse().ok()
+}
+
+/// 表格里使用本地时间，Excel 能直接识别这种格式
+fn local_time(value: Option<&str>) -> String {
+    value.and_then(parse_time)
+        .map(|dt| dt.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+fn select_columns(all: &[&str], columns: Option<Vec<String>>) -> Result<Vec<usize>, String> {
+    let columns = match columns {
+        Some(columns) if !columns.is_empty() => columns,
+        _ => return Ok((0..all.len()).collect()),
+    };
+
+    columns.iter()
+        .map(|name| all.iter().position(|c| c == name).ok_or_else(|| format!("Unknown column: {}", name)))
+        .collect()
+}
+
+/// 按所选列写出 CSV，`rows` 中每行的字段顺序与 `all` 一致
+fn write_csv(path: &str, all: &[&str], selected: &[usize], rows: &[Vec<String>], bom: bool) -> Result<CsvExportReport, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(selected.iter().map(|&i| all[i])).map_err(|e| e.to_string())?;
+    for row in rows {
+        writer.write_record(selected.iter().map(|&i| row[i].as_str())).map_err(|e| e.to_string())?;
+    }
+    let data = writer.into_inner().map_err(|e| e.to_string())?;
+
+    let target = Path::new(path);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut bytes = Vec::with_capacity(data.len() + UTF8_BOM.len());
+    if bom {
+        bytes.extend_from_slice(UTF8_BOM.as_bytes());
+    }
+    bytes.extend_from_slice(&data);
+    fs::write(target, bytes).map_err(|e| e.to_string())?;
+
+    Ok(CsvExportReport {
+        path: path.to_string(),
+        rows: rows.len(),
+        columns: selected.iter().map(|&i| all[i].to_string()).collect(),
+    })
+}
+
+fn task_tag_names(conn: &Connection) -> Result<HashMap<String, Vec<String>>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT tt.task_id, t.name FROM task_tags tt JOIN tags t ON t.id = tt.tag_id ORDER BY t.name"
+    )?;
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+        let (task_id, name) = row?;
+        tags.entry(task_id).or_default().push(name);
+    }
+    Ok(tags)
+}
+
+/// 子任务层级，顶层任务为 0
+fn task_depth(id: &str, parents: &HashMap<String, String>) -> usize {
+    let mut depth = 0;
+    let mut current = id;
+    while let Some(parent) = parents.get(current) {
+        depth += 1;
+        current = parent;
+        // 防止异常数据中的循环引用
+        if depth > parents.len() {
+            break;
+        }
+    }
+    depth
+}
+
+/// 导出任务：任务在时间范围内创建或完成都会包含在内。
+fn build_task_rows(
+    conn: &Connection,
+    project_id: Option<&str>,
+    include_completed: bool,
+    range: &DateRange,
+) -> Result<Vec<Vec<String>>, rusqlite::Error> {
+    let tags = task_tag_names(conn)?;
+
+    let mut stmt = conn.prepare("SELECT id, parent_id, title FROM tasks")?;
+    let mut parents = HashMap::new();
+    let mut titles = HashMap::new();
+    for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, String>(2)?)))? {
+        let (id, parent_id, title) = row?;
+        if let Some(parent_id) = parent_id {
+            parents.insert(id.clone(), parent_id);
+        }
+        titles.insert(id, title);
+    }
+
+    // 子任务不一定设置了 project_id，沿 parent_id 从顶层任务继承所属项目
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE tree(id, project_id) AS (
+            SELECT id, project_id FROM tasks
+            WHERE parent_id IS NULL OR parent_id NOT IN (SELECT id FROM tasks)
+            UNION
+            SELECT t.id, COALESCE(t.project_id, tree.project_id) FROM tasks t INNER JOIN tree ON t.parent_id = tree.id
+         )
+         SELECT t.id, t.title, t.description, p.name, t.is_completed, t.priority, t.due_date, t.created_at, t.parent_id,
+                COALESCE(t.completed_at, t.updated_at)
+         FROM tasks t
+         INNER JOIN tree ON tree.id = t.id
+         LEFT JOIN projects p ON p.id = tree.project_id AND p.deleted_at IS NULL
+         WHERE t.deleted_at IS NULL
+           AND (?1 IS NULL OR tree.project_id = ?1)
+           AND (?2 OR t.is_completed = 0)
+         ORDER BY p.name, t.position, t.created_at"
+    )?;
+
+    let mut rows = Vec::new();
+    let task_iter = stmt.query_map(params![project_id, include_completed], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, i32>(4)? != 0,
+            row.get::<_, i32>(5)?,
+            row.get::<_, Option<String>>(6)?,
+            row.get::<_, String>(7)?,
+            row.get::<_, Option<String>>(8)?,
+            row.get::<_, String>(9)?,
+        ))
+    })?;
+
+    for task in task_iter {
+        let (id, title, description, project, is_completed, priority, due_date, created_at, parent_id, completed_at) = task?;
+
+        // 没有完成时间的旧数据以最后修改时间作为完成时间
+        let completed_at = if is_completed { Some(completed_at) } else { None };
+
+        let in_range = parse_time(&created_at).is_some_and(|t| range.contains(&t))
+            || completed_at.as_deref().and_then(parse_time).is_some_and(|t| range.contains(&t));
+        if !in_range {
+            continue;
+        }
+
+        let parent_title = parent_id.as_ref().and_then(|p| titles.get(p)).cloned().unwrap_or_default();
+        rows.push(vec![
+            id.clone(),
+            title,
+            description.unwrap_or_default(),
+            project.unwrap_or_default(),
+            tags.get(&id).map(|t| t.join(", ")).unwrap_or_default(),
+            if is_completed { "completed" } else { "pending" }.to_string(),
+            priority.to_string(),
+            local_time(due_date.as_deref()),
+            local_time(Some(&created_at)),
+            local_time(completed_at.as_deref()),
+            task_depth(&id, &parents).to_string(),
+            parent_id.unwrap_or_default(),
+            parent_title,
+        ]);
+    }
+
+    Ok(rows)
+}
+
+/// 按项目汇总统计。任务数只计入时间范围内创建的任务，番茄钟只计入范围内开始的会话。
+fn build_project_rows(conn: &Connection, range: &DateRange) -> Result<Vec<Vec<String>>, rusqlite::Error> {
+    let now = Utc::now();
+
+    #[derive(Default)]
+    struct Counts {
+        total: i64,
+        completed: i64,
+        overdue: i64,
+        pomodoros: i64,
+        focus_seconds: i64,
+    }
+    let mut counts: HashMap<String, Counts> = HashMap::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT project_id, is_completed, due_date, created_at FROM tasks
+         WHERE project_id IS NOT NULL AND deleted_at IS NULL"
+    )?;
+    let task_iter = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)? != 0, row.get::<_, Option<String>>(2)?, row.get::<_, String>(3)?))
+    })?;
+    for task in task_iter {
+        let (project_id, is_completed, due_date, created_at) = task?;
+        if !parse_time(&created_at).is_some_and(|t| range.contains(&t)) {
+            continue;
+        }
+        let entry = counts.entry(project_id).or_default();
+        entry.total += 1;
+        if is_completed {
+            entry.completed += 1;
+        } else if due_date.as_deref().and_then(parse_time).is_some_and(|due| due < now) {
+            entry.overdue += 1;
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT t.project_id, s.started_at, s.duration FROM pomodoro_sessions s
+         JOIN tasks t ON t.id = s.task_id
+         WHERE t.project_id IS NOT NULL AND s.is_completed = 1 AND s.phase = 'work'"
+    )?;
+    let session_iter = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+    })?;
+    for session in session_iter {
+        let (project_id, started_at, duration) = session?;
+        if !parse_time(&started_at).is_some_and(|t| range.contains(&t)) {
+            continue;
+        }
+        let entry = counts.entry(project_id).or_default();
+        entry.pomodoros += 1;
+        entry.focus_seconds += duration;
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name, status, due_date, created_at FROM projects WHERE deleted_at IS NULL ORDER BY name"
+    )?;
+    let project_iter = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, String>(4)?,
+        ))
+    })?;
+
+    let mut rows = Vec::new();
+    for project in project_iter {
+        let (id, name, status, due_date, created_at) = project?;
+        let stats = counts.remove(&id).unwrap_or_default();
+        let completion_rate = if stats.total > 0 {
+            format!("{:.1}%", stats.completed as f64 * 100.0 / stats.total as f64)
+        } else {
+            String::new()
+        };
+
+        rows.push(vec![
+            id,
+            name,
+            status,
+            local_time(due_date.as_deref()),
+            stats.total.to_string(),
+            stats.completed.to_string(),
+            (stats.total - stats.completed).to_string(),
+            stats.overdue.to_string(),
+            completion_rate,
+            stats.pomodoros.to_string(),
+            (stats.focus_seconds / 60).to_string(),
+            local_time(Some(&created_at)),
+        ]);
+    }
+
+    Ok(rows)
+}
+
+/// 原始番茄钟会话记录，按开始时间筛选
+fn build_pomodoro_rows(conn: &Connection, range: &DateRange) -> Result<Vec<Vec<String>>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT s.id, s.phase, s.started_at, s.ended_at, s.duration, s.is_completed, t.title, p.name, s.notes,
+                s.paused_seconds, s.abandoned, s.abandon_reason,
+                (SELECT COUNT(*) FROM pomodoro_interruptions i WHERE i.session_id = s.id AND i.kind <> 'pause')
+         FROM pomodoro_sessions s
+         LEFT JOIN tasks t ON t.id = s.task_id
+         LEFT JOIN projects p ON p.id = t.project_id AND p.deleted_at IS NULL
+         ORDER BY s.started_at"
+    )?;
+    let session_iter = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, i64>(4)?,
+            row.get::<_, i32>(5)? != 0,
+            row.get::<_, Option<String>>(6)?,
+            row.get::<_, Option<String>>(7)?,
+            row.get::<_, Option<String>>(8)?,
+            row.get::<_, i64>(9)?,
+            row.get::<_, i32>(10)? != 0,
+            row.get::<_, Option<String>>(11)?,
+            row.get::<_, i64>(12)?,
+        ))
+    })?;
+
+    let mut rows = Vec::new();
+    for session in session_iter {
+        let (id, phase, started_at, ended_at, duration, is_completed, task, project, notes, paused, abandoned, abandon_reason, interruptions) = session?;
+        if !parse_time(&started_at).is_some_and(|t| range.contains(&t)) {
+            continue;
+        }
+
+        rows.push(vec![
+            id,
+            phase,
+            local_time(Some(&started_at)),
+            local_time(ended_at.as_deref()),
+            format!("{:.1}", duration as f64 / 60.0),
+            format!("{:.1}", paused as f64 / 60.0),
+            if is_completed { "yes" } else { "no" }.to_string(),
+            if abandoned { "yes" } else { "no" }.to_string(),
+            abandon_reason.unwrap_or_default(),
+            interruptions.to_string(),
+            task.unwrap_or_default(),
+            project.unwrap_or_default(),
+            notes.unwrap_or_default(),
+        ]);
+    }
+
+    Ok(rows)
+}
+
+/// 返回某类导出可选的列名：tasks / projects / pomodoros
+#[tauri::command]
+pub async fn get_csv_columns(kind: String) -> Result<Vec<String>, String> {
+    let columns = match kind.as_str() {
+        "tasks" => TASK_CSV_COLUMNS,
+        "projects" => PROJECT_CSV_COLUMNS,
+        "pomodoros" => POMODORO_CSV_COLUMNS,
+        _ => return Err(format!("Unknown export kind: {}", kind)),
+    };
+    Ok(columns.iter().map(|c| c.to_string()).collect())
+}
+
+/// 导出任务为 CSV。`bom` 为 true 时写入 UTF-8 BOM，方便中文版 Excel 直接打开
+#[tauri::command]
+pub async fn export_tasks_csv(
+    path: String,
+    columns: Option<Vec<String>>,
+    start: Option<String>,
+    end: Option<String>,
+    project_id: Option<String>,
+    include_completed: Option<bool>,
+    bom: Option<bool>,
+    db: State<'_, Database>
+) -> Result<CsvExportReport, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let selected = select_columns(TASK_CSV_COLUMNS, columns)?;
+    let range = DateRange::parse(start.as_deref(), end.as_deref())?;
+    let rows = build_task_rows(&conn, project_id.as_deref(), include_completed.unwrap_or(true), &range)
+        .map_err(|e| e.to_string())?;
+
+    write_csv(&path, TASK_CSV_COLUMNS, &selected, &rows, bom.unwrap_or(false))
+}
+
+/// 导出每个项目的统计数据为 CSV
+#[tauri::command]
+pub async fn export_project_stats_csv(
+    path: String,
+    columns: Option<Vec<String>>,
+    start: Option<String>,
+    end: Option<String>,
+    bom: Option<bool>,
+    db: State<'_, Database>
+) -> Result<CsvExportReport, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let selected = select_columns(PROJECT_CSV_COLUMNS, columns)?;
+    let range = DateRange::parse(start.as_deref(), end.as_deref())?;
+    let rows = build_project_rows(&conn, &range).map_err(|e| e.to_string())?;
+
+    write_csv(&path, PROJECT_CSV_COLUMNS, &selected, &rows, bom.unwrap_or(false))
+}
+
+/// 导出番茄钟会话记录为 CSV
+#[tauri::command]
+pub async fn export_pomodoro_csv(
+    path: String,
+    columns: Option<Vec<String>>,
+    start: Option<String>,
+    end: Option<String>,
+    bom: Option<bool>,
+    db: State<'_, Database>
+) -> Result<CsvExportReport, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let selected = select_columns(POMODORO_CSV_COLUMNS, columns)?;
+    let range = DateRange::parse(start.as_deref(), end.as_deref())?;
+    let rows = build_pomodoro_rows(&conn, &range).map_err(|e| e.to_string())?;
+
+    write_csv(&path, POMODORO_CSV_COLUMNS, &selected, &rows, bom.unwrap_or(false))
+}
@@ -0,0 +1,717 @@
+use crate::models::{
+    ColumnTask, DataExport, DataImportSummary, ImportTableSummary, KanbanColumn, Milestone, Note, NoteTag,
+    PomodoroInterruption, PomodoroSession, Project, ProjectStatusChange, Tag, Task, TaskDependency, TaskTag,
+};
+use crate::db::Database;
+use crate::commands::projects::{validate_project_status, PROJECT_STATUS_ACTIVE};
+use tauri::State;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+// 导出文件的格式标识与当前版本，结构变化时递增版本号：
+// 2 增加 task_tags；3 增加番茄钟阶段；4 增加暂停、放弃与中断记录；
+// 5 增加里程碑与项目状态历史；6 增加任务依赖、开始日期与预计用时；7 增加任务完成时间；8 增加任务重复规则
+pub const DATA_EXPORT_FORMAT: &str = "productivity-notebook";
+pub const DATA_EXPORT_VERSION: u32 = 8;
+
+fn time_at(row: &Row, idx: usize, name: &str) -> Result<DateTime<Utc>, rusqlite::Error> {
+    row.get::<_, String>(idx)?.parse()
+        .map_err(|_| rusqlite::Error::InvalidColumnType(idx, name.to_string(), rusqlite::types::Type::Text))
+}
+
+fn optional_time_at(row: &Row, idx: usize, name: &str) -> Result<Option<DateTime<Utc>>, rusqlite::Error> {
+    row.get::<_, Option<String>>(idx)?.map(|s| s.parse()).transpose()
+        .map_err(|_| rusqlite::Error::InvalidColumnType(idx, name.to_string(), rusqlite::types::Type::Text))
+}
+
+fn query_all<T, F>(conn: &Connection, sql: &str, map: F) -> Result<Vec<T>, rusqlite::Error>
+where
+    F: FnMut(&Row) -> Result<T, rusqlite::Error>,
+{
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], map)?.collect();
+    rows
+}
+
+/// 读取全部未删除的数据，回收站中的内容不会导出
+pub fn build_data_export(conn: &Connection) -> Result<DataExport, rusqlite::Error> {
+    let notes = query_all(conn,
+        "SELECT id, title, content, created_at, updated_at, project_id FROM notes WHERE deleted_at IS NULL ORDER BY created_at",
+        |row| Ok(Note {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            content: row.get(2)?,
+            created_at: time_at(row, 3, "created_at")?,
+            updated_at: time_at(row, 4, "updated_at")?,
+            project_id: row.get(5)?,
+        }))?;
+
+    let tags = query_all(conn,
+        "SELECT id, name, color FROM tags ORDER BY name",
+        |row| Ok(Tag {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            color: row.get(2)?,
+        }))?;
+
+    let note_tags = query_all(conn,
+        "SELECT nt.note_id, nt.tag_id FROM note_tags nt
+         INNER JOIN notes n ON n.id = nt.note_id
+         WHERE n.deleted_at IS NULL",
+        |row| Ok(NoteTag {
+            note_id: row.get(0)?,
+            tag_id: row.get(1)?,
+        }))?;
+
+    let tasks = query_all(conn,
+        "SELECT id, title, description, is_completed, priority, due_date, remind_at,
+                created_at, updated_at, project_id, parent_id, position, milestone_id,
+                start_date, estimate_minutes, completed_at, recurrence
+         FROM tasks WHERE deleted_at IS NULL ORDER BY position, created_at",
+        |row| Ok(Task {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            description: row.get(2)?,
+            is_completed: row.get::<_, i32>(3)? != 0,
+            priority: row.get(4)?,
+            due_date: optional_time_at(row, 5, "due_date")?,
+            remind_at: optional_time_at(row, 6, "remind_at")?,
+            created_at: time_at(row, 7, "created_at")?,
+            updated_at: time_at(row, 8, "updated_at")?,
+            project_id: row.get(9)?,
+            parent_id: row.get(10)?,
+            position: row.get(11)?,
+            milestone_id: row.get(12)?,
+            start_date: optional_time_at(row, 13, "start_date")?,
+            estimate_minutes: row.get(14)?,
+            completed_at: optional_time_at(row, 15, "completed_at")?,
+            recurrence: row.get(16)?,
+        }))?;
+
+    let projects = query_all(conn,
+        "SELECT id, name, description, status, created_at, updated_at, due_date, color
+         FROM projects WHERE deleted_at IS NULL ORDER BY created_at",
+        |row| Ok(Project {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            status: row.get(3)?,
+            created_at: time_at(row, 4, "created_at")?,
+            updated_at: time_at(row, 5, "updated_at")?,
+            due_date: optional_time_at(row, 6, "due_date")?,
+            color: row.get(7)?,
+        }))?;
+
+    let kanban_columns = query_all(conn,
+        "SELECT id, project_id, name, position FROM kanban_columns ORDER BY project_id, position",
+        |row| Ok(KanbanColumn {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            name: row.get(2)?,
+            position: row.get(3)?,
+        }))?;
+
+    let column_tasks = query_all(conn,
+        "SELECT task_id, column_id, position FROM column_tasks ORDER BY column_id, position",
+        |row| Ok(ColumnTask {
+            task_id: row.get(0)?,
+            column_id: row.get(1)?,
+            position: row.get(2)?,
+        }))?;
+
+    let pomodoro_sessions = query_all(conn,
+        "SELECT id, started_at, ended_at, duration, is_completed, task_id, notes, phase,
+                paused_seconds, abandoned, abandon_reason
+         FROM pomodoro_sessions ORDER BY started_at",
+        |row| Ok(PomodoroSession {
+            id: row.get(0)?,
+            started_at: time_at(row, 1, "started_at")?,
+            ended_at: optional_time_at(row, 2, "ended_at")?,
+            duration: row.get(3)?,
+            is_completed: row.get::<_, i32>(4)? != 0,
+            task_id: row.get(5)?,
+            notes: row.get(6)?,
+            phase: row.get(7)?,
+            paused_seconds: row.get(8)?,
+            abandoned: row.get::<_, i32>(9)? != 0,
+            abandon_reason: row.get(10)?,
+        }))?;
+
+    let task_tags = query_all(conn,
+        "SELECT tt.task_id, tt.tag_id FROM task_tags tt
+         INNER JOIN tasks t ON t.id = tt.task_id
+         WHERE t.deleted_at IS NULL",
+        |row| Ok(TaskTag {
+            task_id: row.get(0)?,
+            tag_id: row.get(1)?,
+        }))?;
+
+    let pomodoro_interruptions = query_all(conn,
+        "SELECT id, session_id, kind, reason, started_at, ended_at, duration
+         FROM pomodoro_interruptions ORDER BY started_at",
+        |row| Ok(PomodoroInterruption {
+            id: row.get(0)?,
+            session_id: row.get(1)?,
+            kind: row.get(2)?,
+            reason: row.get(3)?,
+            started_at: time_at(row, 4, "started_at")?,
+            ended_at: optional_time_at(row, 5, "ended_at")?,
+            duration: row.get(6)?,
+        }))?;
+
+    let milestones = query_all(conn,
+        "SELECT m.id, m.project_id, m.name, m.description, m.due_date, m.completed_at, m.position, m.created_at, m.updated_at
+         FROM milestones m
+         INNER JOIN projects p ON p.id = m.project_id
+         WHERE p.deleted_at IS NULL
+         ORDER BY m.project_id, m.position",
+        |row| Ok(Milestone {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            name: row.get(2)?,
+            description: row.get(3)?,
+            due_date: optional_time_at(row, 4, "due_date")?,
+            completed_at: optional_time_at(row, 5, "completed_at")?,
+            position: row.get(6)?,
+            created_at: time_at(row, 7, "created_at")?,
+            updated_at: time_at(row, 8, "updated_at")?,
+        }))?;
+
+    let project_status_history = query_all(conn,
+        "SELECT h.id, h.project_id, h.from_status, h.to_status, h.note, h.changed_at
+         FROM project_status_history h
+         INNER JOIN projects p ON p.id = h.project_id
+         WHERE p.deleted_at IS NULL
+         ORDER BY h.changed_at",
+        |row| Ok(ProjectStatusChange {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            from_status: row.get(2)?,
+            to_status: row.get(3)?,
+            note: row.get(4)?,
+            changed_at: time_at(row, 5, "changed_at")?,
+        }))?;
+
+    let task_dependencies = query_all(conn,
+        "SELECT d.task_id, d.depends_on_id, d.created_at FROM task_dependencies d
+         INNER JOIN tasks t ON t.id = d.task_id
+         INNER JOIN tasks p ON p.id = d.depends_on_id
+         WHERE t.deleted_at IS NULL AND p.deleted_at IS NULL",
+        |row| Ok(TaskDependency {
+            task_id: row.get(0)?,
+            depends_on_id: row.get(1)?,
+            created_at: time_at(row, 2, "created_at")?,
+        }))?;
+
+    Ok(DataExport {
+        format: DATA_EXPORT_FORMAT.to_string(),
+        version: DATA_EXPORT_VERSION,
+        exported_at: Utc::now(),
+        notes,
+        tags,
+        note_tags,
+        tasks,
+        projects,
+        kanban_columns,
+        column_tasks,
+        pomodoro_sessions,
+        task_tags,
+        pomodoro_interruptions,
+        milestones,
+        project_status_history,
+        task_dependencies,
+    })
+}
+
+/// 将旧版本的文档升级到当前结构。
+/// 旧版本缺少的表和字段由默认值补全，这里只处理需要由其他字段推算的数据
+fn upgrade_export(doc: &mut DataExport) {
+    if doc.version < 7 {
+        // 版本 7 之前没有完成时间，按最后修改时间估计
+        for task in doc.tasks.iter_mut().filter(|t| t.is_completed && t.completed_at.is_none()) {
+            task.completed_at = Some(task.updated_at);
+        }
+    }
+}
+
+/// 检查文档格式、版本与内部引用，返回不影响导入的警告
+fn validate_export(doc: &DataExport) -> Result<Vec<String>, String> {
+    if doc.format != DATA_EXPORT_FORMAT {
+        return Err(format!("Unsupported export format: {}", doc.format));
+    }
+    if doc.version == 0 || doc.version > DATA_EXPORT_VERSION {
+        return Err(format!("Unsupported export version: {} (supported: 1-{})", doc.version, DATA_EXPORT_VERSION));
+    }
+
+    let mut warnings = Vec::new();
+
+    let check_unique = |table: &str, ids: Vec<&String>| -> Result<(), String> {
+        let mut seen = HashSet::new();
+        for id in ids {
+            if !seen.insert(id) {
+                return Err(format!("Duplicate id {} in {}", id, table));
+            }
+        }
+        Ok(())
+    };
+    check_unique("notes", doc.notes.iter().map(|n| &n.id).collect())?;
+    check_unique("tags", doc.tags.iter().map(|t| &t.id).collect())?;
+    check_unique("tasks", doc.tasks.iter().map(|t| &t.id).collect())?;
+    check_unique("projects", doc.projects.iter().map(|p| &p.id).collect())?;
+    check_unique("kanban_columns", doc.kanban_columns.iter().map(|c| &c.id).collect())?;
+    check_unique("pomodoro_sessions", doc.pomodoro_sessions.iter().map(|s| &s.id).collect())?;
+    check_unique("pomodoro_interruptions", doc.pomodoro_interruptions.iter().map(|i| &i.id).collect())?;
+    check_unique("milestones", doc.milestones.iter().map(|m| &m.id).collect())?;
+    check_unique("project_status_history", doc.project_status_history.iter().map(|h| &h.id).collect())?;
+
+    let note_ids: HashSet<&String> = doc.notes.iter().map(|n| &n.id).collect();
+    let tag_ids: HashSet<&String> = doc.tags.iter().map(|t| &t.id).collect();
+    let task_ids: HashSet<&String> = doc.tasks.iter().map(|t| &t.id).collect();
+    let project_ids: HashSet<&String> = doc.projects.iter().map(|p| &p.id).collect();
+    let column_ids: HashSet<&String> = doc.kanban_columns.iter().map(|c| &c.id).collect();
+    let session_ids: HashSet<&String> = doc.pomodoro_sessions.iter().map(|s| &s.id).collect();
+    let milestone_ids: HashSet<&String> = doc.milestones.iter().map(|m| &m.id).collect();
+
+    for link in &doc.note_tags {
+        if !note_ids.contains(&link.note_id) || !tag_ids.contains(&link.tag_id) {
+            warnings.push(format!("note_tags link {} -> {} references missing data and will be skipped", link.note_id, link.tag_id));
+        }
+    }
+    for link in &doc.task_tags {
+        if !task_ids.contains(&link.task_id) || !tag_ids.contains(&link.tag_id) {
+            warnings.push(format!("task_tags link {} -> {} references missing data and will be skipped", link.task_id, link.tag_id));
+        }
+    }
+    for task in &doc.tasks {
+        if let Some(parent_id) = &task.parent_id {
+            if !task_ids.contains(parent_id) {
+                warnings.push(format!("Task {} references missing parent {}", task.id, parent_id));
+            }
+        }
+        if let Some(project_id) = &task.project_id {
+            if !project_ids.contains(project_id) {
+                warnings.push(format!("Task {} references missing project {}", task.id, project_id));
+            }
+        }
+        if let Some(milestone_id) = &task.milestone_id {
+            if !milestone_ids.contains(milestone_id) {
+                warnings.push(format!("Task {} references missing milestone {}", task.id, milestone_id));
+            }
+        }
+    }
+    for link in &doc.task_dependencies {
+        if !task_ids.contains(&link.task_id) || !task_ids.contains(&link.depends_on_id) {
+            warnings.push(format!("Task dependency {} -> {} references missing data and will be skipped", link.task_id, link.depends_on_id));
+        }
+    }
+    for note in &doc.notes {
+        if let Some(project_id) = &note.project_id {
+            if !project_ids.contains(project_id) {
+                warnings.push(format!("Note {} references missing project {}", note.id, project_id));
+            }
+        }
+    }
+    for column in &doc.kanban_columns {
+        if !project_ids.contains(&column.project_id) {
+            warnings.push(format!("Kanban column {} references missing project {}", column.id, column.project_id));
+        }
+    }
+    for link in &doc.column_tasks {
+        if !task_ids.contains(&link.task_id) || !column_ids.contains(&link.column_id) {
+            warnings.push(format!("column_tasks link {} -> {} references missing data and will be skipped", link.task_id, link.column_id));
+        }
+    }
+    for interruption in &doc.pomodoro_interruptions {
+        if !session_ids.contains(&interruption.session_id) {
+            warnings.push(format!("Interruption {} references missing pomodoro session {} and will be skipped", interruption.id, interruption.session_id));
+        }
+    }
+    for milestone in &doc.milestones {
+        if !project_ids.contains(&milestone.project_id) {
+            warnings.push(format!("Milestone {} references missing project {} and will be skipped", milestone.id, milestone.project_id));
+        }
+    }
+    for change in &doc.project_status_history {
+        if !project_ids.contains(&change.project_id) {
+            warnings.push(format!("Status change {} references missing project {} and will be skipped", change.id, change.project_id));
+        }
+    }
+    for project in &doc.projects {
+        if validate_project_status(&project.status).is_err() {
+            warnings.push(format!("Project {} has unknown status {} and will be imported as active", project.id, project.status));
+        }
+    }
+
+    Ok(warnings)
+}
+
+/// 为所有实体生成新的 id 并更新引用，用于合并到已有数据库
+fn remap_export_ids(doc: &mut DataExport) {
+    let mut map: HashMap<String, String> = HashMap::new();
+    let mut fresh = |id: &mut String| {
+        let new_id = Uuid::new_v4().to_string();
+        map.insert(id.clone(), new_id.clone());
+        *id = new_id;
+    };
+
+    doc.notes.iter_mut().for_each(|n| fresh(&mut n.id));
+    doc.tags.iter_mut().for_each(|t| fresh(&mut t.id));
+    doc.tasks.iter_mut().for_each(|t| fresh(&mut t.id));
+    doc.projects.iter_mut().for_each(|p| fresh(&mut p.id));
+    doc.kanban_columns.iter_mut().for_each(|c| fresh(&mut c.id));
+    doc.pomodoro_sessions.iter_mut().for_each(|s| fresh(&mut s.id));
+    doc.pomodoro_interruptions.iter_mut().for_each(|i| fresh(&mut i.id));
+    doc.milestones.iter_mut().for_each(|m| fresh(&mut m.id));
+    doc.project_status_history.iter_mut().for_each(|h| fresh(&mut h.id));
+
+    let lookup = |id: &mut String| {
+        if let Some(new_id) = map.get(id) {
+            *id = new_id.clone();
+        }
+    };
+
+    for note in &mut doc.notes {
+        if let Some(project_id) = &mut note.project_id { lookup(project_id) }
+    }
+    for link in &mut doc.note_tags {
+        lookup(&mut link.note_id);
+        lookup(&mut link.tag_id);
+    }
+    for task in &mut doc.tasks {
+        if let Some(project_id) = &mut task.project_id { lookup(project_id) }
+        if let Some(parent_id) = &mut task.parent_id { lookup(parent_id) }
+        if let Some(milestone_id) = &mut task.milestone_id { lookup(milestone_id) }
+    }
+    for link in &mut doc.task_tags {
+        lookup(&mut link.task_id);
+        lookup(&mut link.tag_id);
+    }
+    for link in &mut doc.task_dependencies {
+        lookup(&mut link.task_id);
+        lookup(&mut link.depends_on_id);
+    }
+    for column in &mut doc.kanban_columns {
+        lookup(&mut column.project_id);
+    }
+    for link in &mut doc.column_tasks {
+        lookup(&mut link.task_id);
+        lookup(&mut link.column_id);
+    }
+    for session in &mut doc.pomodoro_sessions {
+        if let Some(task_id) = &mut session.task_id { lookup(task_id) }
+    }
+    for interruption in &mut doc.pomodoro_interruptions {
+        lookup(&mut interruption.session_id);
+    }
+    for milestone in &mut doc.milestones {
+        lookup(&mut milestone.project_id);
+    }
+    for change in &mut doc.project_status_history {
+        lookup(&mut change.project_id);
+    }
+}
+
+fn exists(conn: &Connection, table: &str, id: &str) -> Result<bool, rusqlite::Error> {
+    conn.query_row(&format!("SELECT COUNT(*) > 0 FROM {} WHERE id = ?", table), params![id], |row| row.get(0))
+}
+
+fn count_upsert(summary: &mut ImportTableSummary, existed: bool) {
+    if existed {
+        summary.updated += 1;
+    } else {
+        summary.created += 1;
+    }
+}
+
+fn table_summary(table: &str) -> ImportTableSummary {
+    ImportTableSummary {
+        table: table.to_string(),
+        created: 0,
+        updated: 0,
+        skipped: 0,
+    }
+}
+
+/// 写入文档中的全部数据，已存在的 id 会被覆盖
+fn apply_export(conn: &Connection, doc: &DataExport) -> Result<Vec<ImportTableSummary>, rusqlite::Error> {
+    let mut summaries = Vec::new();
+
+    let mut projects = table_summary("projects");
+    for project in &doc.projects {
+        count_upsert(&mut projects, exists(conn, "projects", &project.id)?);
+        conn.execute(
+            "INSERT OR REPLACE INTO projects (id, name, description, status, created_at, updated_at, due_date, color, deleted_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, NULL)",
+            params![
+                project.id, project.name, project.description,
+                if validate_project_status(&project.status).is_ok() { project.status.as_str() } else { PROJECT_STATUS_ACTIVE },
+                project.created_at.to_rfc3339(), project.updated_at.to_rfc3339(),
+                project.due_date.map(|d| d.to_rfc3339()), project.color
+            ]
+        )?;
+    }
+    summaries.push(projects);
+
+    let mut milestones = table_summary("milestones");
+    for milestone in &doc.milestones {
+        if !exists(conn, "projects", &milestone.project_id)? {
+            milestones.skipped += 1;
+            continue;
+        }
+        count_upsert(&mut milestones, exists(conn, "milestones", &milestone.id)?);
+        conn.execute(
+            "INSERT OR REPLACE INTO milestones (id, project_id, name, description, due_date, completed_at, position, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                milestone.id, milestone.project_id, milestone.name, milestone.description,
+                milestone.due_date.map(|d| d.to_rfc3339()), milestone.completed_at.map(|d| d.to_rfc3339()),
+                milestone.position, milestone.created_at.to_rfc3339(), milestone.updated_at.to_rfc3339()
+            ]
+        )?;
+    }
+    summaries.push(milestones);
+
+    let mut history = table_summary("project_status_history");
+    for change in &doc.project_status_history {
+        if !exists(conn, "projects", &change.project_id)? {
+            history.skipped += 1;
+            continue;
+        }
+        count_upsert(&mut history, exists(conn, "project_status_history", &change.id)?);
+        conn.execute(
+            "INSERT OR REPLACE INTO project_status_history (id, project_id, from_status, to_status, note, changed_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![change.id, change.project_id, change.from_status, change.to_status, change.note, change.changed_at.to_rfc3339()]
+        )?;
+    }
+    summaries.push(history);
+
+    // 标签名称唯一，同名标签合并到已有标签
+    let mut tag_ids: HashMap<&str, String> = HashMap::new();
+    let mut tags = table_summary("tags");
+    for tag in &doc.tags {
+        let same_name: Option<String> = conn.query_row(
+            "SELECT id FROM tags WHERE name = ? AND id != ?",
+            params![tag.name, tag.id],
+            |row| row.get(0)
+        ).optional()?;
+
+        if let Some(existing_id) = same_name {
+            tag_ids.insert(&tag.id, existing_id);
+            tags.skipped += 1;
+            continue;
+        }
+
+        count_upsert(&mut tags, exists(conn, "tags", &tag.id)?);
+        conn.execute(
+            "INSERT OR REPLACE INTO tags (id, name, color) VALUES (?, ?, ?)",
+            params![tag.id, tag.name, tag.color]
+        )?;
+        tag_ids.insert(&tag.id, tag.id.clone());
+    }
+    summaries.push(tags);
+
+    let mut notes = table_summary("notes");
+    for note in &doc.notes {
+        count_upsert(&mut notes, exists(conn, "notes", &note.id)?);
+        conn.execute(
+            "INSERT OR REPLACE INTO notes (id, title, content, created_at, updated_at, project_id, deleted_at)
+             VALUES (?, ?, ?, ?, ?, ?, NULL)",
+            params![
+                note.id, note.title, note.content,
+                note.created_at.to_rfc3339(), note.updated_at.to_rfc3339(), note.project_id
+            ]
+        )?;
+    }
+    summaries.push(notes);
+
+    let note_ids: HashSet<&String> = doc.notes.iter().map(|n| &n.id).collect();
+    let mut note_tags = table_summary("note_tags");
+    for link in &doc.note_tags {
+        match tag_ids.get(link.tag_id.as_str()) {
+            Some(tag_id) if note_ids.contains(&link.note_id) => {
+                let inserted = conn.execute(
+                    "INSERT OR IGNORE INTO note_tags (note_id, tag_id) VALUES (?, ?)",
+                    params![link.note_id, tag_id]
+                )?;
+                if inserted > 0 { note_tags.created += 1 } else { note_tags.skipped += 1 }
+            }
+            _ => note_tags.skipped += 1,
+        }
+    }
+    summaries.push(note_tags);
+
+    let mut tasks = table_summary("tasks");
+    for task in &doc.tasks {
+        count_upsert(&mut tasks, exists(conn, "tasks", &task.id)?);
+        let milestone_id = match &task.milestone_id {
+            Some(id) if exists(conn, "milestones", id)? => Some(id),
+            _ => None,
+        };
+        conn.execute(
+            "INSERT OR REPLACE INTO tasks (id, title, description, is_completed, priority, due_date, remind_at,
+                                           created_at, updated_at, project_id, parent_id, position, milestone_id,
+                                           start_date, estimate_minutes, completed_at, recurrence, deleted_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, NULL)",
+            params![
+                task.id, task.title, task.description, task.is_completed as i32, task.priority,
+                task.due_date.map(|d| d.to_rfc3339()), task.remind_at.map(|d| d.to_rfc3339()),
+                task.created_at.to_rfc3339(), task.updated_at.to_rfc3339(),
+                task.project_id, task.parent_id, task.position, milestone_id,
+                task.start_date.map(|d| d.to_rfc3339()), task.estimate_minutes,
+                if task.is_completed { task.completed_at.map(|d| d.to_rfc3339()) } else { None },
+                task.recurrence
+            ]
+        )?;
+    }
+    summaries.push(tasks);
+
+    let task_ids: HashSet<&String> = doc.tasks.iter().map(|t| &t.id).collect();
+    let mut task_tags = table_summary("task_tags");
+    for link in &doc.task_tags {
+        match tag_ids.get(link.tag_id.as_str()) {
+            Some(tag_id) if task_ids.contains(&link.task_id) => {
+                let inserted = conn.execute(
+                    "INSERT OR IGNORE INTO task_tags (task_id, tag_id) VALUES (?, ?)",
+                    params![link.task_id, tag_id]
+                )?;
+                if inserted > 0 { task_tags.created += 1 } else { task_tags.skipped += 1 }
+            }
+            _ => task_tags.skipped += 1,
+        }
+    }
+    summaries.push(task_tags);
+
+    let mut dependencies = table_summary("task_dependencies");
+    for link in &doc.task_dependencies {
+        if !task_ids.contains(&link.task_id) || !task_ids.contains(&link.depends_on_id) || link.task_id == link.depends_on_id {
+            dependencies.skipped += 1;
+            continue;
+        }
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO task_dependencies (task_id, depends_on_id, created_at) VALUES (?, ?, ?)",
+            params![link.task_id, link.depends_on_id, link.created_at.to_rfc3339()]
+        )?;
+        if inserted > 0 { dependencies.created += 1 } else { dependencies.skipped += 1 }
+    }
+    summaries.push(dependencies);
+
+    let mut columns = table_summary("kanban_columns");
+    for column in &doc.kanban_columns {
+        count_upsert(&mut columns, exists(conn, "kanban_columns", &column.id)?);
+        conn.execute(
+            "INSERT OR REPLACE INTO kanban_columns (id, project_id, name, position) VALUES (?, ?, ?, ?)",
+            params![column.id, column.project_id, column.name, column.position]
+        )?;
+    }
+    summaries.push(columns);
+
+    let column_ids: HashSet<&String> = doc.kanban_columns.iter().map(|c| &c.id).collect();
+    let mut column_tasks = table_summary("column_tasks");
+    for link in &doc.column_tasks {
+        if !task_ids.contains(&link.task_id) || !column_ids.contains(&link.column_id) {
+            column_tasks.skipped += 1;
+            continue;
+        }
+        conn.execute(
+            "INSERT OR REPLACE INTO column_tasks (task_id, column_id, position) VALUES (?, ?, ?)",
+            params![link.task_id, link.column_id, link.position]
+        )?;
+        column_tasks.created += 1;
+    }
+    summaries.push(column_tasks);
+
+    let mut sessions = table_summary("pomodoro_sessions");
+    for session in &doc.pomodoro_sessions {
+        count_upsert(&mut sessions, exists(conn, "pomodoro_sessions", &session.id)?);
+        conn.execute(
+            "INSERT OR REPLACE INTO pomodoro_sessions (id, started_at, ended_at, duration, is_completed, task_id, notes, phase,
+                                                   paused_seconds, abandoned, abandon_reason)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                session.id, session.started_at.to_rfc3339(), session.ended_at.map(|d| d.to_rfc3339()),
+                session.duration, session.is_completed as i32, session.task_id, session.notes, session.phase,
+                session.paused_seconds, session.abandoned as i32, session.abandon_reason
+            ]
+        )?;
+    }
+    summaries.push(sessions);
+
+    let mut interruptions = table_summary("pomodoro_interruptions");
+    for interruption in &doc.pomodoro_interruptions {
+        if !exists(conn, "pomodoro_sessions", &interruption.session_id)? {
+            interruptions.skipped += 1;
+            continue;
+        }
+        count_upsert(&mut interruptions, exists(conn, "pomodoro_interruptions", &interruption.id)?);
+        conn.execute(
+            "INSERT OR REPLACE INTO pomodoro_interruptions (id, session_id, kind, reason, started_at, ended_at, duration)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            params![
+                interruption.id, interruption.session_id, interruption.kind, interruption.reason,
+                interruption.started_at.to_rfc3339(), interruption.ended_at.map(|d| d.to_rfc3339()), interruption.duration
+            ]
+        )?;
+    }
+    summaries.push(interruptions);
+
+    Ok(summaries)
+}
+
+/// 导出全部数据为带版本号的 JSON 文档；指定路径时同时写入文件
+#[tauri::command]
+pub async fn export_data_json(path: Option<String>, db: State<'_, Database>) -> Result<String, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let doc = build_data_export(&conn).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&doc).map_err(|e| e.to_string())?;
+
+    if let Some(path) = path {
+        fs::write(path, &json).map_err(|e| e.to_string())?;
+    }
+
+    Ok(json)
+}
+
+/// 从 JSON 文件导入数据，所有写入在同一事务中完成。
+///
+/// `remap_ids` 为 true 时为所有实体生成新 id，用于合并到已有数据库而不覆盖；
+/// `dry_run` 为 true 时只返回统计结果，事务会被回滚。
+#[tauri::command]
+pub async fn import_data_json(
+    path: String,
+    remap_ids: Option<bool>,
+    dry_run: Option<bool>,
+    db: State<'_, Database>
+) -> Result<DataImportSummary, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let json = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let mut doc: DataExport = serde_json::from_str(&json).map_err(|e| format!("Invalid export file: {}", e))?;
+
+    let warnings = validate_export(&doc)?;
+    upgrade_export(&mut doc);
+    if remap_ids.unwrap_or(false) {
+        remap_export_ids(&mut doc);
+    }
+
+    let dry_run = dry_run.unwrap_or(false);
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let tables = apply_export(&tx, &doc).map_err(|e| e.to_string())?;
+
+    if dry_run {
+        tx.rollback().map_err(|e| e.to_string())?;
+    } else {
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    Ok(DataImportSummary {
+        version: doc.version,
+        dry_run,
+        tables,
+        warnings,
+    })
+}
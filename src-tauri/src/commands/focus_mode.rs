@@ -0,0 +1,270 @@
+use crate::models::{FocusModeSettings, FocusModeState, PomodoroTimerState};
+use crate::db::{self, Database};
+use crate::commands::pomodoro_timer::{PomodoroTimer, PHASE_WORK};
+use crate::commands::reminders::{deliver_due_reminders, pending_reminders};
+use tauri::{AppHandle, Emitter, State};
+use rusqlite::Connection;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+pub const FOCUS_MODE_EVENT: &str = "focus-mode://changed";
+
+const FOCUS_MODE_SETTING: &str = "focus_mode_settings";
+// 专注模式开启的时间，未开启时为空。异常退出后据此补做关闭钩子
+const FOCUS_MODE_SINCE_SETTING: &str = "focus_mode_active_since";
+
+// 屏蔽文件中由专注模式维护的部分，标记之外的内容保持不变
+const BLOCKLIST_BEGIN: &str = "# >>> focus mode blocklist";
+const BLOCKLIST_END: &str = "# <<< focus mode blocklist";
+const BLOCKLIST_ADDRESS: &str = "127.0.0.1";
+
+// 传给钩子命令的 FOCUS_MODE_EVENT
+const HOOK_START: &str = "start";
+const HOOK_END: &str = "end";
+
+pub fn load_focus_mode_settings(conn: &Connection) -> Result<FocusModeSettings, String> {
+    match db::get_setting(conn, FOCUS_MODE_SETTING).map_err(|e| e.to_string())? {
+        Some(json) => Ok(serde_json::from_str(&json).unwrap_or_default()),
+        None => Ok(FocusModeSettings::default()),
+    }
+}
+
+fn active_since(conn: &Connection) -> Result<Option<DateTime<Utc>>, String> {
+    Ok(db::get_setting(conn, FOCUS_MODE_SINCE_SETTING)
+        .map_err(|e| e.to_string())?
+        .and_then(|v| v.parse().ok()))
+}
+
+/// 专注模式开启且设置了静默提醒
+pub fn reminders_silenced(conn: &Connection) -> Result<bool, String> {
+    if active_since(conn)?.is_none() {
+        return Ok(false);
+    }
+    Ok(load_focus_mode_settings(conn)?.silence_reminders)
+}
+
+fn normalize_site(site: &str) -> Result<Option<String>, String> {
+    let site = site.trim().trim_end_matches('/').to_lowercase();
+    let site = site
+        .strip_prefix("https://")
+        .or_else(|| site.strip_prefix("http://"))
+        .unwrap_or(&site)
+        .to_string();
+    if site.is_empty() {
+        return Ok(None);
+    }
+    if site.contains(|c: char| c.is_whitespace() || c == '/' || c == '#') {
+        return Err(format!("Invalid site to block: {}", site));
+    }
+    Ok(Some(site))
+}
+
+fn normalize_settings(mut settings: FocusModeSettings) -> Result<FocusModeSettings, String> {
+    let non_empty = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    settings.start_command = non_empty(settings.start_command);
+    settings.end_command = non_empty(settings.end_command);
+    settings.blocklist_path = non_empty(settings.blocklist_path);
+
+    let mut seen = HashSet::new();
+    let mut sites = Vec::new();
+    for site in &settings.blocked_sites {
+        if let Some(site) = normalize_site(site)? {
+            if seen.insert(site.clone()) {
+                sites.push(site);
+            }
+        }
+    }
+    settings.blocked_sites = sites;
+
+    if !settings.blocked_sites.is_empty() && settings.blocklist_path.is_none() {
+        return Err("A blocklist file is required to block sites".to_string());
+    }
+    Ok(settings)
+}
+
+/// 替换屏蔽文件中专注模式的部分；`sites` 为空时只移除该部分
+fn render_blocklist(existing: &str, sites: &[String]) -> String {
+    let mut lines = Vec::new();
+    let mut inside = false;
+    for line in existing.lines() {
+        match line.trim() {
+            BLOCKLIST_BEGIN => inside = true,
+            BLOCKLIST_END => inside = false,
+            _ if !inside => lines.push(line.to_string()),
+            _ => {}
+        }
+    }
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+
+    if !sites.is_empty() {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(BLOCKLIST_BEGIN.to_string());
+        for site in sites {
+            lines.push(format!("{} {}", BLOCKLIST_ADDRESS, site));
+            if !site.starts_with("www.") {
+                lines.push(format!("{} www.{}", BLOCKLIST_ADDRESS, site));
+            }
+        }
+        lines.push(BLOCKLIST_END.to_string());
+    }
+
+    let mut out = lines.join("\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+fn write_blocklist(path: &str, sites: &[String]) -> Result<(), String> {
+    let path = Path::new(path);
+    let existing = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read blocklist {}: {}", path.display(), e)),
+    };
+    let content = render_blocklist(&existing, sites);
+    if content == existing {
+        return Ok(());
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to write blocklist {}: {}", path.display(), e))
+}
+
+/// 在后台执行钩子命令，不等待命令结束
+fn run_hook_command(command: &str, event: &str, state: &PomodoroTimerState, settings: &FocusModeSettings) -> Result<(), String> {
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+
+    cmd.env("FOCUS_MODE_EVENT", event)
+        .env("POMODORO_SESSION_ID", state.session_id.clone().unwrap_or_default())
+        .env("POMODORO_TASK_ID", state.task_id.clone().unwrap_or_default())
+        .env("FOCUS_BLOCKLIST_PATH", settings.blocklist_path.clone().unwrap_or_default());
+
+    let mut child = cmd.spawn().map_err(|e| format!("Failed to run focus {} hook: {}", event, e))?;
+    let event = event.to_string();
+    std::thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => log::warn!("Focus {} hook exited with {}", event, status),
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to wait for focus {} hook: {}", event, e),
+    });
+    Ok(())
+}
+
+/// 依次执行各个钩子，单个钩子失败只记录日志，不影响计时
+fn run_hooks(event: &str, state: &PomodoroTimerState, settings: &FocusModeSettings) {
+    let (command, sites) = if event == HOOK_START {
+        (settings.start_command.as_deref(), settings.blocked_sites.as_slice())
+    } else {
+        (settings.end_command.as_deref(), &[][..])
+    };
+
+    if let Some(path) = &settings.blocklist_path {
+        if let Err(e) = write_blocklist(path, sites) {
+            log::warn!("{}", e);
+        }
+    }
+    if let Some(command) = command {
+        if let Err(e) = run_hook_command(command, event, state, settings) {
+            log::warn!("{}", e);
+        }
+    }
+}
+
+fn focus_state(conn: &Connection) -> Result<FocusModeState, String> {
+    let since = active_since(conn)?;
+    let deferred_reminders = if reminders_silenced(conn)? {
+        pending_reminders(conn, Utc::now())?.len()
+    } else {
+        0
+    };
+    Ok(FocusModeState { active: since.is_some(), since, deferred_reminders })
+}
+
+fn emit_focus_state(app: &AppHandle, conn: &Connection) -> Result<(), String> {
+    let state = focus_state(conn)?;
+    app.emit(FOCUS_MODE_EVENT, &state).map_err(|e| e.to_string())
+}
+
+fn activate(app: &AppHandle, conn: &Connection, state: &PomodoroTimerState, settings: &FocusModeSettings) -> Result<(), String> {
+    run_hooks(HOOK_START, state, settings);
+    db::set_setting(conn, FOCUS_MODE_SINCE_SETTING, &Utc::now().to_rfc3339()).map_err(|e| e.to_string())?;
+    emit_focus_state(app, conn)
+}
+
+/// 关闭专注模式，并补发专注期间延后的提醒
+fn deactivate(app: &AppHandle, conn: &Connection, state: &PomodoroTimerState, settings: &FocusModeSettings) -> Result<(), String> {
+    run_hooks(HOOK_END, state, settings);
+    db::set_setting(conn, FOCUS_MODE_SINCE_SETTING, "").map_err(|e| e.to_string())?;
+    deliver_due_reminders(app, conn)?;
+    emit_focus_state(app, conn)
+}
+
+fn apply_focus_mode(app: &AppHandle, conn: &Connection, state: &PomodoroTimerState) -> Result<(), String> {
+    let settings = load_focus_mode_settings(conn)?;
+    let focusing = settings.enabled && state.is_active() && state.phase == PHASE_WORK;
+    match (focusing, active_since(conn)?.is_some()) {
+        (true, false) => activate(app, conn, state, &settings),
+        (false, true) => deactivate(app, conn, state, &settings),
+        _ => Ok(()),
+    }
+}
+
+/// 按计时状态开启或关闭专注模式：专注阶段（包括暂停中）保持开启，进入休息或停止时关闭。
+/// 计时服务每次状态变化后调用，启动时调用可以补做上次异常退出时没有执行的关闭钩子。
+pub fn sync_focus_mode(app: &AppHandle, conn: &Connection, state: &PomodoroTimerState) {
+    if let Err(e) = apply_focus_mode(app, conn, state) {
+        log::warn!("Failed to update focus mode: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_focus_mode_settings(db: State<'_, Database>) -> Result<FocusModeSettings, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    load_focus_mode_settings(&conn)
+}
+
+#[tauri::command]
+pub async fn get_focus_mode_state(db: State<'_, Database>) -> Result<FocusModeState, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    focus_state(&conn)
+}
+
+/// 保存专注模式设置。专注进行中修改时先按旧设置执行关闭钩子，再按新设置重新开启
+#[tauri::command]
+pub async fn update_focus_mode_settings(
+    settings: FocusModeSettings,
+    app: AppHandle,
+    db: State<'_, Database>,
+    timer: State<'_, PomodoroTimer>
+) -> Result<FocusModeSettings, String> {
+    let settings = normalize_settings(settings)?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let state = timer.snapshot()?;
+
+    if active_since(&conn)?.is_some() {
+        let previous = load_focus_mode_settings(&conn)?;
+        deactivate(&app, &conn, &state, &previous)?;
+    }
+
+    let json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    db::set_setting(&conn, FOCUS_MODE_SETTING, &json).map_err(|e| e.to_string())?;
+    apply_focus_mode(&app, &conn, &state)?;
+
+    Ok(settings)
+}
@@ -0,0 +1,344 @@
+use crate::models::{FocusAnalytics, FocusBreakdown, FocusBucket, FocusDay, FocusGoalProgress, FocusHeatmapCell};
+use crate::db::{self, Database};
+use crate::commands::calendar::local_midnight;
+use tauri::State;
+use rusqlite::{params, Connection};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+const DAILY_GOAL_SETTING: &str = "focus_daily_goal_minutes";
+const DEFAULT_DAILY_GOAL_MINUTES: i64 = 100;
+const MAX_DAILY_GOAL_MINUTES: i64 = 24 * 60;
+
+// 热力图颜色分级的上限（分钟），0 分钟为第 0 级
+const HEATMAP_LEVELS: [i64; 4] = [25, 60, 120, 240];
+
+/// 一条专注记录：净专注秒数从开始时间起连续计算
+struct FocusSession {
+    started_at: DateTime<Utc>,
+    seconds: i64,
+    completed: bool,
+    project_id: Option<String>,
+    project_name: Option<String>,
+    task_id: Option<String>,
+}
+
+pub fn load_daily_goal(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    Ok(db::get_setting(conn, DAILY_GOAL_SETTING)?
+        .and_then(|v| v.parse().ok())
+        .filter(|v| (1..=MAX_DAILY_GOAL_MINUTES).contains(v))
+        .unwrap_or(DEFAULT_DAILY_GOAL_MINUTES))
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| "Invalid date format, expected YYYY-MM-DD".to_string())
+}
+
+fn to_minutes(seconds: i64) -> i64 {
+    (seconds + 30) / 60
+}
+
+/// 读取开始时间落在 [start, end) 内的专注阶段记录，放弃的会话也计入已专注的时间。
+/// `start` 为空时从最早的记录开始。
+fn load_focus_sessions(conn: &Connection, start: Option<DateTime<Utc>>, end: DateTime<Utc>) -> Result<Vec<FocusSession>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT s.started_at, s.duration, s.is_completed, t.project_id, p.name, s.task_id
+         FROM pomodoro_sessions s
+         LEFT JOIN tasks t ON t.id = s.task_id
+         LEFT JOIN projects p ON p.id = t.project_id
+         WHERE s.phase = 'work' AND s.ended_at IS NOT NULL
+           AND (?1 IS NULL OR s.started_at >= ?1) AND s.started_at < ?2
+         ORDER BY s.started_at"
+    )?;
+
+    let session_iter = stmt.query_map(params![start.map(|s| s.to_rfc3339()), end.to_rfc3339()], |row| {
+        Ok(FocusSession {
+            started_at: row.get::<_, String>(0)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(0, "started_at".to_string(), rusqlite::types::Type::Text))?,
+            seconds: row.get::<_, i64>(1)?.max(0),
+            completed: row.get::<_, i32>(2)? != 0,
+            project_id: row.get(3)?,
+            project_name: row.get(4)?,
+            task_id: row.get(5)?,
+        })
+    })?;
+
+    session_iter.collect()
+}
+
+/// 按本地时间的整点切分一段专注时间，跨小时、跨天的会话分摊到各个小时
+fn split_by_local_hour(started_at: DateTime<Utc>, seconds: i64) -> Vec<(DateTime<Local>, i64)> {
+    let mut chunks = Vec::new();
+    let mut cursor = started_at.with_timezone(&Local);
+    let mut remaining = seconds;
+
+    while remaining > 0 {
+        let hour_start = cursor
+            .with_minute(0)
+            .and_then(|t| t.with_second(0))
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(cursor);
+        let until_next = (hour_start + Duration::hours(1) - cursor).num_seconds().max(1);
+        let chunk = remaining.min(until_next);
+
+        chunks.push((cursor, chunk));
+        cursor += Duration::seconds(chunk);
+        remaining -= chunk;
+    }
+
+    chunks
+}
+
+/// 每个本地日期的专注秒数与完成的番茄数
+fn seconds_by_day(sessions: &[FocusSession]) -> BTreeMap<NaiveDate, (i64, i64)> {
+    let mut days: BTreeMap<NaiveDate, (i64, i64)> = BTreeMap::new();
+    for session in sessions {
+        for (at, seconds) in split_by_local_hour(session.started_at, session.seconds) {
+            days.entry(at.date_naive()).or_default().0 += seconds;
+        }
+        if session.completed {
+            days.entry(session.started_at.with_timezone(&Local).date_naive()).or_default().1 += 1;
+        }
+    }
+    days
+}
+
+fn task_tags(conn: &Connection) -> Result<HashMap<String, Vec<(String, String)>>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT tt.task_id, t.id, t.name FROM task_tags tt JOIN tags t ON t.id = tt.tag_id"
+    )?;
+    let mut tags: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))? {
+        let (task_id, tag_id, name) = row?;
+        tags.entry(task_id).or_default().push((tag_id, name));
+    }
+    Ok(tags)
+}
+
+fn add_breakdown(breakdown: &mut Vec<FocusBreakdown>, id: Option<&str>, name: &str, session: &FocusSession) {
+    let index = match breakdown.iter().position(|b| b.id.as_deref() == id) {
+        Some(index) => index,
+        None => {
+            breakdown.push(FocusBreakdown {
+                id: id.map(str::to_string),
+                name: name.to_string(),
+                focus_minutes: 0,
+                sessions: 0,
+            });
+            breakdown.len() - 1
+        }
+    };
+    // 先累计秒数，输出前再换算为分钟
+    breakdown[index].focus_minutes += session.seconds;
+    if session.completed {
+        breakdown[index].sessions += 1;
+    }
+}
+
+fn finish_breakdown(mut breakdown: Vec<FocusBreakdown>) -> Vec<FocusBreakdown> {
+    breakdown.iter_mut().for_each(|b| b.focus_minutes = to_minutes(b.focus_minutes));
+    breakdown.sort_by(|a, b| b.focus_minutes.cmp(&a.focus_minutes).then_with(|| a.name.cmp(&b.name)));
+    breakdown
+}
+
+/// 截至 `today` 的当前连续达标天数和历史最长连续天数。
+/// 今天尚未达标时，当前连续天数从昨天开始往前计算。
+fn streaks(days: &BTreeMap<NaiveDate, (i64, i64)>, goal_seconds: i64, today: NaiveDate) -> (i64, i64) {
+    let met = |date: &NaiveDate| days.get(date).is_some_and(|(seconds, _)| *seconds >= goal_seconds);
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for date in days.keys().filter(|d| met(d)) {
+        run = match previous {
+            Some(prev) if prev.succ_opt() == Some(*date) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*date);
+    }
+
+    let mut current = 0;
+    let mut cursor = if met(&today) { Some(today) } else { today.pred_opt() };
+    while let Some(date) = cursor.filter(met) {
+        current += 1;
+        cursor = date.pred_opt();
+    }
+
+    (current, longest)
+}
+
+/// 按日、星期、小时以及项目、标签汇总专注时间。
+/// 日期为本地日期，默认统计最近 30 天（含今天）。
+#[tauri::command]
+pub async fn get_focus_analytics(
+    start_date: Option<String>,
+    end_date: Option<String>,
+    db: State<'_, Database>
+) -> Result<FocusAnalytics, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let end_date = match end_date {
+        Some(value) => parse_date(&value)?,
+        None => Local::now().date_naive(),
+    };
+    let start_date = match start_date {
+        Some(value) => parse_date(&value)?,
+        None => end_date - Duration::days(29),
+    };
+    if start_date > end_date {
+        return Err("start_date must not be after end_date".to_string());
+    }
+
+    let range_start = local_midnight(start_date)?;
+    let range_end = local_midnight(end_date + Duration::days(1))?;
+    let sessions = load_focus_sessions(&conn, Some(range_start), range_end).map_err(|e| e.to_string())?;
+    let goal_minutes = load_daily_goal(&conn).map_err(|e| e.to_string())?;
+
+    let by_day = seconds_by_day(&sessions);
+    let mut days = Vec::new();
+    let mut date = start_date;
+    while date <= end_date {
+        let (seconds, completed) = by_day.get(&date).copied().unwrap_or_default();
+        days.push(FocusDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            focus_minutes: to_minutes(seconds),
+            sessions: completed,
+            goal_met: seconds >= goal_minutes * 60,
+        });
+        date += Duration::days(1);
+    }
+
+    let mut weekdays: Vec<(i64, i64)> = vec![(0, 0); 7];
+    let mut hours: Vec<(i64, i64)> = vec![(0, 0); 24];
+    let mut projects = Vec::new();
+    let mut tags = Vec::new();
+    let tag_map = task_tags(&conn).map_err(|e| e.to_string())?;
+
+    for session in &sessions {
+        for (at, seconds) in split_by_local_hour(session.started_at, session.seconds) {
+            weekdays[at.weekday().num_days_from_monday() as usize].0 += seconds;
+            hours[at.hour() as usize].0 += seconds;
+        }
+        if session.completed {
+            let local = session.started_at.with_timezone(&Local);
+            weekdays[local.weekday().num_days_from_monday() as usize].1 += 1;
+            hours[local.hour() as usize].1 += 1;
+        }
+
+        match (&session.project_id, &session.project_name) {
+            (Some(id), Some(name)) => add_breakdown(&mut projects, Some(id.as_str()), name, session),
+            _ => add_breakdown(&mut projects, None, "No project", session),
+        }
+
+        let session_tags = session.task_id.as_ref().and_then(|id| tag_map.get(id));
+        match session_tags {
+            Some(session_tags) if !session_tags.is_empty() => {
+                for (id, name) in session_tags {
+                    add_breakdown(&mut tags, Some(id.as_str()), name, session);
+                }
+            }
+            _ => add_breakdown(&mut tags, None, "Untagged", session),
+        }
+    }
+
+    let buckets = |values: Vec<(i64, i64)>| -> Vec<FocusBucket> {
+        values.into_iter()
+            .enumerate()
+            .map(|(index, (seconds, sessions))| FocusBucket {
+                index: index as u32,
+                focus_minutes: to_minutes(seconds),
+                sessions,
+            })
+            .collect()
+    };
+
+    Ok(FocusAnalytics {
+        start_date: start_date.format("%Y-%m-%d").to_string(),
+        end_date: end_date.format("%Y-%m-%d").to_string(),
+        total_focus_minutes: to_minutes(sessions.iter().map(|s| s.seconds).sum()),
+        completed_sessions: sessions.iter().filter(|s| s.completed).count() as i64,
+        daily_goal_minutes: goal_minutes,
+        days,
+        weekdays: buckets(weekdays),
+        hours: buckets(hours),
+        projects: finish_breakdown(projects),
+        tags: finish_breakdown(tags),
+    })
+}
+
+/// 指定日期（默认今天）的目标完成情况与连续达标天数
+#[tauri::command]
+pub async fn get_focus_goal_progress(date: Option<String>, db: State<'_, Database>) -> Result<FocusGoalProgress, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let date = match date {
+        Some(value) => parse_date(&value)?,
+        None => Local::now().date_naive(),
+    };
+    let goal_minutes = load_daily_goal(&conn).map_err(|e| e.to_string())?;
+
+    // 连续天数需要完整的历史记录
+    let sessions = load_focus_sessions(&conn, None, local_midnight(date + Duration::days(1))?)
+        .map_err(|e| e.to_string())?;
+    let by_day = seconds_by_day(&sessions);
+    let (seconds, completed) = by_day.get(&date).copied().unwrap_or_default();
+    let (current_streak, longest_streak) = streaks(&by_day, goal_minutes * 60, date);
+
+    Ok(FocusGoalProgress {
+        date: date.format("%Y-%m-%d").to_string(),
+        goal_minutes,
+        focus_minutes: to_minutes(seconds),
+        completed_sessions: completed,
+        progress: seconds as f64 / (goal_minutes * 60) as f64,
+        goal_met: seconds >= goal_minutes * 60,
+        current_streak,
+        longest_streak,
+    })
+}
+
+/// 热力图数据：截至 `end_date`（默认今天）的每天专注分钟数及颜色等级 0-4，默认一年
+#[tauri::command]
+pub async fn get_focus_heatmap(end_date: Option<String>, days: Option<i64>, db: State<'_, Database>) -> Result<Vec<FocusHeatmapCell>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let end_date = match end_date {
+        Some(value) => parse_date(&value)?,
+        None => Local::now().date_naive(),
+    };
+    let days = days.unwrap_or(365).clamp(1, 366 * 2);
+    let start_date = end_date - Duration::days(days - 1);
+
+    let sessions = load_focus_sessions(&conn, Some(local_midnight(start_date)?), local_midnight(end_date + Duration::days(1))?)
+        .map_err(|e| e.to_string())?;
+    let by_day = seconds_by_day(&sessions);
+
+    let mut cells = Vec::new();
+    let mut date = start_date;
+    while date <= end_date {
+        let minutes = to_minutes(by_day.get(&date).map(|(seconds, _)| *seconds).unwrap_or(0));
+        let level = if minutes == 0 {
+            0
+        } else {
+            HEATMAP_LEVELS.iter().position(|max| minutes <= *max).unwrap_or(HEATMAP_LEVELS.len() - 1) as u8 + 1
+        };
+        cells.push(FocusHeatmapCell {
+            date: date.format("%Y-%m-%d").to_string(),
+            weekday: date.weekday().num_days_from_monday(),
+            focus_minutes: minutes,
+            level,
+        });
+        date += Duration::days(1);
+    }
+
+    Ok(cells)
+}
+
+#[tauri::command]
+pub async fn set_focus_daily_goal(minutes: i64, db: State<'_, Database>) -> Result<(), String> {
+    if !(1..=MAX_DAILY_GOAL_MINUTES).contains(&minutes) {
+        return Err(format!("Daily goal must be between 1 and {} minutes", MAX_DAILY_GOAL_MINUTES));
+    }
+
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    db::set_setting(&conn, DAILY_GOAL_SETTING, &minutes.to_string()).map_err(|e| e.to_string())
+}
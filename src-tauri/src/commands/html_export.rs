@@ -0,0 +1,444 @@
+use crate::models::{HtmlExportReport, Note};
+use crate::db::Database;
+use crate::commands::vault::{load_vault_note, load_vault_notes, sanitize_file_name, short_id};
+use tauri::State;
+use rusqlite::{params, Connection};
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag};
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::SyntaxSet;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// 代码高亮使用的主题，导出文件为浅色背景
+const HIGHLIGHT_THEME: &str = "InspiredGitHub";
+
+const EXPORT_CSS: &str = r#"
+body { margin: 0; background: #f9fafb; color: #1f2937; font: 16px/1.7 -apple-system, BlinkMacSystemFont, "Segoe UI", "PingFang SC", "Microsoft YaHei", sans-serif; }
+main { max-width: 820px; margin: 0 auto; padding: 40px 24px 80px; background: #fff; min-height: 100vh; box-sizing: border-box; }
+h1, h2, h3, h4, h5, h6 { line-height: 1.3; margin: 1.6em 0 0.6em; }
+h1.note-title { margin-top: 0; font-size: 2em; }
+a { color: #2563eb; text-decoration: none; }
+a:hover { text-decoration: underline; }
+pre { padding: 14px 16px; border-radius: 6px; overflow-x: auto; font-size: 14px; line-height: 1.5; border: 1px solid #e5e7eb; }
+code { font-family: "SFMono-Regular", Consolas, "Liberation Mono", monospace; }
+:not(pre) > code { background: #f3f4f6; padding: 0.15em 0.35em; border-radius: 4px; font-size: 0.9em; }
+blockquote { margin: 1em 0; padding: 0 1em; color: #6b7280; border-left: 4px solid #e5e7eb; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #e5e7eb; padding: 6px 12px; }
+th { background: #f9fafb; }
+img { max-width: 100%; }
+.meta { color: #6b7280; font-size: 14px; margin-bottom: 24px; }
+.tag { display: inline-block; background: #eef2ff; color: #4338ca; border-radius: 999px; padding: 0 10px; margin-right: 6px; font-size: 13px; }
+nav.toc { background: #f9fafb; border: 1px solid #e5e7eb; border-radius: 6px; padding: 12px 20px; margin-bottom: 32px; font-size: 14px; }
+nav.toc ul { list-style: none; padding-left: 0; margin: 4px 0; }
+nav.toc li { margin: 2px 0; }
+nav.toc .toc-title { font-weight: 600; }
+.note + .note { border-top: 1px solid #e5e7eb; margin-top: 48px; padding-top: 32px; }
+.index-group h2 { border-bottom: 1px solid #e5e7eb; padding-bottom: 4px; }
+.back { font-size: 14px; }
+"#;
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn highlight_theme() -> &'static Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+    THEME.get_or_init(|| {
+        let mut themes = ThemeSet::load_defaults().themes;
+        themes.remove(HIGHLIGHT_THEME).unwrap_or_default()
+    })
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 目录中的一个标题
+pub struct TocEntry {
+    pub level: usize,
+    pub id: String,
+    pub text: String,
+}
+
+/// 生成标题锚点，重复时追加序号
+fn heading_slug(text: &str, used: &mut HashMap<String, usize>) -> String {
+    let slug: String = text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            if c.is_alphanumeric() {
+                Some(c)
+            } else if c.is_whitespace() || c == '-' || c == '_' {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect();
+    let slug = if slug.is_empty() { "section".to_string() } else { slug };
+
+    let count = used.entry(slug.clone()).or_insert(0);
+    *count += 1;
+    if *count == 1 {
+        slug
+    } else {
+        format!("{}-{}", slug, count)
+    }
+}
+
+fn highlight_code(code: &str, lang: &str) -> String {
+    let syntaxes = syntax_set();
+    let syntax = syntaxes.find_syntax_by_token(lang)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+
+    highlighted_html_for_string(code, syntaxes, syntax, highlight_theme())
+        .unwrap_or_else(|_| format!("<pre><code>{}</code></pre>", escape_html(code)))
+}
+
+/// 链接地址是否会执行脚本，比较前去掉浏览器会忽略的空白和控制字符
+fn is_script_url(url: &str) -> bool {
+    let normalized: String = url.chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_lowercase();
+    normalized.starts_with("javascript:") || normalized.starts_with("vbscript:")
+}
+
+/// 将 Markdown 渲染为 HTML，代码块按语言高亮，标题带锚点。
+/// `anchor_prefix` 用于多篇笔记合并到同一文件时区分锚点。
+/// 笔记可能来自外部导入，原始 HTML 按文本转义输出，脚本链接被替换为空链接。
+pub fn render_markdown(content: &str, anchor_prefix: &str) -> (String, Vec<TocEntry>) {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+
+    let mut events: Vec<Event> = Vec::new();
+    let mut toc = Vec::new();
+    let mut used_slugs = HashMap::new();
+
+    let mut heading: Option<(usize, Vec<Event>)> = None;
+    let mut code_block: Option<(String, String)> = None;
+
+    for event in Parser::new_ext(content, options) {
+        match event {
+            Event::Start(Tag::Heading(level, _, _)) => heading = Some((level as usize, Vec::new())),
+            Event::End(Tag::Heading(..)) => {
+                if let Some((level, inner)) = heading.take() {
+                    let text: String = inner.iter()
+                        .filter_map(|e| match e {
+                            Event::Text(t) | Event::Code(t) => Some(t.to_string()),
+                            _ => None,
+                        })
+                        .collect();
+                    let id = format!("{}{}", anchor_prefix, heading_slug(&text, &mut used_slugs));
+
+                    let mut inner_html = String::new();
+                    html::push_html(&mut inner_html, inner.into_iter());
+                    events.push(Event::Html(format!("<h{0} id=\"{1}\">{2}</h{0}>\n", level, id, inner_html).into()));
+                    toc.push(TocEntry { level, id, text });
+                }
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or("").to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((lang, String::new()));
+            }
+            Event::End(Tag::CodeBlock(_)) => {
+                if let Some((lang, code)) = code_block.take() {
+                    events.push(Event::Html(highlight_code(&code, &lang).into()));
+                }
+            }
+            Event::Text(text) if code_block.is_some() => {
+                if let Some((_, code)) = code_block.as_mut() {
+                    code.push_str(&text);
+                }
+            }
+            other => {
+                let event = match other {
+                    Event::Html(raw) => Event::Text(raw),
+                    Event::Start(Tag::Link(kind, url, title)) if is_script_url(&url) => {
+                        Event::Start(Tag::Link(kind, "#".into(), title))
+                    }
+                    Event::Start(Tag::Image(kind, url, title)) if is_script_url(&url) => {
+                        Event::Start(Tag::Image(kind, "".into(), title))
+                    }
+                    other => other,
+                };
+                match heading.as_mut() {
+                    Some((_, inner)) => inner.push(event),
+                    None => events.push(event),
+                }
+            }
+        }
+    }
+
+    let mut body = String::new();
+    html::push_html(&mut body, events.into_iter());
+    (body, toc)
+}
+
+fn render_toc(title: &str, entries: &[TocEntry]) -> String {
+    if entries.is_empty() {
+        return String::new();
+    }
+
+    let min_level = entries.iter().map(|e| e.level).min().unwrap_or(1);
+    let mut out = format!("<nav class=\"toc\"><div class=\"toc-title\">{}</div><ul>\n", escape_html(title));
+    for entry in entries {
+        out.push_str(&format!(
+            "<li style=\"margin-left: {}em\"><a href=\"#{}\">{}</a></li>\n",
+            entry.level - min_level,
+            entry.id,
+            escape_html(&entry.text)
+        ));
+    }
+    out.push_str("</ul></nav>\n");
+    out
+}
+
+fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape_html(title),
+        EXPORT_CSS,
+        body
+    )
+}
+
+fn note_meta(note: &Note, project: Option<&str>, tags: &[String]) -> String {
+    let mut meta = format!("Updated {}", note.updated_at.format("%Y-%m-%d %H:%M UTC"));
+    if let Some(project) = project {
+        meta.push_str(&format!(" · {}", escape_html(project)));
+    }
+
+    let tags: String = tags.iter()
+        .map(|tag| format!("<span class=\"tag\">{}</span>", escape_html(tag)))
+        .collect();
+    if tags.is_empty() {
+        format!("<div class=\"meta\">{}</div>\n", meta)
+    } else {
+        format!("<div class=\"meta\">{}<br>{}</div>\n", meta, tags)
+    }
+}
+
+/// 渲染单篇笔记的正文部分（标题、元信息、目录和内容）
+fn render_note_section(note: &Note, project: Option<&str>, tags: &[String], anchor_prefix: &str, with_toc: bool) -> (String, Vec<TocEntry>) {
+    let (content, toc) = render_markdown(&note.content, anchor_prefix);
+
+    let mut out = format!(
+        "<article class=\"note\" id=\"{}note\">\n<h1 class=\"note-title\">{}</h1>\n",
+        anchor_prefix,
+        escape_html(&note.title)
+    );
+    out.push_str(&note_meta(note, project, tags));
+    if with_toc {
+        out.push_str(&render_toc("Contents", &toc));
+    }
+    out.push_str(&content);
+    out.push_str("</article>\n");
+
+    (out, toc)
+}
+
+/// 单篇笔记的完整 HTML 页面
+pub fn render_note_page(note: &Note, project: Option<&str>, tags: &[String], back_link: Option<&str>) -> String {
+    let (section, _) = render_note_section(note, project, tags, "", true);
+    let back = back_link
+        .map(|href| format!("<p class=\"back\"><a href=\"{}\">← Index</a></p>\n", href))
+        .unwrap_or_default();
+    html_page(&note.title, &format!("{}{}", back, section))
+}
+
+/// 按项目或标签筛选要导出的笔记，两者都未指定时导出全部
+fn select_notes(
+    conn: &Connection,
+    project_id: Option<&str>,
+    tag_ids: &[String],
+) -> Result<Vec<(Note, Option<String>, Vec<String>)>, rusqlite::Error> {
+    let mut tagged: Option<HashSet<String>> = None;
+    if !tag_ids.is_empty() {
+        let mut stmt = conn.prepare("SELECT note_id FROM note_tags WHERE tag_id = ?")?;
+        let mut ids = HashSet::new();
+        for tag_id in tag_ids {
+            for id in stmt.query_map(params![tag_id], |row| row.get::<_, String>(0))? {
+                ids.insert(id?);
+            }
+        }
+        tagged = Some(ids);
+    }
+
+    Ok(load_vault_notes(conn)?
+        .into_iter()
+        .filter(|(note, _, _)| project_id.is_none() || note.project_id.as_deref() == project_id)
+        .filter(|(note, _, _)| match &tagged {
+            Some(ids) => ids.contains(&note.id),
+            None => true,
+        })
+        .collect())
+}
+
+/// 按项目分组的索引列表，未归属项目的笔记排在最后
+fn group_by_project(notes: &[(Note, Option<String>, Vec<String>)]) -> Vec<(Option<String>, Vec<usize>)> {
+    let mut groups: Vec<(Option<String>, Vec<usize>)> = Vec::new();
+    for (index, (_, project, _)) in notes.iter().enumerate() {
+        match groups.iter_mut().find(|(name, _)| name == project) {
+            Some((_, indices)) => indices.push(index),
+            None => groups.push((project.clone(), vec![index])),
+        }
+    }
+    groups.sort_by(|a, b| match (&a.0, &b.0) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    groups
+}
+
+fn render_index(title: &str, notes: &[(Note, Option<String>, Vec<String>)], links: &[String]) -> String {
+    let mut body = format!("<h1 class=\"note-title\">{}</h1>\n<div class=\"meta\">{} notes</div>\n", escape_html(title), notes.len());
+
+    for (project, indices) in group_by_project(notes) {
+        body.push_str("<section class=\"index-group\">\n");
+        body.push_str(&format!("<h2>{}</h2>\n<ul>\n", escape_html(project.as_deref().unwrap_or("No project"))));
+        for index in indices {
+            let (note, _, tags) = &notes[index];
+            let tags: String = tags.iter()
+                .map(|tag| format!(" <span class=\"tag\">{}</span>", escape_html(tag)))
+                .collect();
+            body.push_str(&format!(
+                "<li><a href=\"{}\">{}</a>{}</li>\n",
+                links[index],
+                escape_html(&note.title),
+                tags
+            ));
+        }
+        body.push_str("</ul>\n</section>\n");
+    }
+
+    body
+}
+
+/// 导出为一个目录：每篇笔记一个页面，外加 index.html 索引页
+fn export_html_site(dir: &Path, title: &str, notes: &[(Note, Option<String>, Vec<String>)]) -> Result<Vec<PathBuf>, String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let mut used = HashSet::new();
+    let mut links = Vec::new();
+    let mut files = Vec::new();
+    for (note, project, tags) in notes {
+        let base = sanitize_file_name(&note.title);
+        let mut file_name = format!("{}.html", base);
+        if !used.insert(file_name.to_lowercase()) || file_name.eq_ignore_ascii_case("index.html") {
+            file_name = format!("{} ({}).html", base, short_id(&note.id));
+            used.insert(file_name.to_lowercase());
+        }
+
+        let path = dir.join(&file_name);
+        fs::write(&path, render_note_page(note, project.as_deref(), tags, Some("index.html")))
+            .map_err(|e| e.to_string())?;
+        links.push(file_name.replace(' ', "%20"));
+        files.push(path);
+    }
+
+    let index = dir.join("index.html");
+    fs::write(&index, html_page(title, &render_index(title, notes, &links))).map_err(|e| e.to_string())?;
+    files.push(index);
+
+    Ok(files)
+}
+
+/// 导出为单个 HTML 文件：开头是索引，随后依次是各篇笔记
+fn export_html_single_file(path: &Path, title: &str, notes: &[(Note, Option<String>, Vec<String>)]) -> Result<Vec<PathBuf>, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let links: Vec<String> = (0..notes.len()).map(|i| format!("#n{}-note", i + 1)).collect();
+    let mut body = render_index(title, notes, &links);
+    for (index, (note, project, tags)) in notes.iter().enumerate() {
+        let (section, _) = render_note_section(note, project.as_deref(), tags, &format!("n{}-", index + 1), true);
+        body.push_str(&section);
+    }
+
+    fs::write(path, html_page(title, &body)).map_err(|e| e.to_string())?;
+    Ok(vec![path.to_path_buf()])
+}
+
+/// 把 Markdown 渲染为 HTML 片段，供前端预览使用
+#[tauri::command]
+pub async fn render_note_html(content: String) -> Result<String, String> {
+    Ok(render_markdown(&content, "").0)
+}
+
+/// 导出单篇笔记为独立的 HTML 文件（内嵌样式，包含目录）
+#[tauri::command]
+pub async fn export_note_html(note_id: String, path: String, db: State<'_, Database>) -> Result<HtmlExportReport, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let (note, project, tags) = load_vault_note(&conn, &note_id).map_err(|e| e.to_string())?
+        .ok_or("Note not found")?;
+
+    let target = PathBuf::from(&path);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&target, render_note_page(&note, project.as_deref(), &tags, None)).map_err(|e| e.to_string())?;
+
+    Ok(HtmlExportReport {
+        path,
+        notes: 1,
+        files: vec![target.to_string_lossy().to_string()],
+    })
+}
+
+/// 导出一组笔记：按项目或标签筛选。
+/// `single_file` 为 true 时 `path` 是输出文件，否则是输出目录（包含 index.html 索引页）。
+#[tauri::command]
+pub async fn export_notes_html(
+    path: String,
+    project_id: Option<String>,
+    tag_ids: Option<Vec<String>>,
+    single_file: Option<bool>,
+    title: Option<String>,
+    db: State<'_, Database>
+) -> Result<HtmlExportReport, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let tag_ids = tag_ids.unwrap_or_default();
+    let notes = select_notes(&conn, project_id.as_deref(), &tag_ids).map_err(|e| e.to_string())?;
+    if notes.is_empty() {
+        return Err("No notes match the selection".to_string());
+    }
+
+    let title = match (title, &project_id) {
+        (Some(title), _) => title,
+        (None, Some(_)) => notes[0].1.clone().unwrap_or_else(|| "Notes".to_string()),
+        (None, None) => "Notes".to_string(),
+    };
+
+    let files = if single_file.unwrap_or(false) {
+        export_html_single_file(Path::new(&path), &title, &notes)?
+    } else {
+        export_html_site(Path::new(&path), &title, &notes)?
+    };
+
+    Ok(HtmlExportReport {
+        path,
+        notes: notes.len(),
+        files: files.iter().map(|f| f.to_string_lossy().to_string()).collect(),
+    })
+}
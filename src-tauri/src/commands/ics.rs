@@ -0,0 +1,418 @@
+use crate::db::{self, Database};
+use crate::commands::activity::{entity_title, log_activity};
+use crate::commands::journal::{id_key, ChangeSet};
+use tauri::{AppHandle, Manager, State};
+use rusqlite::{params, Connection};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::time::Duration as StdDuration;
+
+// 订阅文件的设置键：导出路径与可选的项目筛选
+pub const ICS_SUBSCRIPTION_PATH_SETTING: &str = "ics_subscription_path";
+pub const ICS_SUBSCRIPTION_PROJECT_SETTING: &str = "ics_subscription_project";
+
+// 刷新订阅文件的间隔
+const ICS_REFRESH_SECS: u64 = 60;
+
+const ICS_PRODUCT_ID: &str = "-//Productivity Notebook//Tasks//EN";
+
+// RFC 5545 中 RRULE 允许的规则项
+const RRULE_KEYS: &[&str] = &[
+    "FREQ", "UNTIL", "COUNT", "INTERVAL", "BYSECOND", "BYMINUTE", "BYHOUR", "BYDAY",
+    "BYMONTHDAY", "BYYEARDAY", "BYWEEKNO", "BYMONTH", "BYSETPOS", "WKST",
+];
+
+struct IcsTask {
+    id: String,
+    title: String,
+    description: Option<String>,
+    is_completed: bool,
+    priority: i32,
+    due_date: Option<DateTime<Utc>>,
+    start_date: Option<DateTime<Utc>>,
+    remind_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    parent_id: Option<String>,
+    recurrence: Option<String>,
+    categories: Vec<String>,
+}
+
+struct IcsSession {
+    id: String,
+    started_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
+    duration: i32,
+    task_title: Option<String>,
+    notes: Option<String>,
+}
+
+pub fn ics_datetime(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// 按 RFC 5545 转义文本值
+pub fn ics_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// 超过 75 字节的行需要折叠，续行以空格开头
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += len;
+    }
+    out.push_str("\r\n");
+}
+
+/// 任务优先级 1-4 映射为 iCalendar 的 1（最高）到 9（最低）
+fn ics_priority(priority: i32) -> u8 {
+    match priority {
+        1 => 1,
+        2 => 3,
+        3 => 5,
+        _ => 9,
+    }
+}
+
+fn parse_time(value: Option<String>, idx: usize, name: &str) -> Result<Option<DateTime<Utc>>, rusqlite::Error> {
+    value.map(|s| s.parse()).transpose()
+        .map_err(|_| rusqlite::Error::InvalidColumnType(idx, name.to_string(), rusqlite::types::Type::Text))
+}
+
+fn load_ics_tasks(conn: &Connection, project_id: Option<&str>) -> Result<Vec<IcsTask>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.title, t.description, t.is_completed, t.priority, t.due_date, t.remind_at,
+                t.created_at, t.updated_at, t.parent_id, t.recurrence, p.name,
+                CASE WHEN t.is_completed = 1 THEN COALESCE(t.completed_at, t.updated_at) END, t.start_date
+         FROM tasks t
+         LEFT JOIN projects p ON p.id = t.project_id AND p.deleted_at IS NULL
+         WHERE t.deleted_at IS NULL AND (?1 IS NULL OR t.project_id = ?1)
+         ORDER BY t.position, t.created_at"
+    )?;
+
+    let task_iter = stmt.query_map(params![project_id], |row| {
+        Ok(IcsTask {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            description: row.get(2)?,
+            is_completed: row.get::<_, i32>(3)? != 0,
+            priority: row.get(4)?,
+            due_date: parse_time(row.get(5)?, 5, "due_date")?,
+            start_date: parse_time(row.get(13)?, 13, "start_date")?,
+            remind_at: parse_time(row.get(6)?, 6, "remind_at")?,
+            created_at: row.get::<_, String>(7)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(7, "created_at".to_string(), rusqlite::types::Type::Text))?,
+            updated_at: row.get::<_, String>(8)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(8, "updated_at".to_string(), rusqlite::types::Type::Text))?,
+            completed_at: parse_time(row.get(12)?, 12, "completed_at")?,
+            parent_id: row.get(9)?,
+            recurrence: row.get(10)?,
+            categories: row.get::<_, Option<String>>(11)?.into_iter().collect(),
+        })
+    })?;
+
+    let mut tag_stmt = conn.prepare(
+        "SELECT tg.name FROM tags tg INNER JOIN task_tags tt ON tt.tag_id = tg.id WHERE tt.task_id = ? ORDER BY tg.name"
+    )?;
+
+    let mut tasks = Vec::new();
+    for task in task_iter {
+        let mut task = task?;
+        let tags = tag_stmt.query_map(params![task.id], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        task.categories.extend(tags);
+        tasks.push(task);
+    }
+
+    Ok(tasks)
+}
+
+fn load_ics_sessions(conn: &Connection, project_id: Option<&str>) -> Result<Vec<IcsSession>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT s.id, s.started_at, s.ended_at, s.duration, t.title, s.notes
+         FROM pomodoro_sessions s
+         LEFT JOIN tasks t ON t.id = s.task_id
+         WHERE s.is_completed = 1 AND s.phase = 'work' AND (?1 IS NULL OR t.project_id = ?1)
+         ORDER BY s.started_at"
+    )?;
+
+    let session_iter = stmt.query_map(params![project_id], |row| {
+        Ok(IcsSession {
+            id: row.get(0)?,
+            started_at: row.get::<_, String>(1)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(1, "started_at".to_string(), rusqlite::types::Type::Text))?,
+            ended_at: parse_time(row.get(2)?, 2, "ended_at")?,
+            duration: row.get(3)?,
+            task_title: row.get(4)?,
+            notes: row.get(5)?,
+        })
+    })?;
+
+    session_iter.collect()
+}
+
+fn write_vtodo(out: &mut String, task: &IcsTask, stamp: &DateTime<Utc>) {
+    push_line(out, "BEGIN:VTODO");
+    push_line(out, &format!("UID:{}", task.id));
+    push_line(out, &format!("DTSTAMP:{}", ics_datetime(stamp)));
+    push_line(out, &format!("CREATED:{}", ics_datetime(&task.created_at)));
+    push_line(out, &format!("LAST-MODIFIED:{}", ics_datetime(&task.updated_at)));
+    push_line(out, &format!("SUMMARY:{}", ics_escape(&task.title)));
+
+    if let Some(description) = task.description.as_deref().filter(|d| !d.is_empty()) {
+        push_line(out, &format!("DESCRIPTION:{}", ics_escape(description)));
+    }
+    if let Some(due) = &task.due_date {
+        push_line(out, &format!("DUE:{}", ics_datetime(due)));
+    }
+
+    // RRULE 需要 DTSTART 作为重复的起点，且 DTSTART 必须早于 DUE：
+    // 优先使用开始时间，否则取截止时间前一天
+    // 导入的数据没有经过校验，无效的规则不写入
+    if let Some(rule) = task.recurrence.as_deref().filter(|r| validate_rrule(r).is_ok()) {
+        if let Some(due) = &task.due_date {
+            let start = task.start_date
+                .filter(|start| start < due)
+                .unwrap_or_else(|| *due - Duration::days(1));
+            push_line(out, &format!("DTSTART:{}", ics_datetime(&start)));
+            push_line(out, &format!("RRULE:{}", rule));
+        }
+    }
+
+    push_line(out, &format!("PRIORITY:{}", ics_priority(task.priority)));
+
+    if task.is_completed {
+        push_line(out, "STATUS:COMPLETED");
+        push_line(out, "PERCENT-COMPLETE:100");
+        if let Some(completed_at) = &task.completed_at {
+            push_line(out, &format!("COMPLETED:{}", ics_datetime(completed_at)));
+        }
+    } else {
+        push_line(out, "STATUS:NEEDS-ACTION");
+    }
+
+    if let Some(parent_id) = &task.parent_id {
+        push_line(out, &format!("RELATED-TO:{}", parent_id));
+    }
+    if !task.categories.is_empty() {
+        let categories: Vec<String> = task.categories.iter().map(|c| ics_escape(c)).collect();
+        push_line(out, &format!("CATEGORIES:{}", categories.join(",")));
+    }
+
+    if let (Some(remind_at), false) = (&task.remind_at, task.is_completed) {
+        push_line(out, "BEGIN:VALARM");
+        push_line(out, "ACTION:DISPLAY");
+        push_line(out, &format!("DESCRIPTION:{}", ics_escape(&task.title)));
+        push_line(out, &format!("TRIGGER;VALUE=DATE-TIME:{}", ics_datetime(remind_at)));
+        push_line(out, "END:VALARM");
+    }
+
+    push_line(out, "END:VTODO");
+}
+
+fn write_vevent(out: &mut String, session: &IcsSession, stamp: &DateTime<Utc>) {
+    let ended_at = session.ended_at
+        .unwrap_or_else(|| session.started_at + Duration::seconds(session.duration as i64));
+    let summary = match &session.task_title {
+        Some(title) => format!("Pomodoro: {}", title),
+        None => "Pomodoro".to_string(),
+    };
+
+    push_line(out, "BEGIN:VEVENT");
+    push_line(out, &format!("UID:{}", session.id));
+    push_line(out, &format!("DTSTAMP:{}", ics_datetime(stamp)));
+    push_line(out, &format!("DTSTART:{}", ics_datetime(&session.started_at)));
+    push_line(out, &format!("DTEND:{}", ics_datetime(&ended_at)));
+    push_line(out, &format!("SUMMARY:{}", ics_escape(&summary)));
+    if let Some(notes) = session.notes.as_deref().filter(|n| !n.is_empty()) {
+        push_line(out, &format!("DESCRIPTION:{}", ics_escape(notes)));
+    }
+    push_line(out, "TRANSP:TRANSPARENT");
+    push_line(out, "END:VEVENT");
+}
+
+/// 生成包含任务（VTODO）和已完成番茄钟（VEVENT）的日历，`stamp` 为导出时间（DTSTAMP）。
+/// 除 DTSTAMP 外输出只依赖数据内容，方便日历客户端订阅。
+pub fn build_calendar(conn: &Connection, project_id: Option<&str>, stamp: DateTime<Utc>) -> Result<String, rusqlite::Error> {
+    let calendar_name: String = match project_id {
+        Some(id) => conn.query_row("SELECT name FROM projects WHERE id = ?", params![id], |row| row.get(0))?,
+        None => "Productivity Notebook".to_string(),
+    };
+
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{}", ICS_PRODUCT_ID));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", ics_escape(&calendar_name)));
+
+    for task in load_ics_tasks(conn, project_id)? {
+        write_vtodo(&mut out, &task, &stamp);
+    }
+    for session in load_ics_sessions(conn, project_id)? {
+        write_vevent(&mut out, &session, &stamp);
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    Ok(out)
+}
+
+/// 去掉 DTSTAMP 行，用于比较两次生成的日历内容是否相同
+fn without_dtstamp(calendar: &str) -> Vec<&str> {
+    calendar.split("\r\n").filter(|line| !line.starts_with("DTSTAMP:")).collect()
+}
+
+/// 重新生成订阅文件，除导出时间外内容未变化时不写入
+fn refresh_subscription(conn: &Connection) -> Result<bool, String> {
+    let Some(path) = db::get_setting(conn, ICS_SUBSCRIPTION_PATH_SETTING).map_err(|e| e.to_string())? else {
+        return Ok(false);
+    };
+    let project_id = db::get_setting(conn, ICS_SUBSCRIPTION_PROJECT_SETTING).map_err(|e| e.to_string())?
+        .filter(|id| !id.is_empty());
+
+    let calendar = build_calendar(conn, project_id.as_deref(), Utc::now()).map_err(|e| e.to_string())?;
+    if fs::read_to_string(&path).is_ok_and(|existing| without_dtstamp(&existing) == without_dtstamp(&calendar)) {
+        return Ok(false);
+    }
+
+    fs::write(&path, calendar).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 启动后台任务，定期刷新日历订阅文件
+pub fn spawn_ics_subscription(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            {
+                let database = app.state::<Database>();
+                if let Ok(conn) = database.get_connection() {
+                    if let Err(e) = refresh_subscription(&conn) {
+                        log::warn!("Failed to refresh calendar subscription: {}", e);
+                    }
+                }
+            }
+            tokio::time::sleep(StdDuration::from_secs(ICS_REFRESH_SECS)).await;
+        }
+    });
+}
+
+/// 导出 iCalendar 日历，可按项目筛选；指定路径时同时写入文件
+#[tauri::command]
+pub async fn export_ics(
+    path: Option<String>,
+    project_id: Option<String>,
+    db: State<'_, Database>
+) -> Result<String, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let calendar = build_calendar(&conn, project_id.as_deref(), Utc::now()).map_err(|e| e.to_string())?;
+    if let Some(path) = path {
+        fs::write(path, &calendar).map_err(|e| e.to_string())?;
+    }
+
+    Ok(calendar)
+}
+
+/// 设置自动刷新的日历订阅文件，`path` 为空时关闭订阅
+#[tauri::command]
+pub async fn set_ics_subscription(
+    path: Option<String>,
+    project_id: Option<String>,
+    db: State<'_, Database>
+) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    match path {
+        Some(path) => {
+            db::set_setting(&conn, ICS_SUBSCRIPTION_PATH_SETTING, &path).map_err(|e| e.to_string())?;
+            db::set_setting(&conn, ICS_SUBSCRIPTION_PROJECT_SETTING, project_id.as_deref().unwrap_or(""))
+                .map_err(|e| e.to_string())?;
+            refresh_subscription(&conn)?;
+        }
+        None => {
+            conn.execute(
+                "DELETE FROM settings WHERE key IN (?, ?)",
+                params![ICS_SUBSCRIPTION_PATH_SETTING, ICS_SUBSCRIPTION_PROJECT_SETTING]
+            ).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 检查重复规则：只允许 RRULE 中出现的字符和已知的规则项，规则会原样写入导出的日历
+pub fn validate_rrule(rule: &str) -> Result<(), String> {
+    let invalid = || format!("Invalid recurrence rule: {}", rule);
+
+    if !rule.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || "=;,+-".contains(c)) {
+        return Err(invalid());
+    }
+
+    let mut keys = HashSet::new();
+    for part in rule.split(';') {
+        let (key, value) = part.split_once('=').filter(|(k, v)| !k.is_empty() && !v.is_empty()).ok_or_else(invalid)?;
+        if !RRULE_KEYS.contains(&key) || !keys.insert(key) {
+            return Err(invalid());
+        }
+        let valid_value = match key {
+            "FREQ" => matches!(value, "DAILY" | "WEEKLY" | "MONTHLY" | "YEARLY"),
+            "COUNT" | "INTERVAL" => value.parse::<u32>().is_ok_and(|n| n > 0),
+            _ => !value.contains('='),
+        };
+        if !valid_value {
+            return Err(invalid());
+        }
+    }
+
+    if !keys.contains("FREQ") || (keys.contains("COUNT") && keys.contains("UNTIL")) {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+/// 设置任务的重复规则（iCalendar RRULE，例如 "FREQ=WEEKLY;BYDAY=MO"），传入空值清除
+#[tauri::command]
+pub async fn set_task_recurrence(id: String, rule: Option<String>, db: State<'_, Database>) -> Result<(), String> {
+    let rule = rule.map(|r| r.trim().trim_start_matches("RRULE:").to_uppercase()).filter(|r| !r.is_empty());
+
+    if let Some(rule) = &rule {
+        validate_rrule(rule)?;
+    }
+
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let mut change = ChangeSet::new("set_task_recurrence");
+    change.track(&tx, "tasks", id_key(&id)).map_err(|e| e.to_string())?;
+
+    let updated = tx.execute(
+        "UPDATE tasks SET recurrence = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL",
+        params![rule, Utc::now().to_rfc3339(), id]
+    ).map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err("Task not found".to_string());
+    }
+
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    let title = entity_title(&tx, "task", &id).map_err(|e| e.to_string())?;
+    let summary = match &rule {
+        Some(rule) => format!("Task \"{}\" set to repeat ({})", title, rule),
+        None => format!("Task \"{}\" no longer repeats", title),
+    };
+    log_activity(&tx, "task", &id, "updated", &summary).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
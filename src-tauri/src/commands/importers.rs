@@ -0,0 +1,973 @@
+use crate::models::{ImportReport, ImportSkip};
+use crate::db::Database;
+use crate::commands::activity::log_activity;
+use crate::commands::projects::find_or_create_project;
+use crate::commands::tags::{find_or_create_tag, link_tag_to_note};
+use tauri::State;
+use rusqlite::{params, Connection};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use uuid::Uuid;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 任务优先级：1 最高，4 最低（无优先级），与 Todoist 的 p1-p4 对应；新建任务默认为 3。
+// todo.txt 的 (A)、(B) 对应 1、2，(C) 及之后的字母对应 4，没有优先级的行对应 3。
+// 导出 todo.txt 时 3 和 4 都不写优先级；同步时优先级没有变化的行保留文件中原来的字母
+pub const HIGHEST_PRIORITY: i32 = 1;
+pub const DEFAULT_PRIORITY: i32 = 3;
+pub const LOWEST_PRIORITY: i32 = 4;
+
+/// 导入过程中新建任务所需的字段
+pub struct ImportedTask {
+    pub title: String,
+    pub description: Option<String>,
+    pub is_completed: bool,
+    pub priority: i32,
+    pub due_date: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub project_id: Option<String>,
+    pub parent_id: Option<String>,
+}
+
+impl ImportReport {
+    pub fn new(source: &str) -> Self {
+        ImportReport {
+            source: source.to_string(),
+            notes_created: 0,
+            tasks_created: 0,
+            tasks_updated: 0,
+            projects: Vec::new(),
+            tags: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    pub fn skip(&mut self, item: impl Into<String>, reason: impl Into<String>) {
+        self.skipped.push(ImportSkip {
+            item: item.into(),
+            reason: reason.into(),
+        });
+    }
+
+    pub fn use_project(&mut self, name: &str) {
+        if !self.projects.iter().any(|p| p == name) {
+            self.projects.push(name.to_string());
+        }
+    }
+
+    fn use_tag(&mut self, name: &str) {
+        if !self.tags.iter().any(|t| t == name) {
+            self.tags.push(name.to_string());
+        }
+    }
+}
+
+pub fn insert_imported_note(
+    conn: &Connection,
+    title: &str,
+    content: &str,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    project_id: Option<&str>,
+    source: &str,
+) -> Result<String, rusqlite::Error> {
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO notes (id, title, content, created_at, updated_at, project_id)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![id, title, content, created_at.to_rfc3339(), updated_at.to_rfc3339(), project_id]
+    )?;
+
+    log_activity(conn, "note", &id, "imported", &format!("Note \"{}\" imported from {}", title, source))?;
+
+    Ok(id)
+}
+
+pub fn insert_imported_task(conn: &Connection, task: &ImportedTask, source: &str) -> Result<String, rusqlite::Error> {
+    let id = Uuid::new_v4().to_string();
+    let position: i32 = conn.query_row(
+        "SELECT COALESCE(MAX(position), -1) + 1 FROM tasks WHERE parent_id IS ?",
+        params![task.parent_id],
+        |row| row.get(0)
+    )?;
+
+    let now = Utc::now();
+    conn.execute(
+        "INSERT INTO tasks (id, title, description, is_completed, priority, due_date, remind_at,
+                            created_at, updated_at, project_id, parent_id, position, completed_at)
+         VALUES (?, ?, ?, ?, ?, ?, NULL, ?, ?, ?, ?, ?, ?)",
+        params![
+            id, task.title, task.description, task.is_completed as i32, task.priority,
+            task.due_date.map(|d| d.to_rfc3339()),
+            task.created_at.to_rfc3339(), now.to_rfc3339(),
+            task.project_id, task.parent_id, position,
+            if task.is_completed { Some(now.to_rfc3339()) } else { None }
+        ]
+    )?;
+
+    log_activity(conn, "task", &id, "imported", &format!("Task \"{}\" imported from {}", task.title, source))?;
+
+    Ok(id)
+}
+
+fn create_kanban_column(conn: &Connection, project_id: &str, name: &str) -> Result<String, rusqlite::Error> {
+    let id = Uuid::new_v4().to_string();
+    let position: i32 = conn.query_row(
+        "SELECT COALESCE(MAX(position), -1) + 1 FROM kanban_columns WHERE project_id = ?",
+        params![project_id],
+        |row| row.get(0)
+    )?;
+
+    conn.execute(
+        "INSERT INTO kanban_columns (id, project_id, name, position) VALUES (?, ?, ?, ?)",
+        params![id, project_id, name, position]
+    )?;
+
+    Ok(id)
+}
+
+fn add_task_to_column(conn: &Connection, task_id: &str, column_id: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR IGNORE INTO column_tasks (task_id, column_id, position)
+         VALUES (?, ?, (SELECT COALESCE(MAX(position), -1) + 1 FROM column_tasks WHERE column_id = ?))",
+        params![task_id, column_id, column_id]
+    )?;
+    Ok(())
+}
+
+fn local_to_utc(naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local.from_local_datetime(&naive).earliest().map(|dt| dt.with_timezone(&Utc))
+}
+
+/// 解析各种导出文件中常见的日期格式，没有时区的按本地时间处理
+pub fn parse_loose_date(value: &str) -> Option<DateTime<Utc>> {
+    parse_loose_date_with_time(value).map(|(dt, _)| dt)
+}
+
+/// 同 `parse_loose_date`，另外返回输入中是否包含时间，只有日期时结果为当天零点
+pub fn parse_loose_date_with_time(value: &str) -> Option<(DateTime<Utc>, bool)> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some((dt.with_timezone(&Utc), true));
+    }
+
+    const DATETIME_FORMATS: [&str; 6] = [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%B %d, %Y %I:%M %p",
+        "%b %d, %Y %I:%M %p",
+        "%Y/%m/%d %H:%M",
+    ];
+    for format in DATETIME_FORMATS {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+            return local_to_utc(naive).map(|dt| (dt, true));
+        }
+    }
+
+    const DATE_FORMATS: [&str; 5] = ["%Y-%m-%d", "%Y/%m/%d", "%B %d, %Y", "%b %d, %Y", "%d %b %Y"];
+    for format in DATE_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+            return date.and_hms_opt(0, 0, 0).and_then(local_to_utc).map(|dt| (dt, false));
+        }
+    }
+
+    None
+}
+
+/// 把文字形式的优先级（High、p1 等）转换为任务优先级
+fn parse_priority_label(value: &str) -> Option<i32> {
+    match value.trim().to_lowercase().as_str() {
+        "urgent" | "highest" | "critical" | "p1" => Some(1),
+        "high" | "p2" => Some(2),
+        "medium" | "normal" | "p3" => Some(3),
+        "low" | "lowest" | "p4" => Some(4),
+        _ => None,
+    }
+}
+
+/// 递归收集指定扩展名的文件，跳过隐藏文件和目录
+fn collect_files(dir: &Path, extension: &str, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let mut entries = fs::read_dir(dir).map_err(|e| e.to_string())?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    entries.sort();
+
+    for path in entries {
+        let hidden = path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.'));
+
+        if hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect_files(&path, extension, files)?;
+        } else if path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| ext.eq_ignore_ascii_case(extension)) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn files_to_import(path: &Path, extension: &str) -> Result<Vec<PathBuf>, String> {
+    if path.is_dir() {
+        let mut files = Vec::new();
+        collect_files(path, extension, &mut files)?;
+        Ok(files)
+    } else if path.exists() {
+        Ok(vec![path.to_path_buf()])
+    } else {
+        Err(format!("Path not found: {}", path.display()))
+    }
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn read_csv(path: &Path) -> Result<(Vec<String>, Vec<csv::StringRecord>), String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let text = text.trim_start_matches('\u{feff}');
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let headers = reader.headers().map_err(|e| e.to_string())?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    let records = reader.records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    Ok((headers, records))
+}
+
+// Evernote ENEX 导入
+
+fn parse_enex_date(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value.trim(), "%Y%m%dT%H%M%SZ")
+        .ok()
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+#[derive(Default)]
+struct EnmlOutput {
+    text: String,
+    media: usize,
+    encrypted: usize,
+}
+
+impl EnmlOutput {
+    fn ensure_newline(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        let mut last_space = self.text.ends_with(' ') || self.text.ends_with('\n') || self.text.is_empty();
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !last_space {
+                    self.text.push(' ');
+                    last_space = true;
+                }
+            } else {
+                self.text.push(c);
+                last_space = false;
+            }
+        }
+    }
+}
+
+fn enml_children(node: roxmltree::Node, out: &mut EnmlOutput, list_depth: usize) {
+    for child in node.children() {
+        enml_node(child, out, list_depth);
+    }
+}
+
+fn enml_node(node: roxmltree::Node, out: &mut EnmlOutput, list_depth: usize) {
+    if node.is_text() {
+        out.push_text(node.text().unwrap_or(""));
+        return;
+    }
+    if !node.is_element() {
+        return;
+    }
+
+    match node.tag_name().name() {
+        "br" => out.text.push('\n'),
+        "p" | "div" | "blockquote" | "tr" => {
+            out.ensure_newline();
+            enml_children(node, out, list_depth);
+            out.ensure_newline();
+        }
+        name @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+            out.ensure_newline();
+            out.text.push('\n');
+            let level = name[1..].parse::<usize>().unwrap_or(1);
+            out.text.push_str(&"#".repeat(level));
+            out.text.push(' ');
+            enml_children(node, out, list_depth);
+            out.text.push('\n');
+        }
+        "ul" | "ol" => {
+            out.ensure_newline();
+            enml_children(node, out, list_depth + 1);
+            out.ensure_newline();
+        }
+        "li" => {
+            out.ensure_newline();
+            out.text.push_str(&"  ".repeat(list_depth.saturating_sub(1)));
+            out.text.push_str("- ");
+            enml_children(node, out, list_depth);
+        }
+        "b" | "strong" => {
+            out.text.push_str("**");
+            enml_children(node, out, list_depth);
+            out.text.push_str("**");
+        }
+        "i" | "em" => {
+            out.text.push('*');
+            enml_children(node, out, list_depth);
+            out.text.push('*');
+        }
+        "code" => {
+            out.text.push('`');
+            enml_children(node, out, list_depth);
+            out.text.push('`');
+        }
+        "pre" => {
+            out.ensure_newline();
+            out.text.push_str("```\n");
+            out.text.push_str(node.descendants().filter_map(|n| n.text()).collect::<String>().trim_end());
+            out.text.push_str("\n```\n");
+        }
+        "a" => {
+            out.text.push('[');
+            enml_children(node, out, list_depth);
+            out.text.push_str("](");
+            out.text.push_str(node.attribute("href").unwrap_or(""));
+            out.text.push(')');
+        }
+        "hr" => {
+            out.ensure_newline();
+            out.text.push_str("\n---\n");
+        }
+        "td" | "th" => {
+            enml_children(node, out, list_depth);
+            out.text.push_str(" | ");
+        }
+        "en-todo" => {
+            let checked = node.attribute("checked") == Some("true");
+            out.text.push_str(if checked { "[x] " } else { "[ ] " });
+        }
+        "en-media" => out.media += 1,
+        "en-crypt" => out.encrypted += 1,
+        _ => enml_children(node, out, list_depth),
+    }
+}
+
+/// 将 ENML（Evernote 的 XHTML 子集）转换为 Markdown
+fn enml_to_markdown(enml: &str) -> EnmlOutput {
+    // ENML 依赖外部 DTD 定义的 HTML 实体，解析前替换常见的几个
+    let prepared = enml
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&mdash;", "—")
+        .replace("&ndash;", "–")
+        .replace("&hellip;", "…")
+        .replace("&rsquo;", "’")
+        .replace("&lsquo;", "‘")
+        .replace("&rdquo;", "”")
+        .replace("&ldquo;", "“");
+
+    let options = roxmltree::ParsingOptions { allow_dtd: true, ..Default::default() };
+    let mut out = EnmlOutput::default();
+
+    match roxmltree::Document::parse_with_options(&prepared, options) {
+        Ok(doc) => enml_children(doc.root_element(), &mut out, 0),
+        Err(_) => {
+            // 内容不是合法的 XML 时退化为去除标签的纯文本
+            let mut in_tag = false;
+            for c in prepared.chars() {
+                match c {
+                    '<' => in_tag = true,
+                    '>' => in_tag = false,
+                    _ if !in_tag => out.text.push(c),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let mut text = out.text.replace('\u{a0}', " ");
+    while text.contains("\n\n\n") {
+        text = text.replace("\n\n\n", "\n\n");
+    }
+    out.text = text.lines().map(|line| line.trim_end()).collect::<Vec<_>>().join("\n").trim().to_string();
+    out
+}
+
+fn import_enex_file(conn: &Connection, path: &Path, report: &mut ImportReport) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let options = roxmltree::ParsingOptions { allow_dtd: true, ..Default::default() };
+    let doc = roxmltree::Document::parse_with_options(&text, options)
+        .map_err(|e| format!("{}: invalid ENEX file: {}", path.display(), e))?;
+
+    // Evernote 按笔记本导出 ENEX 文件，文件名即笔记本名称，对应到项目
+    let notebook = file_stem(path);
+    let project_id = find_or_create_project(conn, &notebook).map_err(|e| e.to_string())?;
+    report.use_project(&notebook);
+
+    for (index, note) in doc.root_element().children().filter(|n| n.has_tag_name("note")).enumerate() {
+        let child_text = |name: &str| {
+            note.children()
+                .find(|n| n.has_tag_name(name))
+                .map(|n| n.descendants().filter_map(|d| d.text()).collect::<String>())
+        };
+
+        let title = child_text("title")
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| format!("Untitled note {}", index + 1));
+        let item = format!("{}: {}", notebook, title);
+
+        let Some(content) = child_text("content") else {
+            report.skip(item, "note has no content element");
+            continue;
+        };
+
+        let converted = enml_to_markdown(&content);
+        let created_at = child_text("created").and_then(|d| parse_enex_date(&d)).unwrap_or_else(Utc::now);
+        let updated_at = child_text("updated").and_then(|d| parse_enex_date(&d)).unwrap_or(created_at);
+
+        let note_id = insert_imported_note(conn, &title, &converted.text, created_at, updated_at, Some(&project_id), "Evernote")
+            .map_err(|e| e.to_string())?;
+        report.notes_created += 1;
+
+        for tag in note.children().filter(|n| n.has_tag_name("tag")) {
+            let name = tag.text().unwrap_or("").trim();
+            if name.is_empty() {
+                continue;
+            }
+            let tag_id = find_or_create_tag(conn, name).map_err(|e| e.to_string())?;
+            link_tag_to_note(conn, &note_id, &tag_id).map_err(|e| e.to_string())?;
+            report.use_tag(name);
+        }
+
+        let resources = note.children().filter(|n| n.has_tag_name("resource")).count();
+        if resources > 0 || converted.media > 0 {
+            report.skip(&item, format!("{} attachment(s) not imported", resources.max(converted.media)));
+        }
+        if converted.encrypted > 0 {
+            report.skip(&item, format!("{} encrypted block(s) not imported", converted.encrypted));
+        }
+    }
+
+    Ok(())
+}
+
+// Notion 导入
+
+/// 去掉 Notion 导出文件名末尾的 32 位十六进制页面 id
+fn strip_notion_id(name: &str) -> String {
+    match name.rsplit_once(' ') {
+        Some((title, id)) if id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit()) => title.to_string(),
+        _ => name.to_string(),
+    }
+}
+
+fn find_column(headers: &[String], candidates: &[&str]) -> Option<usize> {
+    candidates.iter()
+        .find_map(|candidate| headers.iter().position(|h| h.eq_ignore_ascii_case(candidate)))
+}
+
+fn is_done_value(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "done" | "complete" | "completed" | "yes" | "true" | "closed" | "✅"
+    )
+}
+
+/// 解析 Notion 页面：首行标题作为笔记标题，其余作为正文
+fn parse_notion_page(path: &Path, text: &str) -> (String, String) {
+    let text = text.trim_start_matches('\u{feff}');
+    match text.lines().next() {
+        Some(first) if first.starts_with("# ") => {
+            let body = text[first.len()..].trim_start_matches(['\r', '\n']).to_string();
+            (first[2..].trim().to_string(), body)
+        }
+        _ => (strip_notion_id(&file_stem(path)), text.to_string()),
+    }
+}
+
+fn import_notion_database(
+    conn: &Connection,
+    path: &Path,
+    name: &str,
+    row_pages: &HashMap<String, (PathBuf, String)>,
+    consumed: &mut HashSet<PathBuf>,
+    report: &mut ImportReport,
+) -> Result<(), String> {
+    let (headers, records) = read_csv(path)?;
+    if headers.is_empty() {
+        report.skip(name, "database export has no columns");
+        return Ok(());
+    }
+
+    let project_id = find_or_create_project(conn, name).map_err(|e| e.to_string())?;
+    report.use_project(name);
+
+    let title_col = find_column(&headers, &["Name", "Title", "Task"]).unwrap_or(0);
+    let status_col = find_column(&headers, &["Status", "Done", "Completed", "Complete"]);
+    let due_col = find_column(&headers, &["Due", "Due Date", "Deadline", "Date"]);
+    let priority_col = find_column(&headers, &["Priority"]);
+    let created_col = find_column(&headers, &["Created", "Created time", "Created Time"]);
+    let mapped: Vec<usize> = [Some(title_col), status_col, due_col, priority_col, created_col]
+        .into_iter()
+        .flatten()
+        .collect();
+
+    for (index, record) in records.iter().enumerate() {
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).map(str::trim).unwrap_or("");
+
+        let title = field(Some(title_col)).to_string();
+        if title.is_empty() {
+            report.skip(format!("{} row {}", name, index + 1), "row has no title");
+            continue;
+        }
+        let item = format!("{}: {}", name, title);
+
+        let due_value = field(due_col);
+        let due_date = parse_loose_date(due_value.split(" → ").next().unwrap_or(""));
+        if !due_value.is_empty() && due_date.is_none() {
+            report.skip(&item, format!("due date \"{}\" not recognised", due_value));
+        }
+
+        let priority_value = field(priority_col);
+        let priority = parse_priority_label(priority_value).unwrap_or(3);
+        if !priority_value.is_empty() && parse_priority_label(priority_value).is_none() {
+            report.skip(&item, format!("priority \"{}\" not recognised", priority_value));
+        }
+
+        // 未映射的列以"列名: 值"的形式保留在描述中
+        let mut description: Vec<String> = headers.iter()
+            .enumerate()
+            .filter(|(i, _)| !mapped.contains(i))
+            .filter_map(|(i, header)| {
+                let value = record.get(i).map(str::trim).unwrap_or("");
+                (!value.is_empty()).then(|| format!("{}: {}", header, value))
+            })
+            .collect();
+
+        if let Some((page_path, body)) = row_pages.get(&title) {
+            if !body.trim().is_empty() {
+                description.push(String::new());
+                description.push(body.trim().to_string());
+            }
+            consumed.insert(page_path.clone());
+        }
+
+        let task = ImportedTask {
+            title,
+            description: (!description.is_empty()).then(|| description.join("\n")),
+            is_completed: is_done_value(field(status_col)),
+            priority,
+            due_date,
+            created_at: parse_loose_date(field(created_col)).unwrap_or_else(Utc::now),
+            project_id: Some(project_id.clone()),
+            parent_id: None,
+        };
+        insert_imported_task(conn, &task, "Notion").map_err(|e| e.to_string())?;
+        report.tasks_created += 1;
+    }
+
+    Ok(())
+}
+
+/// 导入 Notion 导出目录：页面导入为笔记，数据库（CSV）导入为项目下的任务
+fn import_notion_export(conn: &Connection, root: &Path, report: &mut ImportReport) -> Result<(), String> {
+    let mut csv_files = Vec::new();
+    collect_files(root, "csv", &mut csv_files)?;
+    let mut md_files = Vec::new();
+    collect_files(root, "md", &mut md_files)?;
+
+    // Notion 同时导出当前视图（X.csv）和全部行（X_all.csv），优先使用后者
+    let all_exports: HashSet<PathBuf> = csv_files.iter()
+        .filter_map(|p| {
+            let stem = file_stem(p);
+            stem.strip_suffix("_all").map(|base| p.with_file_name(format!("{}.csv", base)))
+        })
+        .collect();
+
+    let mut consumed = HashSet::new();
+    for path in &csv_files {
+        if all_exports.contains(path) {
+            continue;
+        }
+
+        let stem = file_stem(path);
+        let base = stem.strip_suffix("_all").unwrap_or(&stem).to_string();
+        let name = strip_notion_id(&base);
+
+        // 数据库的每一行在同名目录下还有一个页面，正文合并到任务描述
+        let rows_dir = path.with_file_name(&base);
+        let mut row_pages = HashMap::new();
+        for md in md_files.iter().filter(|md| md.parent() == Some(rows_dir.as_path())) {
+            let text = fs::read_to_string(md).map_err(|e| e.to_string())?;
+            let (title, body) = parse_notion_page(md, &text);
+            row_pages.insert(title, (md.clone(), body));
+        }
+
+        import_notion_database(conn, path, &name, &row_pages, &mut consumed, report)?;
+    }
+
+    for path in &md_files {
+        if consumed.contains(path) {
+            continue;
+        }
+
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                report.skip(path.display().to_string(), e.to_string());
+                continue;
+            }
+        };
+
+        let (title, body) = parse_notion_page(path, &text);
+        let now = Utc::now();
+        insert_imported_note(conn, &title, &body, now, now, None, "Notion").map_err(|e| e.to_string())?;
+        report.notes_created += 1;
+    }
+
+    Ok(())
+}
+
+// Todoist 导入
+
+/// 拆出任务内容中的 @标签，返回去掉标签的标题和标签列表
+fn split_todoist_labels(content: &str) -> (String, Vec<String>) {
+    let mut labels = Vec::new();
+    let mut words = Vec::new();
+    for word in content.split_whitespace() {
+        match word.strip_prefix('@') {
+            Some(label) if !label.is_empty() => labels.push(label.to_string()),
+            _ => words.push(word),
+        }
+    }
+    (words.join(" "), labels)
+}
+
+fn labels_description(description: &str, labels: &[String]) -> Option<String> {
+    let mut parts = Vec::new();
+    if !description.trim().is_empty() {
+        parts.push(description.trim().to_string());
+    }
+    if !labels.is_empty() {
+        parts.push(format!("Labels: {}", labels.join(", ")));
+    }
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+/// 导入 Todoist 的项目 CSV 模板：文件名为项目名，section 对应看板列，INDENT 表示子任务层级
+fn import_todoist_csv(conn: &Connection, path: &Path, report: &mut ImportReport) -> Result<(), String> {
+    let (headers, records) = read_csv(path)?;
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let (Some(type_col), Some(content_col)) = (column("TYPE"), column("CONTENT")) else {
+        return Err(format!("{}: not a Todoist CSV export", path.display()));
+    };
+    let description_col = column("DESCRIPTION");
+    let priority_col = column("PRIORITY");
+    let indent_col = column("INDENT");
+    let date_col = column("DATE");
+
+    let project_name = file_stem(path);
+    let project_id = find_or_create_project(conn, &project_name).map_err(|e| e.to_string())?;
+    report.use_project(&project_name);
+
+    let mut parents: Vec<(usize, String)> = Vec::new();
+    let mut current_column: Option<String> = None;
+    let mut last_task: Option<String> = None;
+
+    for (index, record) in records.iter().enumerate() {
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).map(str::trim).unwrap_or("");
+        let content = field(Some(content_col));
+        let item = format!("{} row {}", project_name, index + 2);
+
+        match field(Some(type_col)).to_lowercase().as_str() {
+            "" | "meta" => {}
+            "section" => {
+                current_column = Some(create_kanban_column(conn, &project_id, content).map_err(|e| e.to_string())?);
+                parents.clear();
+            }
+            "task" => {
+                let (title, labels) = split_todoist_labels(content);
+                if title.is_empty() {
+                    report.skip(item, "task has no content");
+                    continue;
+                }
+
+                let indent = field(indent_col).parse::<usize>().unwrap_or(1).max(1);
+                while parents.last().is_some_and(|(level, _)| *level >= indent) {
+                    parents.pop();
+                }
+
+                let date_value = field(date_col);
+                let due_date = parse_loose_date(date_value);
+                if !date_value.is_empty() && due_date.is_none() {
+                    report.skip(format!("{}: {}", project_name, title), format!("due date \"{}\" not recognised", date_value));
+                }
+
+                let task = ImportedTask {
+                    title,
+                    description: labels_description(field(description_col), &labels),
+                    is_completed: false,
+                    priority: field(priority_col).parse::<i32>().unwrap_or(LOWEST_PRIORITY).clamp(HIGHEST_PRIORITY, LOWEST_PRIORITY),
+                    due_date,
+                    created_at: Utc::now(),
+                    project_id: Some(project_id.clone()),
+                    parent_id: parents.last().map(|(_, id)| id.clone()),
+                };
+                let task_id = insert_imported_task(conn, &task, "Todoist").map_err(|e| e.to_string())?;
+                report.tasks_created += 1;
+
+                if let (Some(column_id), None) = (&current_column, &task.parent_id) {
+                    add_task_to_column(conn, &task_id, column_id).map_err(|e| e.to_string())?;
+                }
+
+                parents.push((indent, task_id.clone()));
+                last_task = Some(task_id);
+            }
+            "note" => match &last_task {
+                // 评论追加到上一条任务的描述
+                Some(task_id) => {
+                    conn.execute(
+                        "UPDATE tasks SET description = COALESCE(description || char(10) || char(10), '') || ? WHERE id = ?",
+                        params![content, task_id]
+                    ).map_err(|e| e.to_string())?;
+                }
+                None => report.skip(item, "comment is not attached to a task"),
+            },
+            other => report.skip(item, format!("unknown row type \"{}\"", other)),
+        }
+    }
+
+    Ok(())
+}
+
+fn json_id(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn json_str<'a>(value: &'a serde_json::Value, key: &str) -> &'a str {
+    value.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// 导入 Todoist 的 JSON 数据（同步 API 备份或 REST API 的任务列表）
+fn import_todoist_json(conn: &Connection, path: &Path, report: &mut ImportReport) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: serde_json::Value = serde_json::from_str(&text)
+        .map_err(|e| format!("{}: invalid JSON: {}", path.display(), e))?;
+
+    let empty = Vec::new();
+    let (projects, sections, items) = match &value {
+        serde_json::Value::Array(items) => (&empty, &empty, items),
+        serde_json::Value::Object(map) => {
+            let list = |key: &str| map.get(key).and_then(|v| v.as_array()).unwrap_or(&empty);
+            let items = if map.contains_key("items") { list("items") } else { list("tasks") };
+            (list("projects"), list("sections"), items)
+        }
+        _ => return Err(format!("{}: not a Todoist export", path.display())),
+    };
+
+    let mut project_ids: HashMap<String, String> = HashMap::new();
+    for project in projects {
+        let (Some(source_id), name) = (project.get("id").and_then(json_id), json_str(project, "name")) else {
+            report.skip("project", "project has no id");
+            continue;
+        };
+        if name.is_empty() {
+            report.skip(format!("project {}", source_id), "project has no name");
+            continue;
+        }
+        let id = find_or_create_project(conn, name).map_err(|e| e.to_string())?;
+        report.use_project(name);
+        project_ids.insert(source_id, id);
+    }
+
+    // 没有项目信息的任务归入以文件名命名的项目
+    let fallback_name = file_stem(path);
+    let fallback_project = |report: &mut ImportReport| -> Result<String, String> {
+        report.use_project(&fallback_name);
+        find_or_create_project(conn, &fallback_name).map_err(|e| e.to_string())
+    };
+
+    let mut column_ids: HashMap<String, String> = HashMap::new();
+    let mut section_order: Vec<&serde_json::Value> = sections.iter().collect();
+    section_order.sort_by_key(|s| s.get("section_order").or_else(|| s.get("order")).and_then(|o| o.as_i64()).unwrap_or(0));
+    for section in section_order {
+        let Some(source_id) = section.get("id").and_then(json_id) else { continue };
+        let project_id = match section.get("project_id").and_then(json_id).and_then(|p| project_ids.get(&p).cloned()) {
+            Some(id) => id,
+            None => fallback_project(report)?,
+        };
+        let id = create_kanban_column(conn, &project_id, json_str(section, "name")).map_err(|e| e.to_string())?;
+        column_ids.insert(source_id, id);
+    }
+
+    let mut sorted_items: Vec<&serde_json::Value> = items.iter().collect();
+    sorted_items.sort_by_key(|i| i.get("child_order").or_else(|| i.get("order")).and_then(|o| o.as_i64()).unwrap_or(0));
+
+    let mut task_ids: HashMap<String, String> = HashMap::new();
+    let mut pending_parents: Vec<(String, String)> = Vec::new();
+    for item in sorted_items {
+        let content = json_str(item, "content").trim();
+        let Some(source_id) = item.get("id").and_then(json_id) else {
+            report.skip(content, "task has no id");
+            continue;
+        };
+        if content.is_empty() {
+            report.skip(format!("task {}", source_id), "task has no content");
+            continue;
+        }
+
+        let project_id = match item.get("project_id").and_then(json_id).and_then(|p| project_ids.get(&p).cloned()) {
+            Some(id) => id,
+            None => fallback_project(report)?,
+        };
+
+        let due = item.get("due").filter(|d| !d.is_null());
+        let due_value = due.map(|d| {
+            let datetime = json_str(d, "datetime");
+            if datetime.is_empty() { json_str(d, "date") } else { datetime }
+        }).unwrap_or("");
+        let due_date = parse_loose_date(due_value);
+        if !due_value.is_empty() && due_date.is_none() {
+            report.skip(content, format!("due date \"{}\" not recognised", due_value));
+        }
+        if let Some(due) = due.filter(|d| d.get("is_recurring").and_then(|r| r.as_bool()) == Some(true)) {
+            report.skip(content, format!("recurrence \"{}\" not imported", json_str(due, "string")));
+        }
+
+        let labels: Vec<String> = item.get("labels")
+            .and_then(|l| l.as_array())
+            .map(|l| l.iter().filter_map(json_id).collect())
+            .unwrap_or_default();
+
+        // API 中 4 表示最高优先级（p1）
+        let api_priority = item.get("priority").and_then(|p| p.as_i64()).unwrap_or(1) as i32;
+        let is_completed = item.get("checked").or_else(|| item.get("is_completed")).and_then(|c| c.as_bool()).unwrap_or(false)
+            || item.get("completed_at").is_some_and(|c| !c.is_null());
+
+        let task = ImportedTask {
+            title: content.to_string(),
+            description: labels_description(json_str(item, "description"), &labels),
+            is_completed,
+            priority: (5 - api_priority).clamp(HIGHEST_PRIORITY, LOWEST_PRIORITY),
+            due_date,
+            created_at: parse_loose_date(json_str(item, "added_at"))
+                .or_else(|| parse_loose_date(json_str(item, "created_at")))
+                .unwrap_or_else(Utc::now),
+            project_id: Some(project_id),
+            parent_id: None,
+        };
+        let task_id = insert_imported_task(conn, &task, "Todoist").map_err(|e| e.to_string())?;
+        report.tasks_created += 1;
+
+        if let Some(column_id) = item.get("section_id").and_then(json_id).and_then(|s| column_ids.get(&s)) {
+            add_task_to_column(conn, &task_id, column_id).map_err(|e| e.to_string())?;
+        }
+        if let Some(parent) = item.get("parent_id").and_then(json_id) {
+            pending_parents.push((task_id.clone(), parent));
+        }
+        task_ids.insert(source_id, task_id);
+    }
+
+    // 父任务可能出现在子任务之后，全部插入后再建立层级
+    for (task_id, parent) in pending_parents {
+        match task_ids.get(&parent) {
+            Some(parent_id) => {
+                conn.execute("UPDATE tasks SET parent_id = ? WHERE id = ?", params![parent_id, task_id])
+                    .map_err(|e| e.to_string())?;
+            }
+            None => report.skip(format!("task {}", task_id), format!("parent task {} not found in export", parent)),
+        }
+    }
+
+    Ok(())
+}
+
+/// 导入 Evernote 的 ENEX 文件（或包含多个 ENEX 文件的目录），每个笔记本对应一个项目
+#[tauri::command]
+pub async fn import_evernote(path: String, db: State<'_, Database>) -> Result<ImportReport, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let files = files_to_import(Path::new(&path), "enex")?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut report = ImportReport::new("evernote");
+    for file in files {
+        import_enex_file(&tx, &file, &mut report)?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(report)
+}
+
+/// 导入解压后的 Notion 导出目录（Markdown & CSV 格式）
+#[tauri::command]
+pub async fn import_notion(directory: String, db: State<'_, Database>) -> Result<ImportReport, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let root = Path::new(&directory);
+    if !root.is_dir() {
+        return Err(format!("Directory not found: {}", directory));
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut report = ImportReport::new("notion");
+    import_notion_export(&tx, root, &mut report)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(report)
+}
+
+/// 导入 Todoist 导出，支持项目 CSV、CSV 目录和 JSON 备份
+#[tauri::command]
+pub async fn import_todoist(path: String, db: State<'_, Database>) -> Result<ImportReport, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let source = Path::new(&path);
+    let mut files = files_to_import(source, "csv")?;
+    if source.is_dir() {
+        collect_files(source, "json", &mut files)?;
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut report = ImportReport::new("todoist");
+    for file in files {
+        match file.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref() {
+            Some("csv") => import_todoist_csv(&tx, &file, &mut report)?,
+            Some("json") => import_todoist_json(&tx, &file, &mut report)?,
+            _ => report.skip(file.display().to_string(), "unsupported file type"),
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(report)
+}
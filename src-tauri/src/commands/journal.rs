@@ -0,0 +1,340 @@
+use crate::models::JournalEntry;
+use crate::db::Database;
+use tauri::State;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use chrono::Utc;
+
+// 日志中最多保留的操作条数
+pub const MAX_JOURNAL_ENTRIES: i64 = 200;
+
+// 允许通过日志回放写入的表
+const JOURNALED_TABLES: &[&str] = &[
+    "notes", "tasks", "projects", "tags", "note_tags", "milestones", "project_status_history", "task_dependencies",
+    "kanban_columns", "column_tasks", "task_tags",
+];
+
+pub type RowKey = Vec<(String, Value)>;
+
+/// 单行数据在一次操作前后的状态，`None` 表示该行不存在
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RowChange {
+    table: String,
+    key: Map<String, Value>,
+    before: Option<Map<String, Value>>,
+    after: Option<Map<String, Value>>,
+}
+
+/// 记录一次变更操作涉及的行。
+///
+/// 在修改前调用 `track` 记录原始状态，修改完成后调用 `commit`
+/// 读取新状态并写入日志，撤销时按相反顺序恢复原始状态。
+pub struct ChangeSet {
+    label: String,
+    changes: Vec<RowChange>,
+}
+
+impl ChangeSet {
+    pub fn new(label: impl Into<String>) -> Self {
+        ChangeSet {
+            label: label.into(),
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn track(&mut self, conn: &Connection, table: &str, key: RowKey) -> Result<(), rusqlite::Error> {
+        let key: Map<String, Value> = key.into_iter().collect();
+        let before = load_row(conn, table, &key)?;
+        self.changes.push(RowChange {
+            table: table.to_string(),
+            key,
+            before,
+            after: None,
+        });
+        Ok(())
+    }
+
+    /// 记录刚由其他函数创建的行，修改前的状态为不存在
+    pub fn track_created(&mut self, table: &str, key: RowKey) -> Result<(), rusqlite::Error> {
+        check_table(table)?;
+        self.changes.push(RowChange {
+            table: table.to_string(),
+            key: key.into_iter().collect(),
+            before: None,
+            after: None,
+        });
+        Ok(())
+    }
+
+    /// 按 id 记录一组行
+    pub fn track_ids(&mut self, conn: &Connection, table: &str, ids: &[String]) -> Result<(), rusqlite::Error> {
+        for id in ids {
+            self.track(conn, table, id_key(id))?;
+        }
+        Ok(())
+    }
+
+    pub fn commit(mut self, conn: &Connection) -> Result<(), rusqlite::Error> {
+        for change in &mut self.changes {
+            change.after = load_row(conn, &change.table, &change.key)?;
+        }
+        self.changes.retain(|change| change.before != change.after);
+
+        if self.changes.is_empty() {
+            return Ok(());
+        }
+
+        let changes = serde_json::to_string(&self.changes)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+        // 新操作会使重做栈失效
+        conn.execute("DELETE FROM undo_journal WHERE undone = 1", [])?;
+        conn.execute(
+            "INSERT INTO undo_journal (label, changes, created_at, undone) VALUES (?, ?, ?, 0)",
+            params![self.label, changes, Utc::now().to_rfc3339()]
+        )?;
+        conn.execute(
+            "DELETE FROM undo_journal WHERE id NOT IN (
+                SELECT id FROM undo_journal ORDER BY id DESC LIMIT ?
+            )",
+            params![MAX_JOURNAL_ENTRIES]
+        )?;
+
+        Ok(())
+    }
+}
+
+pub fn id_key(id: &str) -> RowKey {
+    vec![("id".to_string(), Value::String(id.to_string()))]
+}
+
+pub fn note_tag_key(note_id: &str, tag_id: &str) -> RowKey {
+    vec![
+        ("note_id".to_string(), Value::String(note_id.to_string())),
+        ("tag_id".to_string(), Value::String(tag_id.to_string())),
+    ]
+}
+
+pub fn task_tag_key(task_id: &str, tag_id: &str) -> RowKey {
+    vec![
+        ("task_id".to_string(), Value::String(task_id.to_string())),
+        ("tag_id".to_string(), Value::String(tag_id.to_string())),
+    ]
+}
+
+pub fn column_task_key(task_id: &str, column_id: &str) -> RowKey {
+    vec![
+        ("task_id".to_string(), Value::String(task_id.to_string())),
+        ("column_id".to_string(), Value::String(column_id.to_string())),
+    ]
+}
+
+pub fn dependency_key(task_id: &str, depends_on_id: &str) -> RowKey {
+    vec![
+        ("task_id".to_string(), Value::String(task_id.to_string())),
+        ("depends_on_id".to_string(), Value::String(depends_on_id.to_string())),
+    ]
+}
+
+fn check_identifier(name: &str) -> Result<(), rusqlite::Error> {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        Ok(())
+    } else {
+        Err(rusqlite::Error::InvalidColumnName(name.to_string()))
+    }
+}
+
+fn check_table(table: &str) -> Result<(), rusqlite::Error> {
+    if JOURNALED_TABLES.contains(&table) {
+        Ok(())
+    } else {
+        Err(rusqlite::Error::InvalidParameterName(table.to_string()))
+    }
+}
+
+fn to_json(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => Value::from(f),
+        ValueRef::Text(t) => Value::String(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Blob(b) => Value::from(b.to_vec()),
+    }
+}
+
+fn to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        Value::Array(items) => SqlValue::Blob(
+            items.iter().filter_map(|v| v.as_u64()).map(|v| v as u8).collect()
+        ),
+        Value::Object(_) => SqlValue::Text(value.to_string()),
+    }
+}
+
+fn where_clause(key: &Map<String, Value>) -> Result<(String, Vec<SqlValue>), rusqlite::Error> {
+    let mut parts = Vec::new();
+    let mut values = Vec::new();
+    for (column, value) in key {
+        check_identifier(column)?;
+        parts.push(format!("{} = ?", column));
+        values.push(to_sql(value));
+    }
+    Ok((parts.join(" AND "), values))
+}
+
+fn load_row(conn: &Connection, table: &str, key: &Map<String, Value>) -> Result<Option<Map<String, Value>>, rusqlite::Error> {
+    check_table(table)?;
+    let (clause, values) = where_clause(key)?;
+
+    let mut stmt = conn.prepare(&format!("SELECT * FROM {} WHERE {}", table, clause))?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+    stmt.query_row(params_from_iter(values), |row| {
+        let mut map = Map::new();
+        for (i, column) in columns.iter().enumerate() {
+            map.insert(column.clone(), to_json(row.get_ref(i)?));
+        }
+        Ok(map)
+    }).optional()
+}
+
+fn write_row(conn: &Connection, table: &str, key: &Map<String, Value>, state: Option<&Map<String, Value>>) -> Result<(), rusqlite::Error> {
+    check_table(table)?;
+
+    match state {
+        None => {
+            let (clause, values) = where_clause(key)?;
+            conn.execute(&format!("DELETE FROM {} WHERE {}", table, clause), params_from_iter(values))?;
+        }
+        Some(row) => {
+            let mut columns = Vec::new();
+            let mut values = Vec::new();
+            for (column, value) in row {
+                check_identifier(column)?;
+                columns.push(column.as_str());
+                values.push(to_sql(value));
+            }
+            let placeholders = vec!["?"; columns.len()].join(", ");
+            conn.execute(
+                &format!("INSERT OR REPLACE INTO {} ({}) VALUES ({})", table, columns.join(", "), placeholders),
+                params_from_iter(values)
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 回放一条日志。`undo` 为 true 时恢复修改前的状态，否则重新应用修改后的状态。
+fn replay(conn: &Connection, changes: &[RowChange], undo: bool) -> Result<(), String> {
+    // 当前数据必须与日志记录的结果一致，否则说明数据已被其他操作修改
+    for change in changes {
+        let expected = if undo { &change.after } else { &change.before };
+        let current = load_row(conn, &change.table, &change.key).map_err(|e| e.to_string())?;
+        if &current != expected {
+            return Err(format!("{} has changed since this operation and cannot be replayed", change.table));
+        }
+    }
+
+    if undo {
+        for change in changes.iter().rev() {
+            write_row(conn, &change.table, &change.key, change.before.as_ref()).map_err(|e| e.to_string())?;
+        }
+    } else {
+        for change in changes {
+            write_row(conn, &change.table, &change.key, change.after.as_ref()).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn step(conn: &mut Connection, undo: bool) -> Result<Option<JournalEntry>, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    // 撤销取最新的未撤销操作，重做取最早的已撤销操作
+    let query = if undo {
+        "SELECT id, label, changes, created_at FROM undo_journal WHERE undone = 0 ORDER BY id DESC LIMIT 1"
+    } else {
+        "SELECT id, label, changes, created_at FROM undo_journal WHERE undone = 1 ORDER BY id ASC LIMIT 1"
+    };
+
+    let entry: Option<(i64, String, String, String)> = tx.query_row(query, [], |row| {
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+    }).optional().map_err(|e| e.to_string())?;
+
+    let (id, label, changes, created_at) = match entry {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+
+    let changes: Vec<RowChange> = serde_json::from_str(&changes).map_err(|e| e.to_string())?;
+    replay(&tx, &changes, undo)?;
+
+    tx.execute(
+        "UPDATE undo_journal SET undone = ? WHERE id = ?",
+        params![if undo { 1 } else { 0 }, id]
+    ).map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(Some(JournalEntry {
+        id,
+        label,
+        created_at: created_at.parse().map_err(|_| "Invalid created_at in journal".to_string())?,
+        undone: undo,
+    }))
+}
+
+/// 撤销最近一次操作，没有可撤销的操作时返回 None
+#[tauri::command]
+pub async fn undo(db: State<'_, Database>) -> Result<Option<JournalEntry>, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    step(&mut conn, true)
+}
+
+/// 重做最近一次被撤销的操作，没有可重做的操作时返回 None
+#[tauri::command]
+pub async fn redo(db: State<'_, Database>) -> Result<Option<JournalEntry>, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    step(&mut conn, false)
+}
+
+#[tauri::command]
+pub async fn get_undo_history(limit: Option<i64>, db: State<'_, Database>) -> Result<Vec<JournalEntry>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, label, created_at, undone
+         FROM undo_journal
+         ORDER BY id DESC
+         LIMIT ?"
+    ).map_err(|e| e.to_string())?;
+
+    let entry_iter = stmt.query_map(params![limit.unwrap_or(MAX_JOURNAL_ENTRIES)], |row| {
+        Ok(JournalEntry {
+            id: row.get(0)?,
+            label: row.get(1)?,
+            created_at: row.get::<_, String>(2)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(2, "created_at".to_string(), rusqlite::types::Type::Text))?,
+            undone: row.get::<_, i32>(3)? != 0,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    let mut entries = Vec::new();
+    for entry in entry_iter {
+        entries.push(entry.map_err(|e| e.to_string())?);
+    }
+
+    Ok(entries)
+}
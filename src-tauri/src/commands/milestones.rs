@@ -0,0 +1,307 @@
+use crate::models::{Milestone, MilestoneProgress};
+use crate::db::Database;
+use crate::commands::journal::{id_key, ChangeSet};
+use crate::commands::activity::{entity_title, log_activity};
+use tauri::State;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+pub const MILESTONE_COLUMNS: &str = "id, project_id, name, description, due_date, completed_at, position, created_at, updated_at";
+
+pub fn milestone_from_row(row: &Row) -> Result<Milestone, rusqlite::Error> {
+    Ok(Milestone {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        description: row.get(3)?,
+        due_date: row.get::<_, Option<String>>(4)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(4, "due_date".to_string(), rusqlite::types::Type::Text))?,
+        completed_at: row.get::<_, Option<String>>(5)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(5, "completed_at".to_string(), rusqlite::types::Type::Text))?,
+        position: row.get(6)?,
+        created_at: row.get::<_, String>(7)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(7, "created_at".to_string(), rusqlite::types::Type::Text))?,
+        updated_at: row.get::<_, String>(8)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(8, "updated_at".to_string(), rusqlite::types::Type::Text))?,
+    })
+}
+
+fn parse_due_date(due_date: &str) -> Result<Option<DateTime<Utc>>, String> {
+    if due_date.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(due_date.parse().map_err(|_| "Invalid due_date format")?))
+    }
+}
+
+/// 任务只能关联到同一项目下的里程碑
+pub fn check_task_milestone(conn: &Connection, project_id: Option<&str>, milestone_id: &str) -> Result<(), String> {
+    let milestone_project: String = conn.query_row(
+        "SELECT project_id FROM milestones WHERE id = ?",
+        params![milestone_id],
+        |row| row.get(0)
+    ).optional().map_err(|e| e.to_string())?.ok_or("Milestone not found")?;
+
+    if project_id != Some(milestone_project.as_str()) {
+        return Err("Milestone belongs to a different project".to_string());
+    }
+    Ok(())
+}
+
+fn milestone_progress(milestone: Milestone, total_tasks: i64, completed_tasks: i64, now: DateTime<Utc>) -> MilestoneProgress {
+    let progress = if total_tasks > 0 { completed_tasks as f64 / total_tasks as f64 } else { 0.0 };
+    let is_overdue = milestone.completed_at.is_none() && milestone.due_date.is_some_and(|due| due < now);
+    MilestoneProgress { milestone, total_tasks, completed_tasks, progress, is_overdue }
+}
+
+/// 项目的里程碑及完成进度，按位置排序。进度只统计未删除的任务
+pub fn load_milestone_progress(conn: &Connection, project_id: &str) -> Result<Vec<MilestoneProgress>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {},
+                (SELECT COUNT(*) FROM tasks t WHERE t.milestone_id = milestones.id AND t.deleted_at IS NULL),
+                (SELECT COUNT(*) FROM tasks t WHERE t.milestone_id = milestones.id AND t.deleted_at IS NULL AND t.is_completed = 1)
+         FROM milestones WHERE project_id = ?
+         ORDER BY position, due_date",
+        MILESTONE_COLUMNS
+    ))?;
+
+    let now = Utc::now();
+    let rows = stmt.query_map(params![project_id], |row| {
+        Ok(milestone_progress(milestone_from_row(row)?, row.get(9)?, row.get(10)?, now))
+    })?;
+
+    let mut milestones = Vec::new();
+    for row in rows {
+        milestones.push(row?);
+    }
+    Ok(milestones)
+}
+
+fn load_milestone(conn: &Connection, id: &str) -> Result<Option<Milestone>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM milestones WHERE id = ?", MILESTONE_COLUMNS),
+        params![id],
+        milestone_from_row
+    ).optional().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_project_milestones(project_id: String, db: State<'_, Database>) -> Result<Vec<MilestoneProgress>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    load_milestone_progress(&conn, &project_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_milestone(
+    project_id: String,
+    name: String,
+    description: Option<String>,
+    due_date: Option<String>,
+    db: State<'_, Database>
+) -> Result<Milestone, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Milestone name cannot be empty".to_string());
+    }
+    let project_exists: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM projects WHERE id = ? AND deleted_at IS NULL",
+        params![project_id],
+        |row| row.get(0)
+    ).map_err(|e| e.to_string())?;
+    if !project_exists {
+        return Err("Project not found".to_string());
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+    let due_date = parse_due_date(due_date.as_deref().unwrap_or(""))?;
+    let position: i32 = tx.query_row(
+        "SELECT COALESCE(MAX(position), 0) + 1 FROM milestones WHERE project_id = ?",
+        params![project_id],
+        |row| row.get(0)
+    ).map_err(|e| e.to_string())?;
+
+    let mut change = ChangeSet::new("create_milestone");
+    change.track(&tx, "milestones", id_key(&id)).map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "INSERT INTO milestones (id, project_id, name, description, due_date, completed_at, position, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, NULL, ?, ?, ?)",
+        params![id, project_id, name, description, due_date.map(|d| d.to_rfc3339()), position, now.to_rfc3339(), now.to_rfc3339()]
+    ).map_err(|e| e.to_string())?;
+
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    log_activity(&tx, "milestone", &id, "created", &format!("Milestone \"{}\" created", name)).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(Milestone {
+        id,
+        project_id,
+        name,
+        description,
+        due_date,
+        completed_at: None,
+        position,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+/// 更新里程碑，只修改传入的字段。`due_date` 为空字符串时清除日期，
+/// `is_completed` 标记完成或重新打开
+#[tauri::command]
+pub async fn update_milestone(
+    id: String,
+    name: Option<String>,
+    description: Option<String>,
+    due_date: Option<String>,
+    position: Option<i32>,
+    is_completed: Option<bool>,
+    db: State<'_, Database>
+) -> Result<Milestone, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    let current = load_milestone(&tx, &id)?.ok_or("Milestone not found")?;
+
+    let mut change = ChangeSet::new("update_milestone");
+    change.track(&tx, "milestones", id_key(&id)).map_err(|e| e.to_string())?;
+
+    let mut query_parts = Vec::new();
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+    if let Some(name) = name {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("Milestone name cannot be empty".to_string());
+        }
+        query_parts.push("name = ?");
+        params_vec.push(Box::new(name));
+    }
+
+    if let Some(description) = description {
+        query_parts.push("description = ?");
+        params_vec.push(Box::new(description));
+    }
+
+    if let Some(due_date) = due_date {
+        query_parts.push("due_date = ?");
+        params_vec.push(Box::new(parse_due_date(&due_date)?.map(|d| d.to_rfc3339())));
+    }
+
+    if let Some(position) = position {
+        query_parts.push("position = ?");
+        params_vec.push(Box::new(position));
+    }
+
+    // 已完成的里程碑保留原来的完成时间
+    let (action, verb) = match is_completed {
+        Some(true) if current.completed_at.is_none() => ("completed", "completed"),
+        Some(false) if current.completed_at.is_some() => ("reopened", "reopened"),
+        _ => ("updated", "updated"),
+    };
+    match action {
+        "completed" => {
+            query_parts.push("completed_at = ?");
+            params_vec.push(Box::new(now.to_rfc3339()));
+        }
+        "reopened" => query_parts.push("completed_at = NULL"),
+        _ => {}
+    }
+
+    query_parts.push("updated_at = ?");
+    params_vec.push(Box::new(now.to_rfc3339()));
+    params_vec.push(Box::new(id.clone()));
+
+    let query = format!("UPDATE milestones SET {} WHERE id = ?", query_parts.join(", "));
+    tx.execute(&query, rusqlite::params_from_iter(params_vec.iter().map(|p| p.as_ref())))
+        .map_err(|e| e.to_string())?;
+
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    let summary = format!("Milestone \"{}\" {}", entity_title(&tx, "milestone", &id).map_err(|e| e.to_string())?, verb);
+    log_activity(&tx, "milestone", &id, action, &summary).map_err(|e| e.to_string())?;
+    let milestone = load_milestone(&tx, &id)?.ok_or("Milestone not found")?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(milestone)
+}
+
+/// 删除里程碑，关联的任务保留，只解除关联
+#[tauri::command]
+pub async fn delete_milestone(id: String, db: State<'_, Database>) -> Result<(), String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let name = entity_title(&tx, "milestone", &id).map_err(|e| e.to_string())?;
+    let project_id: Option<String> = tx.query_row(
+        "SELECT project_id FROM milestones WHERE id = ?",
+        params![id],
+        |row| row.get(0)
+    ).optional().map_err(|e| e.to_string())?;
+    let project_id = project_id.ok_or("Milestone not found")?;
+
+    let mut change = ChangeSet::new("delete_milestone");
+    change.track(&tx, "milestones", id_key(&id)).map_err(|e| e.to_string())?;
+    let task_ids: Vec<String> = {
+        let mut stmt = tx.prepare("SELECT id FROM tasks WHERE milestone_id = ?").map_err(|e| e.to_string())?;
+        let ids = stmt.query_map(params![id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| e.to_string())?;
+        ids
+    };
+    change.track_ids(&tx, "tasks", &task_ids).map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "UPDATE tasks SET milestone_id = NULL, updated_at = ? WHERE milestone_id = ?",
+        params![now.to_rfc3339(), id]
+    ).map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM milestones WHERE id = ?", params![id]).map_err(|e| e.to_string())?;
+
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    // 里程碑已删除，活动记录归到所属项目
+    log_activity(&tx, "project", &project_id, "milestone_deleted", &format!("Milestone \"{}\" deleted", name)).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// 把任务关联到里程碑，`milestone_id` 为空时解除关联
+#[tauri::command]
+pub async fn set_task_milestone(task_id: String, milestone_id: Option<String>, db: State<'_, Database>) -> Result<(), String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    let project_id: Option<String> = tx.query_row(
+        "SELECT project_id FROM tasks WHERE id = ? AND deleted_at IS NULL",
+        params![task_id],
+        |row| row.get(0)
+    ).optional().map_err(|e| e.to_string())?.ok_or("Task not found")?;
+
+    let milestone_id = milestone_id.filter(|id| !id.is_empty());
+    if let Some(milestone_id) = &milestone_id {
+        check_task_milestone(&tx, project_id.as_deref(), milestone_id)?;
+    }
+
+    let mut change = ChangeSet::new("set_task_milestone");
+    change.track(&tx, "tasks", id_key(&task_id)).map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "UPDATE tasks SET milestone_id = ?, updated_at = ? WHERE id = ?",
+        params![milestone_id, now.to_rfc3339(), task_id]
+    ).map_err(|e| e.to_string())?;
+
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    let task = entity_title(&tx, "task", &task_id).map_err(|e| e.to_string())?;
+    let summary = match &milestone_id {
+        Some(milestone_id) => format!("Task \"{}\" added to milestone \"{}\"", task, entity_title(&tx, "milestone", milestone_id).map_err(|e| e.to_string())?),
+        None => format!("Task \"{}\" removed from its milestone", task),
+    };
+    log_activity(&tx, "task", &task_id, "updated", &summary).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
@@ -3,10 +3,58 @@ pub mod tasks;
 pub mod pomodoro;
 pub mod projects;
 pub mod tags;
+pub mod revisions;
+pub mod trash;
+pub mod journal;
+pub mod activity;
+pub mod backup;
+pub mod vault;
+pub mod sync;
+pub mod data_export;
+pub mod importers;
+pub mod todotxt;
+pub mod ics;
+pub mod calendar;
+pub mod html_export;
+pub mod csv_export;
+pub mod opml;
+pub mod pomodoro_timer;
+pub mod focus_stats;
+pub mod pomodoro_recovery;
+pub mod reminders;
+pub mod focus_mode;
+pub mod milestones;
+pub mod project_templates;
+pub mod timeline;
+pub mod project_reports;
 
 // 重新导出所有命令函数
 pub use notes::*;
 pub use tasks::*;
 pub use pomodoro::*;
 pub use projects::*;
-pub use tags::*;
\ No newline at end of file
+pub use tags::*;
+pub use revisions::*;
+pub use trash::*;
+pub use journal::*;
+pub use activity::*;
+pub use backup::*;
+pub use vault::*;
+pub use sync::*;
+pub use data_export::*;
+pub use importers::*;
+pub use todotxt::*;
+pub use ics::*;
+pub use calendar::*;
+pub use html_export::*;
+pub use csv_export::*;
+pub use opml::*;
+pub use pomodoro_timer::*;
+pub use focus_stats::*;
+pub use pomodoro_recovery::*;
+pub use reminders::*;
+pub use focus_mode::*;
+pub use milestones::*;
+pub use project_templates::*;
+pub use timeline::*;
+pub use project_reports::*;
\ No newline at end of file
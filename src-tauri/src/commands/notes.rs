@@ -1,7 +1,11 @@
 use crate::models::Note;
 use crate::db::Database;
+use crate::commands::revisions::save_note_revision;
+use crate::commands::trash::soft_delete_note;
+use crate::commands::journal::{id_key, ChangeSet};
+use crate::commands::activity::{entity_title, log_activity};
 use tauri::State;
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
 use chrono::Utc;
 use uuid::Uuid;
 
@@ -12,6 +16,7 @@ pub async fn get_all_notes(db: State<'_, Database>) -> Result<Vec<Note>, String>
     let mut stmt = conn.prepare(
         "SELECT id, title, content, created_at, updated_at, project_id 
          FROM notes 
+         WHERE deleted_at IS NULL
          ORDER BY updated_at DESC"
     ).map_err(|e| e.to_string())?;
     
@@ -41,7 +46,7 @@ pub async fn get_note_by_id(id: String, db: State<'_, Database>) -> Result<Note,
     let mut stmt = conn.prepare(
         "SELECT id, title, content, created_at, updated_at, project_id 
          FROM notes 
-         WHERE id = ?"
+         WHERE id = ? AND deleted_at IS NULL"
     ).map_err(|e| e.to_string())?;
     
     let note = stmt.query_row(params![id], |row| {
@@ -60,17 +65,25 @@ pub async fn get_note_by_id(id: String, db: State<'_, Database>) -> Result<Note,
 
 #[tauri::command]
 pub async fn create_note(title: String, content: String, project_id: Option<String>, db: State<'_, Database>) -> Result<Note, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
     
     let id = Uuid::new_v4().to_string();
     let now = Utc::now();
     
-    conn.execute(
+    let mut change = ChangeSet::new("create_note");
+    change.track(&tx, "notes", id_key(&id)).map_err(|e| e.to_string())?;
+    
+    tx.execute(
         "INSERT INTO notes (id, title, content, created_at, updated_at, project_id) 
          VALUES (?, ?, ?, ?, ?, ?)",
         params![id, title, content, now.to_rfc3339(), now.to_rfc3339(), project_id]
     ).map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    log_activity(&tx, "note", &id, "created", &format!("Note \"{}\" created", title)).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(Note {
         id,
         title,
@@ -89,9 +102,38 @@ pub async fn update_note(
     project_id: Option<String>,
     db: State<'_, Database>
 ) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
     let now = Utc::now();
     
+    // 覆盖标题或内容前保存一条修订（受节流限制）
+    if title.is_some() || content.is_some() {
+        save_note_revision(&tx, &id, false).map_err(|e| e.to_string())?;
+    }
+    
+    let mut change = ChangeSet::new("update_note");
+    change.track(&tx, "notes", id_key(&id)).map_err(|e| e.to_string())?;
+    
+    // 移动到其他项目时单独记录
+    let note_title = match &title {
+        Some(title) => title.clone(),
+        None => entity_title(&tx, "note", &id).map_err(|e| e.to_string())?,
+    };
+    let current_project: Option<String> = tx.query_row(
+        "SELECT project_id FROM notes WHERE id = ?",
+        params![id],
+        |row| row.get(0)
+    ).optional().map_err(|e| e.to_string())?.flatten();
+    let (action, summary) = match &project_id {
+        Some(project_id) if current_project.as_ref() != Some(project_id) => ("moved", format!(
+            "Note \"{}\" moved to project \"{}\"",
+            note_title,
+            entity_title(&tx, "project", project_id).map_err(|e| e.to_string())?
+        )),
+        _ => ("updated", format!("Note \"{}\" updated", note_title)),
+    };
+    let note_id = id.clone();
+    
     let mut query_parts = Vec::new();
     let mut params_vec = Vec::new();
     
@@ -119,19 +161,33 @@ pub async fn update_note(
         query_parts.join(", ")
     );
     
-    conn.execute(&query, rusqlite::params_from_iter(params_vec))
+    tx.execute(&query, rusqlite::params_from_iter(params_vec))
         .map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    log_activity(&tx, "note", &note_id, action, &summary).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(())
 }
 
 #[tauri::command]
 pub async fn delete_note(id: String, db: State<'_, Database>) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    
+    let mut change = ChangeSet::new("delete_note");
+    change.track(&tx, "notes", id_key(&id)).map_err(|e| e.to_string())?;
     
-    conn.execute("DELETE FROM notes WHERE id = ?", params![id])
+    // 移入回收站，可通过 restore_from_trash 恢复
+    soft_delete_note(&tx, &id, &now.to_rfc3339())
         .map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    log_activity(&tx, "note", &id, "deleted", &format!("Note \"{}\" moved to trash", entity_title(&tx, "note", &id).map_err(|e| e.to_string())?)).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(())
 }
 
@@ -144,7 +200,7 @@ pub async fn search_notes(query: String, db: State<'_, Database>) -> Result<Vec<
     let mut stmt = conn.prepare(
         "SELECT id, title, content, created_at, updated_at, project_id 
          FROM notes 
-         WHERE title LIKE ? OR content LIKE ?
+         WHERE (title LIKE ? OR content LIKE ?) AND deleted_at IS NULL
          ORDER BY updated_at DESC"
     ).map_err(|e| e.to_string())?;
     
@@ -0,0 +1,348 @@
+use crate::models::ImportReport;
+use crate::db::Database;
+use crate::commands::html_export::escape_html;
+use crate::commands::importers::{
+    insert_imported_note, insert_imported_task, parse_loose_date, ImportedTask, HIGHEST_PRIORITY, LOWEST_PRIORITY,
+};
+use crate::commands::projects::find_or_create_project;
+use tauri::State;
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+// 大纲条目的类型，普通条目按任务处理
+const OUTLINE_TYPE_PROJECT: &str = "project";
+const OUTLINE_TYPE_NOTE: &str = "note";
+
+struct OpmlTask {
+    id: String,
+    title: String,
+    description: Option<String>,
+    is_completed: bool,
+    priority: i32,
+    due_date: Option<String>,
+    created_at: String,
+    parent_id: Option<String>,
+}
+
+/// 属性值转义，换行保留为字符引用，以便多行备注能原样导入
+fn xml_attr(value: &str) -> String {
+    escape_html(value)
+        .replace('\r', "")
+        .replace('\n', "&#10;")
+        .replace('\t', "&#9;")
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth + 2)
+}
+
+/// 读取项目的任务树。子任务不一定设置了 project_id，因此从项目的任务出发沿 parent_id 查找；
+/// 未归属项目时从没有未删除父任务的任务出发，避免重复导出其他项目下的子任务
+fn load_opml_tasks(conn: &Connection, project_id: Option<&str>, include_completed: bool) -> Result<Vec<OpmlTask>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE tree(id) AS (
+            SELECT t.id FROM tasks t
+            WHERE t.deleted_at IS NULL AND t.project_id IS ?1
+              AND (?1 IS NOT NULL OR NOT EXISTS (
+                  SELECT 1 FROM tasks p WHERE p.id = t.parent_id AND p.deleted_at IS NULL
+              ))
+            UNION
+            SELECT t.id FROM tasks t INNER JOIN tree ON t.parent_id = tree.id WHERE t.deleted_at IS NULL
+         )
+         SELECT id, title, description, is_completed, priority, due_date, created_at, parent_id
+         FROM tasks
+         WHERE id IN (SELECT id FROM tree)
+           AND (?2 OR is_completed = 0)
+         ORDER BY position, created_at"
+    )?;
+    let task_iter = stmt.query_map(params![project_id, include_completed], |row| {
+        Ok(OpmlTask {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            description: row.get(2)?,
+            is_completed: row.get::<_, i32>(3)? != 0,
+            priority: row.get(4)?,
+            due_date: row.get(5)?,
+            created_at: row.get(6)?,
+            parent_id: row.get(7)?,
+        })
+    })?;
+    task_iter.collect()
+}
+
+fn write_task_outlines(out: &mut String, tasks: &[OpmlTask], children: &HashMap<Option<&str>, Vec<usize>>, parent: Option<&str>, depth: usize) {
+    let Some(indices) = children.get(&parent) else {
+        return;
+    };
+
+    for &index in indices {
+        let task = &tasks[index];
+        out.push_str(&format!("{}<outline text=\"{}\"", indent(depth), xml_attr(&task.title)));
+        if let Some(description) = task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+            out.push_str(&format!(" _note=\"{}\"", xml_attr(description)));
+        }
+        if task.is_completed {
+            out.push_str(" _complete=\"true\"");
+        }
+        out.push_str(&format!(" priority=\"{}\"", task.priority));
+        if let Some(due_date) = &task.due_date {
+            out.push_str(&format!(" due=\"{}\"", xml_attr(due_date)));
+        }
+        out.push_str(&format!(" created=\"{}\"", xml_attr(&task.created_at)));
+
+        if children.contains_key(&Some(task.id.as_str())) {
+            out.push_str(">\n");
+            write_task_outlines(out, tasks, children, Some(&task.id), depth + 1);
+            out.push_str(&format!("{}</outline>\n", indent(depth)));
+        } else {
+            out.push_str("/>\n");
+        }
+    }
+}
+
+/// 输出一个项目（或未归属项目）的任务树，父任务不在导出范围内的任务作为顶层条目
+fn write_task_tree(out: &mut String, tasks: &[OpmlTask], depth: usize) {
+    let ids: HashSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+    let mut children: HashMap<Option<&str>, Vec<usize>> = HashMap::new();
+    for (index, task) in tasks.iter().enumerate() {
+        let parent = task.parent_id.as_deref().filter(|p| ids.contains(p));
+        children.entry(parent).or_default().push(index);
+    }
+    write_task_outlines(out, tasks, &children, None, depth);
+}
+
+fn write_note_outlines(conn: &Connection, out: &mut String, project_id: Option<&str>, depth: usize) -> Result<(), rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT title, content, created_at FROM notes
+         WHERE deleted_at IS NULL AND project_id IS ?
+         ORDER BY created_at"
+    )?;
+    let notes = stmt.query_map(params![project_id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+    })?;
+
+    for note in notes {
+        let (title, content, created_at) = note?;
+        out.push_str(&format!(
+            "{}<outline text=\"{}\" type=\"{}\" _note=\"{}\" created=\"{}\"/>\n",
+            indent(depth),
+            xml_attr(&title),
+            OUTLINE_TYPE_NOTE,
+            xml_attr(&content),
+            xml_attr(&created_at)
+        ));
+    }
+    Ok(())
+}
+
+/// 生成 OPML 文档。指定项目时只导出该项目的任务；
+/// 否则每个项目是一个 type="project" 的顶层条目，未归属项目的任务排在最后。
+pub fn build_opml(conn: &Connection, project_id: Option<&str>, include_completed: bool, include_notes: bool) -> Result<String, String> {
+    let mut body = String::new();
+
+    let title = match project_id {
+        Some(project_id) => {
+            let name: String = conn.query_row(
+                "SELECT name FROM projects WHERE id = ? AND deleted_at IS NULL",
+                params![project_id],
+                |row| row.get(0)
+            ).optional().map_err(|e| e.to_string())?.ok_or("Project not found")?;
+
+            let tasks = load_opml_tasks(conn, Some(project_id), include_completed).map_err(|e| e.to_string())?;
+            write_task_tree(&mut body, &tasks, 0);
+            if include_notes {
+                write_note_outlines(conn, &mut body, Some(project_id), 0).map_err(|e| e.to_string())?;
+            }
+            name
+        }
+        None => {
+            let mut stmt = conn.prepare("SELECT id, name FROM projects WHERE deleted_at IS NULL ORDER BY name")
+                .map_err(|e| e.to_string())?;
+            let projects = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+
+            for (id, name) in projects {
+                body.push_str(&format!("{}<outline text=\"{}\" type=\"{}\">\n", indent(0), xml_attr(&name), OUTLINE_TYPE_PROJECT));
+                let tasks = load_opml_tasks(conn, Some(&id), include_completed).map_err(|e| e.to_string())?;
+                write_task_tree(&mut body, &tasks, 1);
+                if include_notes {
+                    write_note_outlines(conn, &mut body, Some(&id), 1).map_err(|e| e.to_string())?;
+                }
+                body.push_str(&format!("{}</outline>\n", indent(0)));
+            }
+
+            let tasks = load_opml_tasks(conn, None, include_completed).map_err(|e| e.to_string())?;
+            write_task_tree(&mut body, &tasks, 0);
+            if include_notes {
+                write_note_outlines(conn, &mut body, None, 0).map_err(|e| e.to_string())?;
+            }
+            "Tasks".to_string()
+        }
+    };
+
+    Ok(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  <head>\n    <title>{}</title>\n    <dateCreated>{}</dateCreated>\n  </head>\n  <body>\n{}  </body>\n</opml>\n",
+        xml_attr(&title),
+        Utc::now().to_rfc2822(),
+        body
+    ))
+}
+
+// OPML 导入
+
+fn outline_completed(node: &roxmltree::Node) -> bool {
+    node.attribute("_complete").is_some_and(|v| v.eq_ignore_ascii_case("true"))
+        || node.attribute("_status").is_some_and(|v| v.eq_ignore_ascii_case("checked"))
+}
+
+fn outline_text(node: &roxmltree::Node) -> String {
+    node.attribute("text")
+        .or_else(|| node.attribute("title"))
+        .unwrap_or("")
+        .trim()
+        .to_string()
+}
+
+fn outline_time(node: &roxmltree::Node, name: &str) -> Option<DateTime<Utc>> {
+    node.attribute(name).and_then(parse_loose_date)
+}
+
+/// 递归导入大纲条目，文档顺序即任务顺序
+fn import_outlines(
+    conn: &Connection,
+    parent: roxmltree::Node,
+    project_id: Option<&str>,
+    parent_id: Option<&str>,
+    path: &str,
+    report: &mut ImportReport,
+) -> Result<(), String> {
+    for node in parent.children().filter(|n| n.has_tag_name("outline")) {
+        let text = outline_text(&node);
+        let item = if path.is_empty() { text.clone() } else { format!("{} / {}", path, text) };
+
+        match node.attribute("type").map(str::to_lowercase).as_deref() {
+            Some(OUTLINE_TYPE_PROJECT) if parent_id.is_none() && !text.is_empty() => {
+                let id = find_or_create_project(conn, &text).map_err(|e| e.to_string())?;
+                report.use_project(&text);
+                import_outlines(conn, node, Some(&id), None, &item, report)?;
+            }
+            Some(OUTLINE_TYPE_NOTE) => {
+                if text.is_empty() {
+                    report.skip(item, "note without title");
+                    continue;
+                }
+                let created_at = outline_time(&node, "created").unwrap_or_else(Utc::now);
+                insert_imported_note(conn, &text, node.attribute("_note").unwrap_or(""), created_at, created_at, project_id, "OPML")
+                    .map_err(|e| e.to_string())?;
+                report.notes_created += 1;
+            }
+            Some("link") | Some("rss") => report.skip(item, "feed and link outlines are not supported"),
+            _ => {
+                if text.is_empty() {
+                    report.skip(if item.is_empty() { "(untitled)".to_string() } else { item }, "outline without text");
+                    continue;
+                }
+
+                let task = ImportedTask {
+                    title: text,
+                    description: node.attribute("_note").filter(|n| !n.trim().is_empty()).map(str::to_string),
+                    is_completed: outline_completed(&node),
+                    priority: node.attribute("priority").and_then(|p| p.trim().parse::<i32>().ok()).unwrap_or(3)
+                        .clamp(HIGHEST_PRIORITY, LOWEST_PRIORITY),
+                    due_date: outline_time(&node, "due"),
+                    created_at: outline_time(&node, "created").unwrap_or_else(Utc::now),
+                    project_id: project_id.map(str::to_string),
+                    parent_id: parent_id.map(str::to_string),
+                };
+                let id = insert_imported_task(conn, &task, "OPML").map_err(|e| e.to_string())?;
+                report.tasks_created += 1;
+
+                import_outlines(conn, node, project_id, Some(&id), &item, report)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 导出任务树为 OPML，完成状态和描述分别写入 `_complete` 与 `_note` 属性
+#[tauri::command]
+pub async fn export_opml(
+    path: String,
+    project_id: Option<String>,
+    include_completed: Option<bool>,
+    include_notes: Option<bool>,
+    db: State<'_, Database>
+) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let opml = build_opml(&conn, project_id.as_deref(), include_completed.unwrap_or(true), include_notes.unwrap_or(false))?;
+
+    let target = Path::new(&path);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(target, opml).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// 导入 OPML 大纲：嵌套条目成为子任务。
+/// 未指定项目时任务放入以文档标题命名的项目，type="project" 的条目各自对应一个项目。
+#[tauri::command]
+pub async fn import_opml(path: String, project_id: Option<String>, db: State<'_, Database>) -> Result<ImportReport, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let text = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let doc = roxmltree::Document::parse(text.trim_start_matches('\u{feff}'))
+        .map_err(|e| format!("Invalid OPML: {}", e))?;
+    let body = doc.root_element()
+        .children()
+        .find(|n| n.has_tag_name("body"))
+        .ok_or("Invalid OPML: missing body")?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut report = ImportReport::new("opml");
+
+    // 只有存在非项目类型的顶层条目时才需要默认项目
+    let needs_project = body.children()
+        .filter(|n| n.has_tag_name("outline"))
+        .any(|n| !n.attribute("type").is_some_and(|t| t.eq_ignore_ascii_case(OUTLINE_TYPE_PROJECT)));
+
+    let project_id = match project_id {
+        Some(id) => {
+            let name: String = tx.query_row(
+                "SELECT name FROM projects WHERE id = ? AND deleted_at IS NULL",
+                params![id],
+                |row| row.get(0)
+            ).optional().map_err(|e| e.to_string())?.ok_or("Project not found")?;
+            report.use_project(&name);
+            Some(id)
+        }
+        None if needs_project => {
+            let title = doc.descendants()
+                .find(|n| n.has_tag_name("title"))
+                .and_then(|n| n.text())
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .unwrap_or_else(|| {
+                    Path::new(&path).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "OPML".to_string())
+                });
+            let id = find_or_create_project(&tx, &title).map_err(|e| e.to_string())?;
+            report.use_project(&title);
+            Some(id)
+        }
+        None => None,
+    };
+
+    import_outlines(&tx, body, project_id.as_deref(), None, "", &mut report)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(report)
+}
@@ -1,37 +1,104 @@
-use crate::models::PomodoroSession;
+use crate::models::{PomodoroInterruption, PomodoroSession};
 use crate::db::Database;
 use tauri::State;
-use rusqlite::params;
+use rusqlite::{params, Connection, Row};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+pub const SESSION_COLUMNS: &str = "id, started_at, ended_at, duration, is_completed, task_id, notes, phase, paused_seconds, abandoned, abandon_reason";
+
+// 中断类型：普通暂停，以及番茄工作法中的内部打断（自己分心）和外部打断（他人打扰）
+pub const INTERRUPTION_PAUSE: &str = "pause";
+pub const INTERRUPTION_INTERNAL: &str = "internal";
+pub const INTERRUPTION_EXTERNAL: &str = "external";
+
+pub fn validate_interruption_kind(kind: &str) -> Result<(), String> {
+    match kind {
+        INTERRUPTION_PAUSE | INTERRUPTION_INTERNAL | INTERRUPTION_EXTERNAL => Ok(()),
+        _ => Err(format!("Unknown interruption kind: {}", kind)),
+    }
+}
+
+pub fn session_from_row(row: &Row) -> Result<PomodoroSession, rusqlite::Error> {
+    Ok(PomodoroSession {
+        id: row.get(0)?,
+        started_at: row.get::<_, String>(1)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(1, "started_at".to_string(), rusqlite::types::Type::Text))?,
+        ended_at: row.get::<_, Option<String>>(2)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(2, "ended_at".to_string(), rusqlite::types::Type::Text))?,
+        duration: row.get(3)?,
+        is_completed: row.get::<_, i32>(4)? != 0,
+        task_id: row.get(5)?,
+        notes: row.get(6)?,
+        phase: row.get(7)?,
+        paused_seconds: row.get(8)?,
+        abandoned: row.get::<_, i32>(9)? != 0,
+        abandon_reason: row.get(10)?,
+    })
+}
+
+/// 写入一条番茄钟记录及其中断。计时服务在阶段开始时写入未结束的记录，
+/// 阶段完成或放弃时用最终结果覆盖。`duration` 是扣除暂停后的净专注秒数。
+pub fn record_session(conn: &Connection, session: &PomodoroSession, interruptions: &[PomodoroInterruption]) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR REPLACE INTO pomodoro_sessions (id, started_at, ended_at, duration, is_completed, task_id, notes, phase,
+                                                   paused_seconds, abandoned, abandon_reason, heartbeat_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            session.id, session.started_at.to_rfc3339(), session.ended_at.map(|d| d.to_rfc3339()),
+            session.duration, session.is_completed as i32, session.task_id, session.notes, session.phase,
+            session.paused_seconds, session.abandoned as i32, session.abandon_reason,
+            session.ended_at.unwrap_or(session.started_at).to_rfc3339()
+        ]
+    )?;
+
+    save_interruptions(conn, &session.id, interruptions)
+}
+
+pub fn save_interruptions(conn: &Connection, session_id: &str, interruptions: &[PomodoroInterruption]) -> Result<(), rusqlite::Error> {
+    for interruption in interruptions {
+        conn.execute(
+            "INSERT OR REPLACE INTO pomodoro_interruptions (id, session_id, kind, reason, started_at, ended_at, duration)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            params![
+                interruption.id, session_id, interruption.kind, interruption.reason,
+                interruption.started_at.to_rfc3339(), interruption.ended_at.map(|d| d.to_rfc3339()), interruption.duration
+            ]
+        )?;
+    }
+    Ok(())
+}
+
+/// 更新进行中会话的心跳，同时记下到此为止的暂停秒数
+pub fn write_heartbeat(conn: &Connection, session_id: &str, at: DateTime<Utc>, paused_seconds: i64) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE pomodoro_sessions SET heartbeat_at = ?, paused_seconds = ? WHERE id = ? AND ended_at IS NULL",
+        params![at.to_rfc3339(), paused_seconds, session_id]
+    )?;
+    Ok(())
+}
+
+/// 删除会话及其中断
+pub fn delete_session(conn: &Connection, session_id: &str) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM pomodoro_interruptions WHERE session_id = ?", params![session_id])?;
+    conn.execute("DELETE FROM pomodoro_sessions WHERE id = ?", params![session_id])?;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_all_pomodoro_sessions(db: State<'_, Database>) -> Result<Vec<PomodoroSession>, String> {
     let conn = db.get_connection().map_err(|e| e.to_string())?;
-    
-    let mut stmt = conn.prepare(
-        "SELECT id, task_id, duration_minutes, is_completed, started_at, completed_at, created_at
-         FROM pomodoro_sessions 
-         ORDER BY created_at DESC"
-    ).map_err(|e| e.to_string())?;
-    
-    let session_iter = stmt.query_map([], |row| {
-        Ok(PomodoroSession {
-            id: row.get(0)?,
-            task_id: row.get(1)?,
-            duration_minutes: row.get(2)?,
-            is_completed: row.get::<_, i32>(3)? != 0,
-            started_at: row.get::<_, String>(4)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(4, "started_at".to_string(), rusqlite::types::Type::Text))?,
-            completed_at: row.get::<_, Option<String>>(5)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(5, "completed_at".to_string(), rusqlite::types::Type::Text))?,
-            created_at: row.get::<_, String>(6)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(6, "created_at".to_string(), rusqlite::types::Type::Text))?,
-        })
-    }).map_err(|e| e.to_string())?;
-    
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM pomodoro_sessions ORDER BY started_at DESC",
+        SESSION_COLUMNS
+    )).map_err(|e| e.to_string())?;
+
+    let session_iter = stmt.query_map([], session_from_row).map_err(|e| e.to_string())?;
+
     let mut sessions = Vec::new();
     for session in session_iter {
         sessions.push(session.map_err(|e| e.to_string())?);
     }
-    
+
     Ok(sessions)
 }
 
@@ -42,50 +109,118 @@ pub async fn start_pomodoro_session(
     db: State<'_, Database>
 ) -> Result<PomodoroSession, String> {
     let conn = db.get_connection().map_err(|e| e.to_string())?;
-    
+
     let id = Uuid::new_v4().to_string();
     let now = Utc::now();
-    
+    let duration = duration_minutes * 60;
+
     conn.execute(
-        "INSERT INTO pomodoro_sessions (id, task_id, duration_minutes, is_completed, started_at, completed_at, created_at) 
-         VALUES (?, ?, ?, 0, ?, NULL, ?)",
-        params![id, task_id, duration_minutes, now.to_rfc3339(), now.to_rfc3339()]
+        "INSERT INTO pomodoro_sessions (id, started_at, ended_at, duration, is_completed, task_id, notes, phase, heartbeat_at)
+         VALUES (?, ?, NULL, ?, 0, ?, NULL, 'work', ?)",
+        params![id, now.to_rfc3339(), duration, task_id, now.to_rfc3339()]
     ).map_err(|e| e.to_string())?;
-    
+
     Ok(PomodoroSession {
         id,
-        task_id,
-        duration_minutes,
-        is_completed: false,
         started_at: now,
-        completed_at: None,
-        created_at: now,
+        ended_at: None,
+        duration,
+        is_completed: false,
+        task_id,
+        notes: None,
+        phase: "work".to_string(),
+        paused_seconds: 0,
+        abandoned: false,
+        abandon_reason: None,
     })
 }
 
+/// 前端计时的会话定期调用，崩溃后可以按最后一次心跳截断
+#[tauri::command]
+pub async fn heartbeat_pomodoro_session(id: String, db: State<'_, Database>) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "UPDATE pomodoro_sessions SET heartbeat_at = ? WHERE id = ? AND ended_at IS NULL",
+        params![Utc::now().to_rfc3339(), id]
+    ).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn complete_pomodoro_session(id: String, db: State<'_, Database>) -> Result<(), String> {
     let conn = db.get_connection().map_err(|e| e.to_string())?;
     let now = Utc::now();
-    
+
     conn.execute(
-        "UPDATE pomodoro_sessions SET is_completed = 1, completed_at = ? WHERE id = ?",
+        "UPDATE pomodoro_sessions SET is_completed = 1, ended_at = ? WHERE id = ?",
         params![now.to_rfc3339(), id]
     ).map_err(|e| e.to_string())?;
-    
+
     Ok(())
 }
 
+/// 放弃会话：保留记录并标记为放弃，时长按实际进行的时间计算
 #[tauri::command]
-pub async fn cancel_pomodoro_session(id: String, db: State<'_, Database>) -> Result<(), String> {
+pub async fn cancel_pomodoro_session(id: String, reason: Option<String>, db: State<'_, Database>) -> Result<(), String> {
     let conn = db.get_connection().map_err(|e| e.to_string())?;
-    
-    conn.execute("DELETE FROM pomodoro_sessions WHERE id = ?", params![id])
-        .map_err(|e| e.to_string())?;
-    
+    let now = Utc::now();
+
+    let (started_at, ended_at, duration): (String, Option<String>, i32) = conn.query_row(
+        "SELECT started_at, ended_at, duration FROM pomodoro_sessions WHERE id = ?",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    ).map_err(|e| e.to_string())?;
+
+    // 仍在进行的会话以当前时间结束，时长不超过计划时长
+    let (ended_at, duration) = match ended_at {
+        Some(ended_at) => (ended_at, duration),
+        None => {
+            let started_at: DateTime<Utc> = started_at.parse().map_err(|_| "Invalid started_at")?;
+            let elapsed = (now - started_at).num_seconds().clamp(0, duration as i64) as i32;
+            (now.to_rfc3339(), elapsed)
+        }
+    };
+
+    conn.execute(
+        "UPDATE pomodoro_sessions SET is_completed = 0, abandoned = 1, abandon_reason = ?, ended_at = ?, duration = ?
+         WHERE id = ?",
+        params![reason.filter(|r| !r.trim().is_empty()), ended_at, duration, id]
+    ).map_err(|e| e.to_string())?;
+
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_session_interruptions(session_id: String, db: State<'_, Database>) -> Result<Vec<PomodoroInterruption>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, session_id, kind, reason, started_at, ended_at, duration
+         FROM pomodoro_interruptions WHERE session_id = ? ORDER BY started_at"
+    ).map_err(|e| e.to_string())?;
+
+    let interruption_iter = stmt.query_map(params![session_id], |row| {
+        Ok(PomodoroInterruption {
+            id: row.get(0)?,
+            session_id: row.get(1)?,
+            kind: row.get(2)?,
+            reason: row.get(3)?,
+            started_at: row.get::<_, String>(4)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(4, "started_at".to_string(), rusqlite::types::Type::Text))?,
+            ended_at: row.get::<_, Option<String>>(5)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(5, "ended_at".to_string(), rusqlite::types::Type::Text))?,
+            duration: row.get(6)?,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    let mut interruptions = Vec::new();
+    for interruption in interruption_iter {
+        interruptions.push(interruption.map_err(|e| e.to_string())?);
+    }
+
+    Ok(interruptions)
+}
+
 #[tauri::command]
 pub async fn get_pomodoro_stats(
     start_date: Option<String>,
@@ -93,69 +228,82 @@ pub async fn get_pomodoro_stats(
     db: State<'_, Database>
 ) -> Result<serde_json::Value, String> {
     let conn = db.get_connection().map_err(|e| e.to_string())?;
-    
-    let mut query = "SELECT 
-        COUNT(*) as total_sessions,
-        COUNT(CASE WHEN is_completed = 1 THEN 1 END) as completed_sessions,
-        SUM(CASE WHEN is_completed = 1 THEN duration_minutes ELSE 0 END) as total_minutes,
-        AVG(CASE WHEN is_completed = 1 THEN duration_minutes ELSE NULL END) as avg_duration
-        FROM pomodoro_sessions WHERE 1=1".to_string();
-    
-    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-    
-    if let Some(start_date) = start_date {
-        query.push_str(" AND created_at >= ?");
-        params_vec.push(Box::new(start_date));
-    }
-    
-    if let Some(end_date) = end_date {
-        query.push_str(" AND created_at <= ?");
-        params_vec.push(Box::new(end_date));
-    }
-    
+
+    // 会话数与平均时长只统计专注阶段，休息时间单独汇总。
+    // duration 是扣除暂停后的净专注时长，wall_clock_minutes 为开始到结束的实际经过时间。
+    // 进行中和等待恢复的会话还没有结果，不计入统计。
+    let query = "SELECT
+        COUNT(CASE WHEN phase = 'work' THEN 1 END) as total_sessions,
+        COUNT(CASE WHEN phase = 'work' AND is_completed = 1 THEN 1 END) as completed_sessions,
+        COALESCE(SUM(CASE WHEN phase = 'work' AND is_completed = 1 THEN duration ELSE 0 END), 0) / 60 as total_minutes,
+        AVG(CASE WHEN phase = 'work' AND is_completed = 1 THEN duration / 60.0 ELSE NULL END) as avg_duration,
+        COALESCE(SUM(CASE WHEN phase = 'work' THEN duration ELSE 0 END), 0) / 60 as focus_minutes,
+        COALESCE(SUM(CASE WHEN phase <> 'work' THEN duration ELSE 0 END), 0) / 60 as break_minutes,
+        COUNT(CASE WHEN phase = 'work' AND abandoned = 1 THEN 1 END) as abandoned_sessions,
+        COALESCE(SUM(CASE WHEN phase = 'work' THEN paused_seconds ELSE 0 END), 0) / 60 as paused_minutes,
+        CAST(COALESCE(SUM(CASE WHEN phase = 'work' AND ended_at IS NOT NULL
+            THEN (julianday(ended_at) - julianday(started_at)) * 86400 ELSE 0 END), 0) / 60 AS INTEGER) as wall_clock_minutes,
+        COUNT(CASE WHEN phase = 'work' AND EXISTS (
+            SELECT 1 FROM pomodoro_interruptions i WHERE i.session_id = pomodoro_sessions.id AND i.kind <> 'pause'
+        ) THEN 1 END) as interrupted_sessions,
+        (SELECT COUNT(*) FROM pomodoro_interruptions i JOIN pomodoro_sessions s ON s.id = i.session_id
+          WHERE i.kind = 'internal' AND s.phase = 'work' AND s.ended_at IS NOT NULL AND s.started_at >= ?1 AND s.started_at <= ?2) as internal_interruptions,
+        (SELECT COUNT(*) FROM pomodoro_interruptions i JOIN pomodoro_sessions s ON s.id = i.session_id
+          WHERE i.kind = 'external' AND s.phase = 'work' AND s.ended_at IS NOT NULL AND s.started_at >= ?1 AND s.started_at <= ?2) as external_interruptions
+        FROM pomodoro_sessions WHERE ended_at IS NOT NULL AND started_at >= ?1 AND started_at <= ?2";
+
+    // 未指定的边界用不会限制结果的值代替，RFC3339 字符串可以直接比较
+    let start_date = start_date.unwrap_or_default();
+    let end_date = end_date.unwrap_or_else(|| "9999".to_string());
+
     let stats = conn.query_row(
-        &query,
-        rusqlite::params_from_iter(params_vec.iter().map(|p| p.as_ref())),
+        query,
+        params![start_date, end_date],
         |row| {
+            let total_sessions = row.get::<_, i32>(0)?;
+            let interrupted_sessions = row.get::<_, i32>(9)?;
+            let interruption_rate = if total_sessions > 0 {
+                Some(interrupted_sessions as f64 / total_sessions as f64)
+            } else {
+                None
+            };
+
             Ok(serde_json::json!({
-                "total_sessions": row.get::<_, i32>(0)?,
+                "total_sessions": total_sessions,
                 "completed_sessions": row.get::<_, i32>(1)?,
                 "total_minutes": row.get::<_, i32>(2)?,
-                "avg_duration": row.get::<_, Option<f64>>(3)?
+                "avg_duration": row.get::<_, Option<f64>>(3)?,
+                "focus_minutes": row.get::<_, i32>(4)?,
+                "break_minutes": row.get::<_, i32>(5)?,
+                "abandoned_sessions": row.get::<_, i32>(6)?,
+                "paused_minutes": row.get::<_, i32>(7)?,
+                "wall_clock_minutes": row.get::<_, i32>(8)?,
+                "interrupted_sessions": interrupted_sessions,
+                "interruption_rate": interruption_rate,
+                "internal_interruptions": row.get::<_, i32>(10)?,
+                "external_interruptions": row.get::<_, i32>(11)?
             }))
         }
     ).map_err(|e| e.to_string())?;
-    
+
     Ok(stats)
 }
 
 #[tauri::command]
 pub async fn get_sessions_by_task(task_id: String, db: State<'_, Database>) -> Result<Vec<PomodoroSession>, String> {
     let conn = db.get_connection().map_err(|e| e.to_string())?;
-    
-    let mut stmt = conn.prepare(
-        "SELECT id, task_id, duration_minutes, is_completed, started_at, completed_at, created_at
-         FROM pomodoro_sessions 
-         WHERE task_id = ?
-         ORDER BY created_at DESC"
-    ).map_err(|e| e.to_string())?;
-    
-    let session_iter = stmt.query_map(params![task_id], |row| {
-        Ok(PomodoroSession {
-            id: row.get(0)?,
-            task_id: row.get(1)?,
-            duration_minutes: row.get(2)?,
-            is_completed: row.get::<_, i32>(3)? != 0,
-            started_at: row.get::<_, String>(4)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(4, "started_at".to_string(), rusqlite::types::Type::Text))?,
-            completed_at: row.get::<_, Option<String>>(5)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(5, "completed_at".to_string(), rusqlite::types::Type::Text))?,
-            created_at: row.get::<_, String>(6)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(6, "created_at".to_string(), rusqlite::types::Type::Text))?,
-        })
-    }).map_err(|e| e.to_string())?;
-    
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM pomodoro_sessions WHERE task_id = ? ORDER BY started_at DESC",
+        SESSION_COLUMNS
+    )).map_err(|e| e.to_string())?;
+
+    let session_iter = stmt.query_map(params![task_id], session_from_row).map_err(|e| e.to_string())?;
+
     let mut sessions = Vec::new();
     for session in session_iter {
         sessions.push(session.map_err(|e| e.to_string())?);
     }
-    
+
     Ok(sessions)
-}
\ No newline at end of file
+}
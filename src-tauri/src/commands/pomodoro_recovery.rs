@@ -0,0 +1,171 @@
+use crate::models::{DanglingPomodoroSession, PomodoroSession};
+use crate::db::Database;
+use crate::commands::pomodoro::{delete_session, session_from_row, SESSION_COLUMNS};
+use crate::commands::pomodoro_timer::PomodoroTimer;
+use tauri::State;
+use rusqlite::{params, Connection};
+use chrono::{DateTime, Duration, Utc};
+
+// 遗留会话的处理方式
+pub const RECOVER_COMPLETE: &str = "complete";
+pub const RECOVER_TRUNCATE: &str = "truncate";
+pub const RECOVER_DISCARD: &str = "discard";
+
+// 截断后没有走完的会话记为放弃，使用这个原因
+const CRASH_ABANDON_REASON: &str = "App closed unexpectedly";
+
+fn validate_action(action: &str) -> Result<(), String> {
+    match action {
+        RECOVER_COMPLETE | RECOVER_TRUNCATE | RECOVER_DISCARD => Ok(()),
+        _ => Err(format!("Unknown recovery action: {}", action)),
+    }
+}
+
+fn dangling_from_session(session: PomodoroSession, heartbeat_at: Option<DateTime<Utc>>) -> DanglingPomodoroSession {
+    let paused = Duration::seconds(session.paused_seconds as i64);
+    let planned_end_at = session.started_at + paused + Duration::seconds(session.duration as i64);
+    // 心跳之前的暂停已经计入 paused_seconds，净时长不超过计划时长
+    let heartbeat_duration = heartbeat_at
+        .map(|at| (at - session.started_at - paused).num_seconds())
+        .unwrap_or(0)
+        .clamp(0, session.duration as i64) as i32;
+
+    DanglingPomodoroSession { session, heartbeat_at, planned_end_at, heartbeat_duration }
+}
+
+/// 查找没有结束的会话，`active_id` 为计时服务正在进行的会话，不算遗留
+pub fn load_dangling_sessions(conn: &Connection, active_id: Option<&str>) -> Result<Vec<DanglingPomodoroSession>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, heartbeat_at FROM pomodoro_sessions
+         WHERE ended_at IS NULL AND id <> ?
+         ORDER BY started_at",
+        SESSION_COLUMNS
+    ))?;
+
+    let rows = stmt.query_map(params![active_id.unwrap_or("")], |row| {
+        let heartbeat_at = row.get::<_, Option<String>>(11)?.map(|s| s.parse()).transpose()
+            .map_err(|_| rusqlite::Error::InvalidColumnType(11, "heartbeat_at".to_string(), rusqlite::types::Type::Text))?;
+        Ok(dangling_from_session(session_from_row(row)?, heartbeat_at))
+    })?;
+
+    let mut sessions = Vec::new();
+    for row in rows {
+        sessions.push(row?);
+    }
+    Ok(sessions)
+}
+
+/// 按指定方式结束遗留会话，丢弃时返回 None
+fn resolve_session(conn: &Connection, dangling: DanglingPomodoroSession, action: &str, now: DateTime<Utc>) -> Result<Option<PomodoroSession>, String> {
+    let mut session = dangling.session;
+    match action {
+        RECOVER_DISCARD => {
+            delete_session(conn, &session.id).map_err(|e| e.to_string())?;
+            return Ok(None);
+        }
+        RECOVER_COMPLETE => {
+            // 计划结束时间还没到的会话只能算到现在
+            if dangling.planned_end_at <= now {
+                session.ended_at = Some(dangling.planned_end_at);
+                session.is_completed = true;
+            } else {
+                let elapsed = (now - session.started_at).num_seconds() - session.paused_seconds as i64;
+                session.ended_at = Some(now);
+                session.duration = elapsed.clamp(0, session.duration as i64) as i32;
+                session.abandoned = true;
+                session.abandon_reason = Some(CRASH_ABANDON_REASON.to_string());
+            }
+        }
+        _ => {
+            let heartbeat_at = dangling.heartbeat_at.unwrap_or(session.started_at);
+            session.is_completed = dangling.heartbeat_duration >= session.duration;
+            session.ended_at = Some(heartbeat_at);
+            session.duration = dangling.heartbeat_duration;
+            if !session.is_completed {
+                session.abandoned = true;
+                session.abandon_reason = Some(CRASH_ABANDON_REASON.to_string());
+            }
+        }
+    }
+
+    conn.execute(
+        "UPDATE pomodoro_sessions
+         SET ended_at = ?, duration = ?, is_completed = ?, abandoned = ?, abandon_reason = ?, heartbeat_at = ?
+         WHERE id = ?",
+        params![
+            session.ended_at.map(|d| d.to_rfc3339()), session.duration, session.is_completed as i32,
+            session.abandoned as i32, session.abandon_reason, session.ended_at.map(|d| d.to_rfc3339()), session.id
+        ]
+    ).map_err(|e| e.to_string())?;
+
+    // 仍未结束的中断截止到会话结束
+    if let Some(ended_at) = session.ended_at {
+        conn.execute(
+            "UPDATE pomodoro_interruptions
+             SET ended_at = ?1,
+                 duration = MAX(CAST((julianday(?1) - julianday(started_at)) * 86400 AS INTEGER), 0)
+             WHERE session_id = ?2 AND ended_at IS NULL",
+            params![ended_at.to_rfc3339(), session.id]
+        ).map_err(|e| e.to_string())?;
+    }
+
+    Ok(Some(session))
+}
+
+/// 启动后检查上次异常退出遗留的会话，前端据此询问用户如何处理
+#[tauri::command]
+pub async fn get_dangling_pomodoro_sessions(
+    db: State<'_, Database>,
+    timer: State<'_, PomodoroTimer>
+) -> Result<Vec<DanglingPomodoroSession>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let active_id = timer.active_session_id();
+    load_dangling_sessions(&conn, active_id.as_deref()).map_err(|e| e.to_string())
+}
+
+/// 处理一条遗留会话。`action` 为 complete（按计划结束时间完成）、
+/// truncate（截断到最后一次心跳）或 discard（删除）
+#[tauri::command]
+pub async fn recover_pomodoro_session(
+    id: String,
+    action: String,
+    db: State<'_, Database>,
+    timer: State<'_, PomodoroTimer>
+) -> Result<Option<PomodoroSession>, String> {
+    validate_action(&action)?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let active_id = timer.active_session_id();
+
+    let dangling = load_dangling_sessions(&conn, active_id.as_deref())
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|s| s.session.id == id)
+        .ok_or_else(|| "Session is not waiting for recovery".to_string())?;
+
+    resolve_session(&conn, dangling, &action, Utc::now())
+}
+
+/// 用同一种方式处理全部遗留会话，返回保留下来的会话
+#[tauri::command]
+pub async fn recover_all_pomodoro_sessions(
+    action: String,
+    db: State<'_, Database>,
+    timer: State<'_, PomodoroTimer>
+) -> Result<Vec<PomodoroSession>, String> {
+    validate_action(&action)?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let active_id = timer.active_session_id();
+    let now = Utc::now();
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let dangling = load_dangling_sessions(&tx, active_id.as_deref()).map_err(|e| e.to_string())?;
+    let mut sessions = Vec::new();
+    for session in dangling {
+        if let Some(session) = resolve_session(&tx, session, &action, now)? {
+            sessions.push(session);
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(sessions)
+}
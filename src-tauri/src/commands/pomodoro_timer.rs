@@ -0,0 +1,717 @@
+use crate::models::{PomodoroInterruption, PomodoroPhaseEvent, PomodoroSession, PomodoroSettings, PomodoroTimerState};
+use crate::db::{self, Database};
+use crate::commands::focus_mode::sync_focus_mode;
+use crate::commands::pomodoro_recovery::load_dangling_sessions;
+use crate::commands::pomodoro::{delete_session, record_session, save_interruptions, validate_interruption_kind, write_heartbeat, INTERRUPTION_PAUSE};
+use tauri::{AppHandle, Emitter, Manager, State};
+use rusqlite::Connection;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+use std::sync::Mutex;
+
+// 计时阶段
+pub const PHASE_WORK: &str = "work";
+pub const PHASE_SHORT_BREAK: &str = "short_break";
+pub const PHASE_LONG_BREAK: &str = "long_break";
+
+// 计时状态
+pub const STATUS_RUNNING: &str = "running";
+pub const STATUS_PAUSED: &str = "paused";
+pub const STATUS_STOPPED: &str = "stopped";
+
+// 发送给所有窗口的事件
+pub const TICK_EVENT: &str = "pomodoro://tick";
+pub const PHASE_EVENT: &str = "pomodoro://phase-changed";
+pub const STATE_EVENT: &str = "pomodoro://state-changed";
+
+const TIMER_STATE_SETTING: &str = "pomodoro_timer_state";
+
+// 运行中每隔多少秒写一次心跳
+const HEARTBEAT_SECONDS: i64 = 30;
+
+// 番茄钟设置项
+const WORK_MINUTES_SETTING: &str = "pomodoro_work_minutes";
+const SHORT_BREAK_MINUTES_SETTING: &str = "pomodoro_short_break_minutes";
+const LONG_BREAK_MINUTES_SETTING: &str = "pomodoro_long_break_minutes";
+const LONG_BREAK_INTERVAL_SETTING: &str = "pomodoro_long_break_interval";
+const AUTO_START_BREAKS_SETTING: &str = "pomodoro_auto_start_breaks";
+const AUTO_START_WORK_SETTING: &str = "pomodoro_auto_start_work";
+
+// 默认时长（分钟），以及每完成几次专注进入一次长休息
+const DEFAULT_WORK_MINUTES: i64 = 25;
+const DEFAULT_SHORT_BREAK_MINUTES: i64 = 5;
+const DEFAULT_LONG_BREAK_MINUTES: i64 = 15;
+const DEFAULT_LONG_BREAK_INTERVAL: i64 = 4;
+
+const MAX_PHASE_MINUTES: i64 = 240;
+const MAX_LONG_BREAK_INTERVAL: i64 = 12;
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        PomodoroSettings {
+            work_minutes: DEFAULT_WORK_MINUTES,
+            short_break_minutes: DEFAULT_SHORT_BREAK_MINUTES,
+            long_break_minutes: DEFAULT_LONG_BREAK_MINUTES,
+            long_break_interval: DEFAULT_LONG_BREAK_INTERVAL,
+            auto_start_breaks: false,
+            auto_start_work: false,
+        }
+    }
+}
+
+impl PomodoroSettings {
+    fn phase_seconds(&self, phase: &str) -> i64 {
+        match phase {
+            PHASE_SHORT_BREAK => self.short_break_minutes * 60,
+            PHASE_LONG_BREAK => self.long_break_minutes * 60,
+            _ => self.work_minutes * 60,
+        }
+    }
+
+    fn auto_start(&self, phase: &str) -> bool {
+        if phase == PHASE_WORK { self.auto_start_work } else { self.auto_start_breaks }
+    }
+}
+
+/// 读取番茄钟设置，未设置或无效的值使用默认值
+pub fn load_pomodoro_settings(conn: &Connection) -> Result<PomodoroSettings, rusqlite::Error> {
+    let defaults = PomodoroSettings::default();
+    let number = |key: &str, default: i64, max: i64| -> Result<i64, rusqlite::Error> {
+        Ok(db::get_setting(conn, key)?
+            .and_then(|v| v.parse().ok())
+            .filter(|v| (1..=max).contains(v))
+            .unwrap_or(default))
+    };
+    let flag = |key: &str| -> Result<bool, rusqlite::Error> {
+        Ok(db::get_setting(conn, key)?.as_deref() == Some("true"))
+    };
+
+    Ok(PomodoroSettings {
+        work_minutes: number(WORK_MINUTES_SETTING, defaults.work_minutes, MAX_PHASE_MINUTES)?,
+        short_break_minutes: number(SHORT_BREAK_MINUTES_SETTING, defaults.short_break_minutes, MAX_PHASE_MINUTES)?,
+        long_break_minutes: number(LONG_BREAK_MINUTES_SETTING, defaults.long_break_minutes, MAX_PHASE_MINUTES)?,
+        long_break_interval: number(LONG_BREAK_INTERVAL_SETTING, defaults.long_break_interval, MAX_LONG_BREAK_INTERVAL)?,
+        auto_start_breaks: flag(AUTO_START_BREAKS_SETTING)?,
+        auto_start_work: flag(AUTO_START_WORK_SETTING)?,
+    })
+}
+
+fn validate_phase(phase: &str) -> Result<(), String> {
+    match phase {
+        PHASE_WORK | PHASE_SHORT_BREAK | PHASE_LONG_BREAK => Ok(()),
+        _ => Err(format!("Unknown pomodoro phase: {}", phase)),
+    }
+}
+
+impl PomodoroTimerState {
+    fn stopped(phase: &str, task_id: Option<String>, completed_work_sessions: i64, settings: &PomodoroSettings) -> Self {
+        let mut state = PomodoroTimerState {
+            state: STATUS_STOPPED.to_string(),
+            phase: phase.to_string(),
+            status: STATUS_STOPPED.to_string(),
+            task_id,
+            session_id: None,
+            interruptions: Vec::new(),
+            phase_started_at: None,
+            resumed_at: None,
+            elapsed_seconds: 0,
+            duration_seconds: settings.phase_seconds(phase),
+            remaining_seconds: 0,
+            completed_work_sessions,
+            updated_at: Utc::now(),
+        };
+        state.refresh(Utc::now());
+        state
+    }
+
+    /// 当前阶段已经计时的秒数，暂停的时间不计入
+    fn elapsed_at(&self, now: DateTime<Utc>) -> i64 {
+        let running = self.resumed_at.map(|at| (now - at).num_seconds().max(0)).unwrap_or(0);
+        self.elapsed_seconds + running
+    }
+
+    /// 运行中阶段的预计结束时间
+    fn ends_at(&self) -> Option<DateTime<Utc>> {
+        self.resumed_at.map(|at| at + Duration::seconds(self.duration_seconds - self.elapsed_seconds))
+    }
+
+    /// 重新计算派生字段 `state` 和 `remaining_seconds`
+    fn refresh(&mut self, now: DateTime<Utc>) {
+        self.remaining_seconds = (self.duration_seconds - self.elapsed_at(now)).max(0);
+        self.state = match self.status.as_str() {
+            STATUS_RUNNING => self.phase.clone(),
+            status => status.to_string(),
+        };
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status != STATUS_STOPPED
+    }
+
+    fn begin(&mut self, phase: &str, now: DateTime<Utc>, settings: &PomodoroSettings) {
+        self.phase = phase.to_string();
+        self.status = STATUS_RUNNING.to_string();
+        self.session_id = Some(Uuid::new_v4().to_string());
+        self.interruptions.clear();
+        self.phase_started_at = Some(now);
+        self.resumed_at = Some(now);
+        self.elapsed_seconds = 0;
+        self.duration_seconds = settings.phase_seconds(phase);
+        self.updated_at = now;
+    }
+
+    /// 记录一次中断；`pause` 为 true 时同时暂停计时，直到继续时才结束这次中断
+    fn interrupt(&mut self, kind: &str, reason: Option<String>, pause: bool, now: DateTime<Utc>) {
+        self.interruptions.push(PomodoroInterruption {
+            id: Uuid::new_v4().to_string(),
+            session_id: self.session_id.clone().unwrap_or_default(),
+            kind: kind.to_string(),
+            reason: reason.filter(|r| !r.trim().is_empty()),
+            started_at: now,
+            ended_at: if pause { None } else { Some(now) },
+            duration: 0,
+        });
+
+        if pause {
+            self.elapsed_seconds = self.elapsed_at(now);
+            self.resumed_at = None;
+            self.status = STATUS_PAUSED.to_string();
+        }
+        self.updated_at = now;
+    }
+
+    /// 结束仍在进行的暂停
+    fn close_interruptions(&mut self, now: DateTime<Utc>) {
+        for interruption in self.interruptions.iter_mut().filter(|i| i.ended_at.is_none()) {
+            interruption.ended_at = Some(now);
+            interruption.duration = (now - interruption.started_at).num_seconds().max(0);
+        }
+    }
+
+    fn resume(&mut self, now: DateTime<Utc>) {
+        self.close_interruptions(now);
+        self.resumed_at = Some(now);
+        self.status = STATUS_RUNNING.to_string();
+        self.updated_at = now;
+    }
+
+    /// 生成当前阶段的会话记录。`duration` 为净计时秒数，暂停时间单独记在 `paused_seconds`
+    fn session_record(&mut self, at: DateTime<Utc>, completed: bool, abandon_reason: Option<String>) -> Option<(PomodoroSession, Vec<PomodoroInterruption>)> {
+        let started_at = self.phase_started_at?;
+        let duration = if completed {
+            self.duration_seconds
+        } else {
+            self.elapsed_at(at).min(self.duration_seconds)
+        };
+        if duration <= 0 {
+            return None;
+        }
+
+        self.close_interruptions(at);
+        let session = PomodoroSession {
+            id: self.session_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string()),
+            started_at,
+            ended_at: Some(at),
+            duration: duration as i32,
+            is_completed: completed,
+            task_id: self.task_id.clone(),
+            notes: None,
+            phase: self.phase.clone(),
+            paused_seconds: self.paused_seconds() as i32,
+            abandoned: !completed,
+            abandon_reason: abandon_reason.filter(|r| !r.trim().is_empty()),
+        };
+        Some((session, std::mem::take(&mut self.interruptions)))
+    }
+
+    /// 阶段开始时写入的未结束记录，异常退出后据此恢复
+    fn open_record(&self) -> Option<PomodoroSession> {
+        Some(PomodoroSession {
+            id: self.session_id.clone()?,
+            started_at: self.phase_started_at?,
+            ended_at: None,
+            duration: self.duration_seconds as i32,
+            is_completed: false,
+            task_id: self.task_id.clone(),
+            notes: None,
+            phase: self.phase.clone(),
+            paused_seconds: 0,
+            abandoned: false,
+            abandon_reason: None,
+        })
+    }
+
+    fn paused_seconds(&self) -> i64 {
+        self.interruptions.iter().map(|i| i.duration).sum()
+    }
+
+    /// 当前阶段结束后的下一个阶段
+    fn next_phase(&self, settings: &PomodoroSettings) -> &'static str {
+        if self.phase != PHASE_WORK {
+            PHASE_WORK
+        } else if self.completed_work_sessions > 0 && self.completed_work_sessions % settings.long_break_interval == 0 {
+            PHASE_LONG_BREAK
+        } else {
+            PHASE_SHORT_BREAK
+        }
+    }
+}
+
+/// 计时服务，作为 Tauri 状态管理
+pub struct PomodoroTimer {
+    state: Mutex<PomodoroTimerState>,
+}
+
+impl PomodoroTimer {
+    pub fn snapshot(&self) -> Result<PomodoroTimerState, String> {
+        let mut state = self.state.lock().map_err(|e| e.to_string())?.clone();
+        state.refresh(Utc::now());
+        Ok(state)
+    }
+
+    /// 正在计时或暂停中的会话，恢复时不把它当作遗留会话
+    pub fn active_session_id(&self) -> Option<String> {
+        let state = self.state.lock().ok()?;
+        if state.is_active() { state.session_id.clone() } else { None }
+    }
+}
+
+impl Default for PomodoroTimer {
+    fn default() -> Self {
+        PomodoroTimer {
+            state: Mutex::new(PomodoroTimerState::stopped(PHASE_WORK, None, 0, &PomodoroSettings::default())),
+        }
+    }
+}
+
+fn save_state(conn: &Connection, state: &PomodoroTimerState) -> Result<(), String> {
+    let json = serde_json::to_string(state).map_err(|e| e.to_string())?;
+    db::set_setting(conn, TIMER_STATE_SETTING, &json).map_err(|e| e.to_string())
+}
+
+fn load_state(conn: &Connection) -> Result<Option<PomodoroTimerState>, String> {
+    match db::get_setting(conn, TIMER_STATE_SETTING).map_err(|e| e.to_string())? {
+        Some(json) => Ok(serde_json::from_str(&json).ok()),
+        None => Ok(None),
+    }
+}
+
+/// 开始阶段后立即写入未结束的记录
+fn open_session(conn: &Connection, state: &PomodoroTimerState) -> Result<(), String> {
+    match state.open_record() {
+        Some(session) => record_session(conn, &session, &[]).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// 用阶段的最终结果覆盖开始时写入的记录，没有计时的阶段直接删除记录
+fn close_session(
+    conn: &Connection,
+    state: &mut PomodoroTimerState,
+    at: DateTime<Utc>,
+    completed: bool,
+    abandon_reason: Option<String>,
+) -> Result<Option<String>, String> {
+    match state.session_record(at, completed, abandon_reason) {
+        Some((session, interruptions)) => {
+            record_session(conn, &session, &interruptions).map_err(|e| e.to_string())?;
+            Ok(Some(session.id))
+        }
+        None => {
+            if let Some(id) = &state.session_id {
+                delete_session(conn, id).map_err(|e| e.to_string())?;
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// 放弃当前阶段：已计时的部分记为放弃的会话，连同中断一起保存
+fn abandon_phase(conn: &Connection, state: &mut PomodoroTimerState, now: DateTime<Utc>, reason: Option<String>) -> Result<(), String> {
+    if !state.is_active() {
+        return Ok(());
+    }
+
+    close_session(conn, state, now, false, reason).map(|_| ())
+}
+
+/// 结束当前阶段并切换到下一个阶段。
+/// `completed` 为 true 表示计时走完，`at` 为阶段实际结束的时间；
+/// 未走完的阶段记为放弃，原因写入 `abandon_reason`。
+/// `allow_auto_start` 为 false 时即使开启了自动开始也停在下一个阶段之前。
+fn finish_phase(
+    conn: &Connection,
+    state: &mut PomodoroTimerState,
+    completed: bool,
+    at: DateTime<Utc>,
+    abandon_reason: Option<String>,
+    allow_auto_start: bool,
+) -> Result<PomodoroPhaseEvent, String> {
+    let settings = load_pomodoro_settings(conn).map_err(|e| e.to_string())?;
+    let previous_phase = state.phase.clone();
+
+    let mut session_id = None;
+    if completed {
+        session_id = close_session(conn, state, at, true, None)?;
+        if state.phase == PHASE_WORK {
+            state.completed_work_sessions += 1;
+        }
+    } else {
+        abandon_phase(conn, state, at, abandon_reason)?;
+    }
+
+    let next = state.next_phase(&settings);
+    *state = PomodoroTimerState::stopped(next, state.task_id.take(), state.completed_work_sessions, &settings);
+    state.updated_at = at;
+    if completed && allow_auto_start && settings.auto_start(next) {
+        state.begin(next, at, &settings);
+        open_session(conn, state)?;
+    }
+    save_state(conn, state)?;
+
+    Ok(PomodoroPhaseEvent {
+        previous_phase,
+        phase: next.to_string(),
+        completed,
+        session_id,
+        state: state.clone(),
+    })
+}
+
+fn emit_state(app: &AppHandle, state: &PomodoroTimerState) {
+    if let Err(e) = app.emit(STATE_EVENT, state) {
+        log::warn!("Failed to emit pomodoro state: {}", e);
+    }
+}
+
+fn emit_phase(app: &AppHandle, event: &PomodoroPhaseEvent) {
+    if let Err(e) = app.emit(PHASE_EVENT, event) {
+        log::warn!("Failed to emit pomodoro phase change: {}", e);
+    }
+}
+
+/// 检查运行中的阶段是否已经到时，到时则写入记录并切换阶段
+fn advance_if_due(conn: &Connection, state: &mut PomodoroTimerState, now: DateTime<Utc>, allow_auto_start: bool) -> Result<Option<PomodoroPhaseEvent>, String> {
+    if state.status != STATUS_RUNNING {
+        return Ok(None);
+    }
+    match state.ends_at() {
+        Some(ends_at) if ends_at <= now => finish_phase(conn, state, true, ends_at, None, allow_auto_start).map(Some),
+        _ => Ok(None),
+    }
+}
+
+fn is_session_open(conn: &Connection, session_id: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pomodoro_sessions WHERE id = ? AND ended_at IS NULL",
+        [session_id],
+        |row| row.get::<_, i64>(0)
+    ).map(|count| count > 0).map_err(|e| e.to_string())
+}
+
+/// 读取上次保存的计时状态。关闭期间已经到时的阶段无法确定是否真的走完，
+/// 保留为未结束的会话，由用户选择按计划完成、截断到最后一次心跳或丢弃；
+/// 没有写入开始记录的旧版本状态仍按到时时间补记。不会自动开始下一个阶段。
+fn restore_timer(app: &AppHandle) -> Result<(), String> {
+    let database = app.state::<Database>();
+    let conn = database.get_connection().map_err(|e| e.to_string())?;
+    let settings = load_pomodoro_settings(&conn).map_err(|e| e.to_string())?;
+    let mut state = match load_state(&conn)? {
+        Some(state) => state,
+        None => PomodoroTimerState::stopped(PHASE_WORK, None, 0, &settings),
+    };
+
+    let now = Utc::now();
+    let mut event = None;
+    let is_due = state.status == STATUS_RUNNING && state.ends_at().is_some_and(|ends_at| ends_at <= now);
+    match state.session_id.clone() {
+        Some(id) if is_due && is_session_open(&conn, &id)? => {
+            save_interruptions(&conn, &id, &state.interruptions).map_err(|e| e.to_string())?;
+            let phase = state.phase.clone();
+            state = PomodoroTimerState::stopped(&phase, state.task_id.take(), state.completed_work_sessions, &settings);
+            save_state(&conn, &state)?;
+        }
+        _ => event = advance_if_due(&conn, &mut state, now, false)?,
+    }
+    state.refresh(now);
+
+    let active_id = if state.is_active() { state.session_id.clone() } else { None };
+    let dangling = load_dangling_sessions(&conn, active_id.as_deref()).map_err(|e| e.to_string())?;
+    if !dangling.is_empty() {
+        log::info!("{} pomodoro session(s) waiting for recovery", dangling.len());
+    }
+
+    // 补做上次退出时没有执行的专注模式钩子
+    sync_focus_mode(app, &conn, &state);
+
+    let timer = app.state::<PomodoroTimer>();
+    *timer.state.lock().map_err(|e| e.to_string())? = state;
+    if let Some(event) = event {
+        emit_phase(app, &event);
+    }
+    Ok(())
+}
+
+/// 每秒检查一次计时状态，向所有窗口发送 tick 事件
+pub fn spawn_pomodoro_timer(app: AppHandle) {
+    if let Err(e) = restore_timer(&app) {
+        log::warn!("Failed to restore pomodoro timer: {}", e);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        let mut next_heartbeat = Utc::now();
+        loop {
+            interval.tick().await;
+
+            let timer = app.state::<PomodoroTimer>();
+            let mut state = match timer.state.lock() {
+                Ok(state) => state,
+                Err(_) => continue,
+            };
+            if state.status != STATUS_RUNNING {
+                continue;
+            }
+
+            let now = Utc::now();
+            if state.ends_at().is_some_and(|ends_at| ends_at <= now) {
+                let database = app.state::<Database>();
+                let result = database.get_connection()
+                    .map_err(|e| e.to_string())
+                    .and_then(|conn| {
+                        let event = advance_if_due(&conn, &mut state, now, true)?;
+                        sync_focus_mode(&app, &conn, &state);
+                        Ok(event)
+                    });
+                match result {
+                    Ok(Some(event)) => emit_phase(&app, &event),
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Failed to finish pomodoro phase: {}", e),
+                }
+                continue;
+            }
+
+            state.refresh(now);
+            if let Err(e) = app.emit(TICK_EVENT, &*state) {
+                log::warn!("Failed to emit pomodoro tick: {}", e);
+            }
+
+            if now >= next_heartbeat {
+                next_heartbeat = now + Duration::seconds(HEARTBEAT_SECONDS);
+                if let Some(id) = &state.session_id {
+                    let database = app.state::<Database>();
+                    let result = database.get_connection()
+                        .map_err(|e| e.to_string())
+                        .and_then(|conn| write_heartbeat(&conn, id, now, state.paused_seconds()).map_err(|e| e.to_string()));
+                    if let Err(e) = result {
+                        log::warn!("Failed to write pomodoro heartbeat: {}", e);
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// 在锁内修改计时状态，保存后通知所有窗口
+fn update_timer<F>(app: &AppHandle, db: &Database, timer: &PomodoroTimer, f: F) -> Result<PomodoroTimerState, String>
+where
+    F: FnOnce(&Connection, &mut PomodoroTimerState, DateTime<Utc>) -> Result<Option<PomodoroPhaseEvent>, String>,
+{
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut state = timer.state.lock().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    // 先处理已经到时但还没来得及切换的阶段
+    if let Some(event) = advance_if_due(&conn, &mut state, now, true)? {
+        emit_phase(app, &event);
+    }
+
+    let event = f(&conn, &mut state, now)?;
+    state.refresh(now);
+    save_state(&conn, &state)?;
+    sync_focus_mode(app, &conn, &state);
+
+    if let Some(event) = event {
+        emit_phase(app, &event);
+    }
+    emit_state(app, &state);
+    Ok(state.clone())
+}
+
+#[tauri::command]
+pub async fn get_pomodoro_timer(timer: State<'_, PomodoroTimer>) -> Result<PomodoroTimerState, String> {
+    timer.snapshot()
+}
+
+/// 开始计时。未指定阶段时开始当前准备好的阶段，正在进行的专注会被记为中断。
+#[tauri::command]
+pub async fn start_pomodoro_timer(
+    task_id: Option<String>,
+    phase: Option<String>,
+    app: AppHandle,
+    db: State<'_, Database>,
+    timer: State<'_, PomodoroTimer>
+) -> Result<PomodoroTimerState, String> {
+    if let Some(phase) = &phase {
+        validate_phase(phase)?;
+    }
+
+    update_timer(&app, &db, &timer, |conn, state, now| {
+        abandon_phase(conn, state, now, None)?;
+
+        let settings = load_pomodoro_settings(conn).map_err(|e| e.to_string())?;
+        let phase = phase.unwrap_or_else(|| state.phase.clone());
+        if task_id.is_some() {
+            state.task_id = task_id;
+        }
+        state.begin(&phase, now, &settings);
+        open_session(conn, state)?;
+        Ok(None)
+    })
+}
+
+/// 暂停计时。`kind` 为 pause（默认）、internal 或 external，可附带原因
+#[tauri::command]
+pub async fn pause_pomodoro_timer(
+    kind: Option<String>,
+    reason: Option<String>,
+    app: AppHandle,
+    db: State<'_, Database>,
+    timer: State<'_, PomodoroTimer>
+) -> Result<PomodoroTimerState, String> {
+    let kind = kind.unwrap_or_else(|| INTERRUPTION_PAUSE.to_string());
+    validate_interruption_kind(&kind)?;
+
+    update_timer(&app, &db, &timer, |_, state, now| {
+        if state.status != STATUS_RUNNING {
+            return Err("Pomodoro timer is not running".to_string());
+        }
+        state.interrupt(&kind, reason, true, now);
+        Ok(None)
+    })
+}
+
+/// 记录一次没有暂停计时的打断，例如忍住了的分心
+#[tauri::command]
+pub async fn log_pomodoro_interruption(
+    kind: String,
+    reason: Option<String>,
+    app: AppHandle,
+    db: State<'_, Database>,
+    timer: State<'_, PomodoroTimer>
+) -> Result<PomodoroTimerState, String> {
+    validate_interruption_kind(&kind)?;
+
+    update_timer(&app, &db, &timer, |_, state, now| {
+        if !state.is_active() {
+            return Err("Pomodoro timer is not running".to_string());
+        }
+        state.interrupt(&kind, reason, false, now);
+        Ok(None)
+    })
+}
+
+#[tauri::command]
+pub async fn resume_pomodoro_timer(
+    app: AppHandle,
+    db: State<'_, Database>,
+    timer: State<'_, PomodoroTimer>
+) -> Result<PomodoroTimerState, String> {
+    update_timer(&app, &db, &timer, |_, state, now| {
+        if state.status != STATUS_PAUSED {
+            return Err("Pomodoro timer is not paused".to_string());
+        }
+        state.resume(now);
+        Ok(None)
+    })
+}
+
+/// 停止计时并重置循环，进行中的阶段记为放弃
+#[tauri::command]
+pub async fn stop_pomodoro_timer(
+    reason: Option<String>,
+    app: AppHandle,
+    db: State<'_, Database>,
+    timer: State<'_, PomodoroTimer>
+) -> Result<PomodoroTimerState, String> {
+    update_timer(&app, &db, &timer, |conn, state, now| {
+        abandon_phase(conn, state, now, reason)?;
+        let settings = load_pomodoro_settings(conn).map_err(|e| e.to_string())?;
+        *state = PomodoroTimerState::stopped(PHASE_WORK, state.task_id.take(), 0, &settings);
+        state.updated_at = now;
+        Ok(None)
+    })
+}
+
+/// 跳过当前阶段，直接进入下一个阶段，已计时的部分记为放弃
+#[tauri::command]
+pub async fn skip_pomodoro_phase(
+    reason: Option<String>,
+    app: AppHandle,
+    db: State<'_, Database>,
+    timer: State<'_, PomodoroTimer>
+) -> Result<PomodoroTimerState, String> {
+    update_timer(&app, &db, &timer, |conn, state, now| {
+        finish_phase(conn, state, false, now, reason, true).map(Some)
+    })
+}
+
+#[tauri::command]
+pub async fn get_pomodoro_settings(db: State<'_, Database>) -> Result<PomodoroSettings, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    load_pomodoro_settings(&conn).map_err(|e| e.to_string())
+}
+
+/// 更新番茄钟设置，只修改传入的项。计时停止时立即按新时长显示下一个阶段
+#[tauri::command]
+pub async fn update_pomodoro_settings(
+    work_minutes: Option<i64>,
+    short_break_minutes: Option<i64>,
+    long_break_minutes: Option<i64>,
+    long_break_interval: Option<i64>,
+    auto_start_breaks: Option<bool>,
+    auto_start_work: Option<bool>,
+    app: AppHandle,
+    db: State<'_, Database>,
+    timer: State<'_, PomodoroTimer>
+) -> Result<PomodoroSettings, String> {
+    let minutes = [
+        (WORK_MINUTES_SETTING, work_minutes, MAX_PHASE_MINUTES),
+        (SHORT_BREAK_MINUTES_SETTING, short_break_minutes, MAX_PHASE_MINUTES),
+        (LONG_BREAK_MINUTES_SETTING, long_break_minutes, MAX_PHASE_MINUTES),
+        (LONG_BREAK_INTERVAL_SETTING, long_break_interval, MAX_LONG_BREAK_INTERVAL),
+    ];
+    // 先校验所有参数，任一无效时不修改任何设置
+    for (key, value, max) in minutes {
+        if value.is_some_and(|value| !(1..=max).contains(&value)) {
+            return Err(format!("{} must be between 1 and {}", key.trim_start_matches("pomodoro_"), max));
+        }
+    }
+
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for (key, value, _) in minutes {
+        if let Some(value) = value {
+            db::set_setting(&tx, key, &value.to_string()).map_err(|e| e.to_string())?;
+        }
+    }
+    for (key, value) in [(AUTO_START_BREAKS_SETTING, auto_start_breaks), (AUTO_START_WORK_SETTING, auto_start_work)] {
+        if let Some(value) = value {
+            db::set_setting(&tx, key, if value { "true" } else { "false" }).map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let settings = load_pomodoro_settings(&conn).map_err(|e| e.to_string())?;
+
+    // 进行中的阶段保持开始时的时长，只更新尚未开始的阶段
+    let mut state = timer.state.lock().map_err(|e| e.to_string())?;
+    if state.status == STATUS_STOPPED {
+        state.duration_seconds = settings.phase_seconds(&state.phase);
+        state.refresh(Utc::now());
+        save_state(&conn, &state)?;
+        emit_state(&app, &state);
+    }
+
+    Ok(settings)
+}
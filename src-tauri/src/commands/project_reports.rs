@@ -0,0 +1,283 @@
+use crate::models::{BurndownPoint, ProjectBurndown, WeeklyThroughput};
+use crate::db::Database;
+use crate::commands::projects::{project_from_row, PROJECT_COLUMNS, PROJECT_STATUS_ARCHIVED};
+use crate::commands::calendar::local_midnight;
+use tauri::{AppHandle, Manager, State};
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::time::Duration as StdDuration;
+
+// 记录项目快照的间隔，当天的快照会被覆盖为最后一次记录的结果
+const SNAPSHOT_INTERVAL_SECS: u64 = 3600;
+
+// 报表最多覆盖的天数
+const MAX_REPORT_DAYS: i64 = 730;
+
+// 计算速度时默认使用的最近周数
+const DEFAULT_VELOCITY_WEEKS: i64 = 4;
+
+// 项目下的任务连同子任务，包括回收站中的任务，用于推算历史数据
+const PROJECT_TASK_HISTORY_TREE: &str = "WITH RECURSIVE tree(id) AS (
+        SELECT id FROM tasks WHERE project_id = ?1
+        UNION
+        SELECT t.id FROM tasks t INNER JOIN tree ON t.parent_id = tree.id
+    )";
+
+/// 推算历史任务数需要的时间
+struct TaskHistory {
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// 记录所有未归档项目当前的任务数，作为今天的快照
+pub fn record_project_snapshots(conn: &Connection) -> Result<usize, rusqlite::Error> {
+    let project_ids: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT id FROM projects WHERE deleted_at IS NULL AND status != ?")?;
+        let ids = stmt.query_map(params![PROJECT_STATUS_ARCHIVED], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        ids
+    };
+
+    let now = Utc::now();
+    let today = format_date(now.with_timezone(&Local).date_naive());
+    for project_id in &project_ids {
+        let (total, completed): (i64, i64) = conn.query_row(
+            &format!(
+                "{} SELECT COUNT(*), COUNT(CASE WHEN is_completed = 1 THEN 1 END)
+                    FROM tasks WHERE id IN (SELECT id FROM tree) AND deleted_at IS NULL",
+                PROJECT_TASK_HISTORY_TREE
+            ),
+            params![project_id],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        )?;
+        conn.execute(
+            "INSERT OR REPLACE INTO project_snapshots (project_id, date, total_tasks, completed_tasks, recorded_at)
+             VALUES (?, ?, ?, ?, ?)",
+            params![project_id, today, total, completed, now.to_rfc3339()]
+        )?;
+    }
+
+    Ok(project_ids.len())
+}
+
+/// 定期记录项目快照，启动时立即记录一次
+pub fn spawn_project_snapshots(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            {
+                let database = app.state::<Database>();
+                let result = database.get_connection()
+                    .map_err(|e| e.to_string())
+                    .and_then(|conn| record_project_snapshots(&conn).map_err(|e| e.to_string()));
+                if let Err(e) = result {
+                    log::warn!("Failed to record project snapshots: {}", e);
+                }
+            }
+            tokio::time::sleep(StdDuration::from_secs(SNAPSHOT_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+fn load_task_history(conn: &Connection, project_id: &str) -> Result<Vec<TaskHistory>, rusqlite::Error> {
+    // 没有完成时间的已完成任务（旧数据或外部导入）按最后修改时间计
+    let mut stmt = conn.prepare(&format!(
+        "{} SELECT created_at, CASE WHEN is_completed = 1 THEN COALESCE(completed_at, updated_at) END, deleted_at
+            FROM tasks WHERE id IN (SELECT id FROM tree)",
+        PROJECT_TASK_HISTORY_TREE
+    ))?;
+
+    let rows = stmt.query_map(params![project_id], |row| {
+        Ok(TaskHistory {
+            created_at: row.get::<_, String>(0)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(0, "created_at".to_string(), rusqlite::types::Type::Text))?,
+            completed_at: row.get::<_, Option<String>>(1)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(1, "completed_at".to_string(), rusqlite::types::Type::Text))?,
+            deleted_at: row.get::<_, Option<String>>(2)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(2, "deleted_at".to_string(), rusqlite::types::Type::Text))?,
+        })
+    })?;
+
+    let mut history = Vec::new();
+    for row in rows {
+        history.push(row?);
+    }
+    Ok(history)
+}
+
+/// 由任务时间推算 `at` 时的任务总数和完成数。
+/// 之后才移入项目或重新打开的任务无法还原，这部分以快照为准
+fn derived_counts(history: &[TaskHistory], at: DateTime<Utc>) -> (i64, i64) {
+    let mut total = 0;
+    let mut completed = 0;
+    for task in history {
+        if task.created_at >= at || task.deleted_at.is_some_and(|d| d < at) {
+            continue;
+        }
+        total += 1;
+        if task.completed_at.is_some_and(|c| c < at) {
+            completed += 1;
+        }
+    }
+    (total, completed)
+}
+
+fn load_snapshots(conn: &Connection, project_id: &str, start: NaiveDate, end: NaiveDate) -> Result<HashMap<String, (i64, i64)>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT date, total_tasks, completed_tasks FROM project_snapshots
+         WHERE project_id = ? AND date >= ? AND date <= ?"
+    )?;
+    let rows = stmt.query_map(params![project_id, format_date(start), format_date(end)], |row| {
+        Ok((row.get::<_, String>(0)?, (row.get::<_, i64>(1)?, row.get::<_, i64>(2)?)))
+    })?;
+
+    let mut snapshots = HashMap::new();
+    for row in rows {
+        let (date, counts) = row?;
+        snapshots.insert(date, counts);
+    }
+    Ok(snapshots)
+}
+
+fn parse_report_date(value: &str, name: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| format!("Invalid {} format, expected YYYY-MM-DD", name))
+}
+
+/// 项目燃尽/燃起数据、每周完成数和按当前速度预计的完成日期。
+///
+/// 每天的数据优先使用当天的快照，没有快照的日期由任务的创建、完成和删除时间推算，今天始终使用当前数据。
+/// `start_date` 默认为项目创建日期，`end_date` 默认为今天；速度取最近 `velocity_weeks` 周（默认 4 周）的平均净完成数。
+#[tauri::command]
+pub async fn get_project_burndown(
+    project_id: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    velocity_weeks: Option<i64>,
+    db: State<'_, Database>
+) -> Result<ProjectBurndown, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let project = conn.query_row(
+        &format!("SELECT {} FROM projects WHERE id = ? AND deleted_at IS NULL", PROJECT_COLUMNS),
+        params![project_id],
+        project_from_row
+    ).optional().map_err(|e| e.to_string())?.ok_or("Project not found")?;
+
+    let now = Utc::now();
+    let today = now.with_timezone(&Local).date_naive();
+    let end = match end_date {
+        Some(date) => parse_report_date(&date, "end_date")?.min(today),
+        None => today,
+    };
+    let start = match start_date {
+        Some(date) => parse_report_date(&date, "start_date")?,
+        None => project.created_at.with_timezone(&Local).date_naive(),
+    };
+    if start > end {
+        return Err("start_date must not be after end_date".to_string());
+    }
+    let start = start.max(end - Duration::days(MAX_REPORT_DAYS - 1));
+    let velocity_weeks = velocity_weeks.unwrap_or(DEFAULT_VELOCITY_WEEKS).clamp(1, 52);
+
+    let history = load_task_history(&conn, &project.id).map_err(|e| e.to_string())?;
+    let baseline_date = start - Duration::days(1);
+    let snapshots = load_snapshots(&conn, &project.id, baseline_date, end).map_err(|e| e.to_string())?;
+
+    let counts_at = |date: NaiveDate| -> Result<((i64, i64), bool), String> {
+        if date >= today {
+            return Ok((derived_counts(&history, now), false));
+        }
+        if let Some(counts) = snapshots.get(&format_date(date)) {
+            return Ok((*counts, true));
+        }
+        Ok((derived_counts(&history, local_midnight(date + Duration::days(1))?), false))
+    };
+
+    // 起始日前一天的数据作为第一周的基准
+    let ((baseline_total, baseline_completed), _) = counts_at(baseline_date)?;
+
+    let due_day = project.due_date.map(|due| due.with_timezone(&Local).date_naive());
+    let mut points = Vec::new();
+    let mut date = start;
+    while date <= end {
+        let ((total, completed), from_snapshot) = counts_at(date)?;
+        points.push((date, BurndownPoint {
+            date: format_date(date),
+            total_tasks: total,
+            completed_tasks: completed,
+            open_tasks: total - completed,
+            ideal_open_tasks: None,
+            from_snapshot,
+        }));
+        date += Duration::days(1);
+    }
+
+    // 理想线从起始日的剩余任务数匀速降到截止日期的 0
+    if let (Some(due_day), Some(initial_open)) = (due_day, points.first().map(|(_, p)| p.open_tasks as f64)) {
+        let span = (due_day - start).num_days();
+        for (date, point) in &mut points {
+            let elapsed = (*date - start).num_days();
+            point.ideal_open_tasks = Some(if span > 0 && elapsed < span {
+                initial_open * (1.0 - elapsed as f64 / span as f64)
+            } else {
+                0.0
+            });
+        }
+    }
+
+    let mut weekly_throughput: Vec<WeeklyThroughput> = Vec::new();
+    let (mut previous_total, mut previous_completed) = (baseline_total, baseline_completed);
+    for (date, point) in &points {
+        let week_start = format_date(*date - Duration::days(date.weekday().num_days_from_monday() as i64));
+        match weekly_throughput.last_mut() {
+            Some(week) if week.week_start == week_start => {
+                week.completed_tasks += point.completed_tasks - previous_completed;
+                week.added_tasks += point.total_tasks - previous_total;
+            }
+            _ => weekly_throughput.push(WeeklyThroughput {
+                week_start,
+                completed_tasks: point.completed_tasks - previous_completed,
+                added_tasks: point.total_tasks - previous_total,
+            }),
+        }
+        previous_total = point.total_tasks;
+        previous_completed = point.completed_tasks;
+    }
+
+    // 速度 = 最近几周的净完成数 / 周数，报表不足这么长时按实际天数计算
+    let window_days = (velocity_weeks * 7).min((end - baseline_date).num_days());
+    let ((_, window_start_completed), _) = counts_at(end - Duration::days(window_days))?;
+    let open_tasks = points.last().map(|(_, p)| p.open_tasks).unwrap_or(0);
+    let end_completed = points.last().map(|(_, p)| p.completed_tasks).unwrap_or(0);
+    let velocity_per_week = if window_days > 0 {
+        (end_completed - window_start_completed).max(0) as f64 * 7.0 / window_days as f64
+    } else {
+        0.0
+    };
+
+    let projected_day = if open_tasks == 0 {
+        Some(end)
+    } else if velocity_per_week > 0.0 {
+        Some(today + Duration::days((open_tasks as f64 / velocity_per_week * 7.0).ceil() as i64))
+    } else {
+        None
+    };
+
+    Ok(ProjectBurndown {
+        project_id: project.id,
+        start_date: format_date(start),
+        end_date: format_date(end),
+        points: points.into_iter().map(|(_, p)| p).collect(),
+        weekly_throughput,
+        velocity_per_week,
+        open_tasks,
+        due_date: project.due_date,
+        projected_completion: projected_day.map(format_date),
+        days_ahead_of_due: match (due_day, projected_day) {
+            (Some(due), Some(projected)) => Some((due - projected).num_days()),
+            _ => None,
+        },
+    })
+}
@@ -0,0 +1,580 @@
+use crate::models::{
+    Project, ProjectTemplate, ProjectTemplateContent, TemplateColumn, TemplateMilestone, TemplateNote, TemplateTask,
+};
+use crate::db::Database;
+use crate::commands::projects::{
+    project_from_row, record_status_change, validate_project_status, PROJECT_COLUMNS,
+    PROJECT_STATUS_ACTIVE, PROJECT_STATUS_ARCHIVED, PROJECT_STATUS_COMPLETED, PROJECT_STATUS_PLANNING,
+};
+use crate::commands::milestones::{milestone_from_row, MILESTONE_COLUMNS};
+use crate::commands::tags::{find_or_create_tag, link_tag_to_note};
+use crate::commands::journal::{column_task_key, dependency_key, id_key, note_tag_key, task_tag_key, ChangeSet};
+use crate::commands::activity::log_activity;
+use tauri::State;
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use uuid::Uuid;
+use std::collections::HashMap;
+
+const TIME_FORMAT: &str = "%H:%M:%S";
+
+fn parse_start_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| "Invalid start_date format, expected YYYY-MM-DD".to_string())
+}
+
+/// 把日期拆成相对开始日期的天数和本地时间
+fn date_offset(date: Option<DateTime<Utc>>, start: NaiveDate) -> (Option<i64>, Option<String>) {
+    match date {
+        Some(date) => {
+            let local = date.with_timezone(&Local);
+            (Some((local.date_naive() - start).num_days()), Some(local.time().format(TIME_FORMAT).to_string()))
+        }
+        None => (None, None),
+    }
+}
+
+/// 按开始日期还原日期，本地时间不存在（夏令时跳过的时段）时顺延一小时
+fn shifted_date(start: NaiveDate, offset_days: Option<i64>, time: Option<&str>) -> Option<DateTime<Utc>> {
+    let date = start + Duration::days(offset_days?);
+    let time = time
+        .and_then(|t| NaiveTime::parse_from_str(t, TIME_FORMAT).ok())
+        .unwrap_or(NaiveTime::MIN);
+    let naive = date.and_time(time);
+    Local.from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| Local.from_local_datetime(&(naive + Duration::hours(1))).earliest())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn load_project(conn: &Connection, id: &str) -> Result<Project, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM projects WHERE id = ? AND deleted_at IS NULL", PROJECT_COLUMNS),
+        params![id],
+        project_from_row
+    ).optional().map_err(|e| e.to_string())?.ok_or_else(|| "Project not found".to_string())
+}
+
+/// 按 id 分组的标签名称
+fn tag_names(conn: &Connection, sql: &str, project_id: &str) -> Result<HashMap<String, Vec<String>>, rusqlite::Error> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params![project_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for row in rows {
+        let (id, name) = row?;
+        tags.entry(id).or_default().push(name);
+    }
+    Ok(tags)
+}
+
+/// 读取项目的完整结构，日期换算为相对 `start` 的偏移。
+/// `keep_progress` 为 false 时（保存为模板）任务和里程碑都按未完成保存。
+fn capture_project(conn: &Connection, project: &Project, start: NaiveDate, keep_progress: bool) -> Result<ProjectTemplateContent, rusqlite::Error> {
+    // 项目下的任务连同子任务，子任务不一定设置了 project_id
+    let tree = "WITH RECURSIVE tree(id) AS (
+                    SELECT id FROM tasks WHERE project_id = ?1 AND deleted_at IS NULL
+                    UNION
+                    SELECT t.id FROM tasks t INNER JOIN tree ON t.parent_id = tree.id WHERE t.deleted_at IS NULL
+                )";
+
+    let task_tags = tag_names(conn, &format!(
+        "{} SELECT tt.task_id, tg.name FROM task_tags tt
+            INNER JOIN tags tg ON tg.id = tt.tag_id
+            WHERE tt.task_id IN (SELECT id FROM tree) ORDER BY tg.name",
+        tree
+    ), &project.id)?;
+
+    let mut placements: HashMap<String, (String, i32)> = HashMap::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT ct.task_id, ct.column_id, ct.position FROM column_tasks ct
+             INNER JOIN kanban_columns c ON c.id = ct.column_id
+             WHERE c.project_id = ?"
+        )?;
+        let rows = stmt.query_map(params![project.id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i32>(2)?)))?;
+        for row in rows {
+            let (task_id, column_id, position) = row?;
+            placements.entry(task_id).or_insert((column_id, position));
+        }
+    }
+
+    let mut columns = Vec::new();
+    {
+        let mut stmt = conn.prepare("SELECT id, name, position FROM kanban_columns WHERE project_id = ? ORDER BY position")?;
+        let rows = stmt.query_map(params![project.id], |row| {
+            Ok(TemplateColumn { key: row.get(0)?, name: row.get(1)?, position: row.get(2)? })
+        })?;
+        for row in rows {
+            columns.push(row?);
+        }
+    }
+
+    let mut milestones = Vec::new();
+    {
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM milestones WHERE project_id = ? ORDER BY position", MILESTONE_COLUMNS))?;
+        let rows = stmt.query_map(params![project.id], milestone_from_row)?;
+        for row in rows {
+            let milestone = row?;
+            let (due_offset_days, due_time) = date_offset(milestone.due_date, start);
+            milestones.push(TemplateMilestone {
+                key: milestone.id,
+                name: milestone.name,
+                description: milestone.description,
+                position: milestone.position,
+                due_offset_days,
+                due_time,
+                is_completed: keep_progress && milestone.completed_at.is_some(),
+                completed_at: milestone.completed_at.filter(|_| keep_progress),
+            });
+        }
+    }
+
+    let mut dependencies: HashMap<String, Vec<String>> = HashMap::new();
+    {
+        let mut stmt = conn.prepare(&format!(
+            "{} SELECT task_id, depends_on_id FROM task_dependencies
+                WHERE task_id IN (SELECT id FROM tree) AND depends_on_id IN (SELECT id FROM tree)",
+            tree
+        ))?;
+        let rows = stmt.query_map(params![project.id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (task_id, depends_on_id) = row?;
+            dependencies.entry(task_id).or_default().push(depends_on_id);
+        }
+    }
+
+    let mut tasks = Vec::new();
+    {
+        let mut stmt = conn.prepare(&format!(
+            "{} SELECT id, parent_id, title, description, priority, position, due_date, is_completed, recurrence, milestone_id,
+                       start_date, estimate_minutes, CASE WHEN is_completed = 1 THEN COALESCE(completed_at, updated_at) END
+                FROM tasks WHERE id IN (SELECT id FROM tree)
+                ORDER BY position, created_at",
+            tree
+        ))?;
+        let rows = stmt.query_map(params![project.id], |row| {
+            let due_date: Option<DateTime<Utc>> = row.get::<_, Option<String>>(6)?.map(|s| s.parse()).transpose()
+                .map_err(|_| rusqlite::Error::InvalidColumnType(6, "due_date".to_string(), rusqlite::types::Type::Text))?;
+            let start_date: Option<DateTime<Utc>> = row.get::<_, Option<String>>(10)?.map(|s| s.parse()).transpose()
+                .map_err(|_| rusqlite::Error::InvalidColumnType(10, "start_date".to_string(), rusqlite::types::Type::Text))?;
+            let completed_at: Option<DateTime<Utc>> = row.get::<_, Option<String>>(12)?.map(|s| s.parse()).transpose()
+                .map_err(|_| rusqlite::Error::InvalidColumnType(12, "completed_at".to_string(), rusqlite::types::Type::Text))?;
+            Ok((
+                TemplateTask {
+                    key: row.get(0)?,
+                    parent_key: row.get(1)?,
+                    title: row.get(2)?,
+                    description: row.get(3)?,
+                    priority: row.get(4)?,
+                    position: row.get(5)?,
+                    due_offset_days: None,
+                    due_time: None,
+                    is_completed: keep_progress && row.get::<_, i32>(7)? != 0,
+                    recurrence: row.get(8)?,
+                    milestone_key: row.get(9)?,
+                    column_key: None,
+                    column_position: 0,
+                    tags: Vec::new(),
+                    start_offset_days: None,
+                    start_time: None,
+                    estimate_minutes: row.get(11)?,
+                    depends_on: Vec::new(),
+                    completed_at: None,
+                },
+                due_date,
+                start_date,
+                completed_at,
+            ))
+        })?;
+        for row in rows {
+            let (mut task, due_date, start_date, completed_at) = row?;
+            task.completed_at = completed_at.filter(|_| task.is_completed);
+            (task.due_offset_days, task.due_time) = date_offset(due_date, start);
+            (task.start_offset_days, task.start_time) = date_offset(start_date, start);
+            task.depends_on = dependencies.remove(&task.key).unwrap_or_default();
+            if let Some((column_id, position)) = placements.get(&task.key) {
+                task.column_key = Some(column_id.clone());
+                task.column_position = *position;
+            }
+            task.tags = task_tags.get(&task.key).cloned().unwrap_or_default();
+            tasks.push(task);
+        }
+    }
+
+    let note_tags = tag_names(conn,
+        "SELECT nt.note_id, tg.name FROM note_tags nt
+         INNER JOIN tags tg ON tg.id = nt.tag_id
+         INNER JOIN notes n ON n.id = nt.note_id
+         WHERE n.project_id = ? AND n.deleted_at IS NULL ORDER BY tg.name",
+        &project.id)?;
+
+    let mut notes = Vec::new();
+    {
+        let mut stmt = conn.prepare("SELECT id, title, content FROM notes WHERE project_id = ? AND deleted_at IS NULL ORDER BY created_at")?;
+        let rows = stmt.query_map(params![project.id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?;
+        for row in rows {
+            let (id, title, content) = row?;
+            notes.push(TemplateNote { title, content, tags: note_tags.get(&id).cloned().unwrap_or_default() });
+        }
+    }
+
+    let (due_offset_days, due_time) = date_offset(project.due_date, start);
+    Ok(ProjectTemplateContent {
+        description: project.description.clone(),
+        color: project.color.clone(),
+        due_offset_days,
+        due_time,
+        columns,
+        milestones,
+        tasks,
+        notes,
+    })
+}
+
+/// 任务在模板中的层级，父任务先于子任务创建
+fn task_depths(tasks: &[TemplateTask]) -> HashMap<&str, usize> {
+    let parents: HashMap<&str, Option<&str>> = tasks.iter()
+        .map(|t| (t.key.as_str(), t.parent_key.as_deref()))
+        .collect();
+
+    let mut depths = HashMap::new();
+    for task in tasks {
+        let mut depth = 0;
+        let mut current = task.parent_key.as_deref();
+        // 父任务不在模板中时视为顶层任务，深度上限防止环
+        while let Some(parent) = current.filter(|p| parents.contains_key(p)) {
+            depth += 1;
+            if depth > tasks.len() {
+                break;
+            }
+            current = parents[parent];
+        }
+        depths.insert(task.key.as_str(), depth);
+    }
+    depths
+}
+
+/// 按名称查找标签，新建的标签记录到撤销日志
+fn find_or_create_tracked_tag(conn: &Connection, change: &mut ChangeSet, name: &str) -> Result<String, rusqlite::Error> {
+    let existed: bool = conn.query_row("SELECT COUNT(*) > 0 FROM tags WHERE name = ?", params![name], |row| row.get(0))?;
+    let id = find_or_create_tag(conn, name)?;
+    if !existed {
+        change.track_created("tags", id_key(&id))?;
+    }
+    Ok(id)
+}
+
+/// 按模板创建项目及其看板列、里程碑、任务和笔记，日期从 `start` 起算
+fn instantiate_template(
+    conn: &Connection,
+    label: &str,
+    content: &ProjectTemplateContent,
+    name: &str,
+    status: &str,
+    start: NaiveDate,
+) -> Result<Project, String> {
+    let now = Utc::now();
+    let project_id = Uuid::new_v4().to_string();
+    let mut change = ChangeSet::new(label);
+
+    let project = Project {
+        id: project_id.clone(),
+        name: name.to_string(),
+        description: content.description.clone(),
+        status: status.to_string(),
+        created_at: now,
+        updated_at: now,
+        due_date: shifted_date(start, content.due_offset_days, content.due_time.as_deref()),
+        color: content.color.clone(),
+    };
+
+    change.track(conn, "projects", id_key(&project_id)).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO projects (id, name, description, status, created_at, updated_at, due_date, color)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            project.id, project.name, project.description, project.status, now.to_rfc3339(), now.to_rfc3339(),
+            project.due_date.map(|d| d.to_rfc3339()), project.color
+        ]
+    ).map_err(|e| e.to_string())?;
+    record_status_change(conn, &mut change, &project_id, None, status, None, now).map_err(|e| e.to_string())?;
+
+    let mut column_ids = HashMap::new();
+    for column in &content.columns {
+        let id = Uuid::new_v4().to_string();
+        change.track(conn, "kanban_columns", id_key(&id)).map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO kanban_columns (id, project_id, name, position) VALUES (?, ?, ?, ?)",
+            params![id, project_id, column.name, column.position]
+        ).map_err(|e| e.to_string())?;
+        column_ids.insert(column.key.as_str(), id);
+    }
+
+    let mut milestone_ids = HashMap::new();
+    for milestone in &content.milestones {
+        let id = Uuid::new_v4().to_string();
+        let due_date = shifted_date(start, milestone.due_offset_days, milestone.due_time.as_deref());
+        change.track(conn, "milestones", id_key(&id)).map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO milestones (id, project_id, name, description, due_date, completed_at, position, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                id, project_id, milestone.name, milestone.description, due_date.map(|d| d.to_rfc3339()),
+                if milestone.is_completed { Some(milestone.completed_at.unwrap_or(now).to_rfc3339()) } else { None },
+                milestone.position, now.to_rfc3339(), now.to_rfc3339()
+            ]
+        ).map_err(|e| e.to_string())?;
+        milestone_ids.insert(milestone.key.as_str(), id);
+    }
+
+    let depths = task_depths(&content.tasks);
+    let mut ordered: Vec<&TemplateTask> = content.tasks.iter().collect();
+    ordered.sort_by_key(|t| depths.get(t.key.as_str()).copied().unwrap_or(0));
+
+    let mut task_ids: HashMap<&str, String> = HashMap::new();
+    for task in ordered {
+        let id = Uuid::new_v4().to_string();
+        let parent_id = task.parent_key.as_deref().and_then(|key| task_ids.get(key)).cloned();
+        let milestone_id = task.milestone_key.as_deref().and_then(|key| milestone_ids.get(key));
+        let due_date = shifted_date(start, task.due_offset_days, task.due_time.as_deref());
+        let start_date = shifted_date(start, task.start_offset_days, task.start_time.as_deref());
+
+        change.track(conn, "tasks", id_key(&id)).map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO tasks (id, title, description, is_completed, priority, due_date, remind_at,
+                               created_at, updated_at, project_id, parent_id, position, recurrence, milestone_id,
+                               start_date, estimate_minutes, completed_at)
+             VALUES (?, ?, ?, ?, ?, ?, NULL, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                id, task.title, task.description, task.is_completed as i32, task.priority,
+                due_date.map(|d| d.to_rfc3339()), now.to_rfc3339(), now.to_rfc3339(),
+                project_id, parent_id, task.position, task.recurrence, milestone_id,
+                start_date.map(|d| d.to_rfc3339()), task.estimate_minutes,
+                if task.is_completed { Some(task.completed_at.unwrap_or(now).to_rfc3339()) } else { None }
+            ]
+        ).map_err(|e| e.to_string())?;
+
+        for tag in &task.tags {
+            let tag_id = find_or_create_tracked_tag(conn, &mut change, tag).map_err(|e| e.to_string())?;
+            change.track(conn, "task_tags", task_tag_key(&id, &tag_id)).map_err(|e| e.to_string())?;
+            conn.execute(
+                "INSERT OR IGNORE INTO task_tags (task_id, tag_id) VALUES (?, ?)",
+                params![id, tag_id]
+            ).map_err(|e| e.to_string())?;
+        }
+        if let Some(column_id) = task.column_key.as_deref().and_then(|key| column_ids.get(key)) {
+            change.track(conn, "column_tasks", column_task_key(&id, column_id)).map_err(|e| e.to_string())?;
+            conn.execute(
+                "INSERT OR IGNORE INTO column_tasks (task_id, column_id, position) VALUES (?, ?, ?)",
+                params![id, column_id, task.column_position]
+            ).map_err(|e| e.to_string())?;
+        }
+        task_ids.insert(task.key.as_str(), id);
+    }
+
+    // 所有任务创建后再写入依赖，前置任务不在模板中时忽略
+    for task in &content.tasks {
+        for depends_on in &task.depends_on {
+            if let (Some(task_id), Some(depends_on_id)) = (task_ids.get(task.key.as_str()), task_ids.get(depends_on.as_str())) {
+                change.track(conn, "task_dependencies", dependency_key(task_id, depends_on_id)).map_err(|e| e.to_string())?;
+                conn.execute(
+                    "INSERT OR IGNORE INTO task_dependencies (task_id, depends_on_id, created_at) VALUES (?, ?, ?)",
+                    params![task_id, depends_on_id, now.to_rfc3339()]
+                ).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    for note in &content.notes {
+        let id = Uuid::new_v4().to_string();
+        change.track(conn, "notes", id_key(&id)).map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO notes (id, title, content, created_at, updated_at, project_id) VALUES (?, ?, ?, ?, ?, ?)",
+            params![id, note.title, note.content, now.to_rfc3339(), now.to_rfc3339(), project_id]
+        ).map_err(|e| e.to_string())?;
+        for tag in &note.tags {
+            let tag_id = find_or_create_tracked_tag(conn, &mut change, tag).map_err(|e| e.to_string())?;
+            change.track(conn, "note_tags", note_tag_key(&id, &tag_id)).map_err(|e| e.to_string())?;
+            link_tag_to_note(conn, &id, &tag_id).map_err(|e| e.to_string())?;
+        }
+    }
+
+    change.commit(conn).map_err(|e| e.to_string())?;
+    Ok(project)
+}
+
+fn template_from_row(row: &rusqlite::Row) -> Result<ProjectTemplate, rusqlite::Error> {
+    let content: String = row.get(3)?;
+    Ok(ProjectTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        content: serde_json::from_str(&content).map_err(|_| rusqlite::Error::InvalidColumnType(3, "content".to_string(), rusqlite::types::Type::Text))?,
+        created_at: row.get::<_, String>(4)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(4, "created_at".to_string(), rusqlite::types::Type::Text))?,
+        updated_at: row.get::<_, String>(5)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(5, "updated_at".to_string(), rusqlite::types::Type::Text))?,
+    })
+}
+
+fn load_template(conn: &Connection, id: &str) -> Result<ProjectTemplate, String> {
+    conn.query_row(
+        "SELECT id, name, description, content, created_at, updated_at FROM project_templates WHERE id = ?",
+        params![id],
+        template_from_row
+    ).optional().map_err(|e| e.to_string())?.ok_or_else(|| "Template not found".to_string())
+}
+
+/// 把项目保存为模板。日期保存为相对 `start_date`（默认项目创建日期）的偏移，
+/// 任务完成状态不会保存。同名模板会被覆盖。
+#[tauri::command]
+pub async fn save_project_as_template(
+    project_id: String,
+    name: String,
+    description: Option<String>,
+    start_date: Option<String>,
+    db: State<'_, Database>
+) -> Result<ProjectTemplate, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+
+    let project = load_project(&conn, &project_id)?;
+    let start = match start_date {
+        Some(date) => parse_start_date(&date)?,
+        None => project.created_at.with_timezone(&Local).date_naive(),
+    };
+    let content = capture_project(&conn, &project, start, false).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string(&content).map_err(|e| e.to_string())?;
+
+    let now = Utc::now();
+    let existing: Option<(String, String)> = conn.query_row(
+        "SELECT id, created_at FROM project_templates WHERE name = ?",
+        params![name],
+        |row| Ok((row.get(0)?, row.get(1)?))
+    ).optional().map_err(|e| e.to_string())?;
+    let (id, created_at) = match existing {
+        Some((id, created_at)) => (id, created_at.parse().unwrap_or(now)),
+        None => (Uuid::new_v4().to_string(), now),
+    };
+
+    conn.execute(
+        "INSERT OR REPLACE INTO project_templates (id, name, description, content, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![id, name, description, json, created_at.to_rfc3339(), now.to_rfc3339()]
+    ).map_err(|e| e.to_string())?;
+
+    Ok(ProjectTemplate {
+        id,
+        name,
+        description,
+        content,
+        created_at,
+        updated_at: now,
+    })
+}
+
+#[tauri::command]
+pub async fn get_project_templates(db: State<'_, Database>) -> Result<Vec<ProjectTemplate>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name, description, content, created_at, updated_at FROM project_templates ORDER BY name"
+    ).map_err(|e| e.to_string())?;
+
+    let template_iter = stmt.query_map([], template_from_row).map_err(|e| e.to_string())?;
+
+    let mut templates = Vec::new();
+    for template in template_iter {
+        templates.push(template.map_err(|e| e.to_string())?);
+    }
+
+    Ok(templates)
+}
+
+#[tauri::command]
+pub async fn delete_project_template(id: String, db: State<'_, Database>) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let deleted = conn.execute("DELETE FROM project_templates WHERE id = ?", params![id]).map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err("Template not found".to_string());
+    }
+
+    Ok(())
+}
+
+/// 按模板创建项目，所有日期从 `start_date`（默认今天）起算。
+/// `status` 只能是 planning 或 active（默认）
+#[tauri::command]
+pub async fn create_project_from_template(
+    template_id: String,
+    name: String,
+    start_date: Option<String>,
+    status: Option<String>,
+    db: State<'_, Database>
+) -> Result<Project, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Project name cannot be empty".to_string());
+    }
+    let status = status.unwrap_or_else(|| PROJECT_STATUS_ACTIVE.to_string());
+    if status != PROJECT_STATUS_PLANNING && status != PROJECT_STATUS_ACTIVE {
+        return Err(format!("New projects must start as {} or {}", PROJECT_STATUS_PLANNING, PROJECT_STATUS_ACTIVE));
+    }
+    let start = match start_date {
+        Some(date) => parse_start_date(&date)?,
+        None => Local::now().date_naive(),
+    };
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let template = load_template(&tx, &template_id)?;
+    let project = instantiate_template(&tx, "create_project_from_template", &template.content, &name, &status, start)?;
+    log_activity(&tx, "project", &project.id, "created", &format!("Project \"{}\" created from template \"{}\"", name, template.name))
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(project)
+}
+
+/// 深度复制项目：任务层级、完成状态、依赖、看板列、里程碑、标签和笔记。
+/// 指定 `start_date` 时所有日期整体平移，使原项目的创建日期对应到该日期。
+/// 已完成或归档的项目复制后为 active。
+#[tauri::command]
+pub async fn duplicate_project(
+    project_id: String,
+    name: Option<String>,
+    start_date: Option<String>,
+    db: State<'_, Database>
+) -> Result<Project, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let source = load_project(&tx, &project_id)?;
+    let anchor = source.created_at.with_timezone(&Local).date_naive();
+    let start = match start_date {
+        Some(date) => parse_start_date(&date)?,
+        None => anchor,
+    };
+
+    let name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| format!("{} (copy)", source.name));
+    let status = match source.status.as_str() {
+        PROJECT_STATUS_COMPLETED | PROJECT_STATUS_ARCHIVED => PROJECT_STATUS_ACTIVE,
+        status if validate_project_status(status).is_ok() => status,
+        _ => PROJECT_STATUS_ACTIVE,
+    };
+
+    let content = capture_project(&tx, &source, anchor, true).map_err(|e| e.to_string())?;
+    let project = instantiate_template(&tx, "duplicate_project", &content, &name, status, start)?;
+    log_activity(&tx, "project", &project.id, "created", &format!("Project \"{}\" duplicated from \"{}\"", name, source.name))
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(project)
+}
@@ -1,31 +1,167 @@
-use crate::models::Project;
+use crate::models::{Project, ProjectStatusChange};
 use crate::db::Database;
+use crate::commands::trash::{project_note_ids, project_task_ids, soft_delete_project};
+use crate::commands::journal::{id_key, ChangeSet};
+use crate::commands::activity::{entity_title, log_activity};
 use tauri::State;
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension, Row};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+// 项目状态
+pub const PROJECT_STATUS_PLANNING: &str = "planning";
+pub const PROJECT_STATUS_ACTIVE: &str = "active";
+pub const PROJECT_STATUS_ON_HOLD: &str = "on_hold";
+pub const PROJECT_STATUS_COMPLETED: &str = "completed";
+pub const PROJECT_STATUS_ARCHIVED: &str = "archived";
+
+pub const PROJECT_COLUMNS: &str = "id, name, description, status, created_at, updated_at, due_date, color";
+
+pub fn validate_project_status(status: &str) -> Result<(), String> {
+    match status {
+        PROJECT_STATUS_PLANNING | PROJECT_STATUS_ACTIVE | PROJECT_STATUS_ON_HOLD
+        | PROJECT_STATUS_COMPLETED | PROJECT_STATUS_ARCHIVED => Ok(()),
+        _ => Err(format!("Unknown project status: {}", status)),
+    }
+}
+
+/// 允许的状态变化。归档的项目可以恢复到任意状态，已完成的项目只能重新开始或归档
+fn can_transition(from: &str, to: &str) -> bool {
+    matches!(
+        (from, to),
+        (PROJECT_STATUS_PLANNING, PROJECT_STATUS_ACTIVE | PROJECT_STATUS_ON_HOLD | PROJECT_STATUS_ARCHIVED)
+            | (PROJECT_STATUS_ACTIVE, PROJECT_STATUS_ON_HOLD | PROJECT_STATUS_COMPLETED | PROJECT_STATUS_ARCHIVED)
+            | (PROJECT_STATUS_ON_HOLD, PROJECT_STATUS_PLANNING | PROJECT_STATUS_ACTIVE | PROJECT_STATUS_ARCHIVED)
+            | (PROJECT_STATUS_COMPLETED, PROJECT_STATUS_ACTIVE | PROJECT_STATUS_ARCHIVED)
+            | (PROJECT_STATUS_ARCHIVED, PROJECT_STATUS_PLANNING | PROJECT_STATUS_ACTIVE | PROJECT_STATUS_ON_HOLD | PROJECT_STATUS_COMPLETED)
+    )
+}
+
+pub fn project_from_row(row: &Row) -> Result<Project, rusqlite::Error> {
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        status: row.get(3)?,
+        created_at: row.get::<_, String>(4)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(4, "created_at".to_string(), rusqlite::types::Type::Text))?,
+        updated_at: row.get::<_, String>(5)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(5, "updated_at".to_string(), rusqlite::types::Type::Text))?,
+        due_date: row.get::<_, Option<String>>(6)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(6, "due_date".to_string(), rusqlite::types::Type::Text))?,
+        color: row.get(7)?,
+    })
+}
+
+/// 写入一条状态变更记录，记录的 id 预先登记到 `change` 以便撤销
+pub fn record_status_change(
+    conn: &Connection,
+    change: &mut ChangeSet,
+    project_id: &str,
+    from_status: Option<&str>,
+    to_status: &str,
+    note: Option<String>,
+    at: DateTime<Utc>,
+) -> Result<ProjectStatusChange, rusqlite::Error> {
+    let record = ProjectStatusChange {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        from_status: from_status.map(|s| s.to_string()),
+        to_status: to_status.to_string(),
+        note: note.filter(|n| !n.trim().is_empty()),
+        changed_at: at,
+    };
+
+    change.track(conn, "project_status_history", id_key(&record.id))?;
+    conn.execute(
+        "INSERT INTO project_status_history (id, project_id, from_status, to_status, note, changed_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![record.id, record.project_id, record.from_status, record.to_status, record.note, at.to_rfc3339()]
+    )?;
+
+    Ok(record)
+}
+
+/// 检查并执行状态变化，记录变更时间并写入活动日志
+fn transition_project(conn: &Connection, label: &str, id: &str, to: &str, note: Option<String>) -> Result<ProjectStatusChange, String> {
+    validate_project_status(to)?;
+    let now = Utc::now();
+
+    let from: String = conn.query_row(
+        "SELECT status FROM projects WHERE id = ? AND deleted_at IS NULL",
+        params![id],
+        |row| row.get(0)
+    ).optional().map_err(|e| e.to_string())?.ok_or("Project not found")?;
+
+    if from == to {
+        return Err(format!("Project is already {}", to));
+    }
+    if !can_transition(&from, to) {
+        return Err(format!("Cannot change project status from {} to {}", from, to));
+    }
+
+    let mut change = ChangeSet::new(label);
+    change.track(conn, "projects", id_key(id)).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "UPDATE projects SET status = ?, updated_at = ? WHERE id = ?",
+        params![to, now.to_rfc3339(), id]
+    ).map_err(|e| e.to_string())?;
+    let record = record_status_change(conn, &mut change, id, Some(from.as_str()), to, note, now).map_err(|e| e.to_string())?;
+
+    change.commit(conn).map_err(|e| e.to_string())?;
+
+    let name = entity_title(conn, "project", id).map_err(|e| e.to_string())?;
+    let (action, summary) = if to == PROJECT_STATUS_ARCHIVED {
+        ("archived", format!("Project \"{}\" archived", name))
+    } else if from == PROJECT_STATUS_ARCHIVED {
+        ("unarchived", format!("Project \"{}\" unarchived as {}", name, to))
+    } else {
+        ("status_changed", format!("Project \"{}\" moved from {} to {}", name, from, to))
+    };
+    log_activity(conn, "project", id, action, &summary).map_err(|e| e.to_string())?;
+
+    Ok(record)
+}
+
+/// 按名称查找未删除的项目，不存在时创建，返回项目 id
+pub fn find_or_create_project(conn: &Connection, name: &str) -> Result<String, rusqlite::Error> {
+    let existing: Option<String> = conn.query_row(
+        "SELECT id FROM projects WHERE name = ? AND deleted_at IS NULL",
+        params![name],
+        |row| row.get(0)
+    ).optional()?;
+
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO projects (id, name, status, created_at, updated_at, color)
+         VALUES (?, ?, 'active', ?, ?, '#3B82F6')",
+        params![id, name, now, now]
+    )?;
+
+    Ok(id)
+}
+
+/// 获取项目列表，可按状态筛选
 #[tauri::command]
-pub async fn get_all_projects(db: State<'_, Database>) -> Result<Vec<Project>, String> {
+pub async fn get_all_projects(status: Option<String>, db: State<'_, Database>) -> Result<Vec<Project>, String> {
     let conn = db.get_connection().map_err(|e| e.to_string())?;
     
-    let mut stmt = conn.prepare(
-        "SELECT id, name, description, color, is_archived, created_at, updated_at
+    if let Some(status) = &status {
+        validate_project_status(status)?;
+    }
+    
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
          FROM projects 
-         ORDER BY created_at DESC"
-    ).map_err(|e| e.to_string())?;
+         WHERE deleted_at IS NULL AND (?1 IS NULL OR status = ?1)
+         ORDER BY created_at DESC",
+        PROJECT_COLUMNS
+    )).map_err(|e| e.to_string())?;
     
-    let project_iter = stmt.query_map([], |row| {
-        Ok(Project {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            description: row.get(2)?,
-            color: row.get(3)?,
-            is_archived: row.get::<_, i32>(4)? != 0,
-            created_at: row.get::<_, String>(5)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(5, "created_at".to_string(), rusqlite::types::Type::Text))?,
-            updated_at: row.get::<_, String>(6)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(6, "updated_at".to_string(), rusqlite::types::Type::Text))?,
-        })
-    }).map_err(|e| e.to_string())?;
+    let project_iter = stmt.query_map(params![status], project_from_row).map_err(|e| e.to_string())?;
     
     let mut projects = Vec::new();
     for project in project_iter {
@@ -40,20 +176,9 @@ pub async fn get_project_by_id(id: String, db: State<'_, Database>) -> Result<Op
     let conn = db.get_connection().map_err(|e| e.to_string())?;
     
     let result = conn.query_row(
-        "SELECT id, name, description, color, is_archived, created_at, updated_at
-         FROM projects WHERE id = ?",
+        &format!("SELECT {} FROM projects WHERE id = ? AND deleted_at IS NULL", PROJECT_COLUMNS),
         params![id],
-        |row| {
-            Ok(Project {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                color: row.get(3)?,
-                is_archived: row.get::<_, i32>(4)? != 0,
-                created_at: row.get::<_, String>(5)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(5, "created_at".to_string(), rusqlite::types::Type::Text))?,
-                updated_at: row.get::<_, String>(6)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(6, "updated_at".to_string(), rusqlite::types::Type::Text))?,
-            })
-        }
+        project_from_row
     );
     
     match result {
@@ -63,48 +188,78 @@ pub async fn get_project_by_id(id: String, db: State<'_, Database>) -> Result<Op
     }
 }
 
+/// 创建项目，初始状态只能是 planning 或 active（默认）
 #[tauri::command]
 pub async fn create_project(
     name: String,
     description: Option<String>,
     color: Option<String>,
+    status: Option<String>,
+    due_date: Option<String>,
     db: State<'_, Database>
 ) -> Result<Project, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
     
     let id = Uuid::new_v4().to_string();
     let now = Utc::now();
     let color = color.unwrap_or_else(|| "#3B82F6".to_string());
+    let status = status.unwrap_or_else(|| PROJECT_STATUS_ACTIVE.to_string());
+    if status != PROJECT_STATUS_PLANNING && status != PROJECT_STATUS_ACTIVE {
+        return Err(format!("New projects must start as {} or {}", PROJECT_STATUS_PLANNING, PROJECT_STATUS_ACTIVE));
+    }
     
-    conn.execute(
-        "INSERT INTO projects (id, name, description, color, is_archived, created_at, updated_at) 
-         VALUES (?, ?, ?, ?, 0, ?, ?)",
-        params![id, name, description, color, now.to_rfc3339(), now.to_rfc3339()]
+    let due_date_parsed: Option<DateTime<Utc>> = if let Some(date_str) = &due_date {
+        Some(date_str.parse().map_err(|_| "Invalid due_date format")?)
+    } else {
+        None
+    };
+    
+    let mut change = ChangeSet::new("create_project");
+    change.track(&tx, "projects", id_key(&id)).map_err(|e| e.to_string())?;
+    
+    tx.execute(
+        "INSERT INTO projects (id, name, description, status, created_at, updated_at, due_date, color) 
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![id, name, description, status, now.to_rfc3339(), now.to_rfc3339(), due_date_parsed.map(|d| d.to_rfc3339()), color]
     ).map_err(|e| e.to_string())?;
+    record_status_change(&tx, &mut change, &id, None, &status, None, now).map_err(|e| e.to_string())?;
+    
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    log_activity(&tx, "project", &id, "created", &format!("Project \"{}\" created", name)).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
     
     Ok(Project {
         id,
         name,
         description,
-        color,
-        is_archived: false,
+        status,
         created_at: now,
         updated_at: now,
+        due_date: due_date_parsed,
+        color,
     })
 }
 
+/// 更新项目信息。状态通过 `set_project_status` 修改，以便检查状态变化是否允许
 #[tauri::command]
 pub async fn update_project(
     id: String,
     name: Option<String>,
     description: Option<String>,
     color: Option<String>,
-    is_archived: Option<bool>,
+    due_date: Option<String>,
     db: State<'_, Database>
 ) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
     let now = Utc::now();
     
+    let mut change = ChangeSet::new("update_project");
+    change.track(&tx, "projects", id_key(&id)).map_err(|e| e.to_string())?;
+    
+    let project_id = id.clone();
+    
     let mut query_parts = Vec::new();
     let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
     
@@ -123,9 +278,14 @@ pub async fn update_project(
         params_vec.push(Box::new(color));
     }
     
-    if let Some(is_archived) = is_archived {
-        query_parts.push("is_archived = ?");
-        params_vec.push(Box::new(if is_archived { 1 } else { 0 }));
+    if let Some(due_date) = due_date {
+        query_parts.push("due_date = ?");
+        let due_date_parsed: Option<DateTime<Utc>> = if due_date.is_empty() {
+            None
+        } else {
+            Some(due_date.parse().map_err(|_| "Invalid due_date format")?)
+        };
+        params_vec.push(Box::new(due_date_parsed.map(|d| d.to_rfc3339())));
     }
     
     query_parts.push("updated_at = ?");
@@ -137,23 +297,37 @@ pub async fn update_project(
         query_parts.join(", ")
     );
     
-    conn.execute(&query, rusqlite::params_from_iter(params_vec.iter().map(|p| p.as_ref())))
+    tx.execute(&query, rusqlite::params_from_iter(params_vec.iter().map(|p| p.as_ref())))
         .map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    let summary = format!("Project \"{}\" updated", entity_title(&tx, "project", &project_id).map_err(|e| e.to_string())?);
+    log_activity(&tx, "project", &project_id, "updated", &summary).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(())
 }
 
 #[tauri::command]
 pub async fn delete_project(id: String, db: State<'_, Database>) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let now = Utc::now();
     
-    // 删除项目相关的任务
-    conn.execute("DELETE FROM tasks WHERE project_id = ?", params![id])
-        .map_err(|e| e.to_string())?;
+    // 项目连同其任务和笔记一起移入回收站，不再直接删除任务
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    
+    let mut change = ChangeSet::new("delete_project");
+    change.track(&tx, "projects", id_key(&id)).map_err(|e| e.to_string())?;
+    let task_ids = project_task_ids(&tx, &id, None).map_err(|e| e.to_string())?;
+    change.track_ids(&tx, "tasks", &task_ids).map_err(|e| e.to_string())?;
+    let note_ids = project_note_ids(&tx, &id, None).map_err(|e| e.to_string())?;
+    change.track_ids(&tx, "notes", &note_ids).map_err(|e| e.to_string())?;
     
-    // 删除项目
-    conn.execute("DELETE FROM projects WHERE id = ?", params![id])
+    soft_delete_project(&tx, &id, &now.to_rfc3339())
         .map_err(|e| e.to_string())?;
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    log_activity(&tx, "project", &id, "deleted", &format!("Project \"{}\" moved to trash", entity_title(&tx, "project", &id).map_err(|e| e.to_string())?)).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
     
     Ok(())
 }
@@ -166,14 +340,18 @@ pub async fn get_project_stats(id: String, db: State<'_, Database>) -> Result<se
         "SELECT 
             COUNT(*) as total_tasks,
             COUNT(CASE WHEN is_completed = 1 THEN 1 END) as completed_tasks,
-            COUNT(CASE WHEN is_completed = 0 THEN 1 END) as pending_tasks
-         FROM tasks WHERE project_id = ?",
+            COUNT(CASE WHEN is_completed = 0 THEN 1 END) as pending_tasks,
+            (SELECT COUNT(*) FROM milestones WHERE project_id = ?1) as total_milestones,
+            (SELECT COUNT(*) FROM milestones WHERE project_id = ?1 AND completed_at IS NOT NULL) as completed_milestones
+         FROM tasks WHERE project_id = ?1 AND deleted_at IS NULL",
         params![id],
         |row| {
             Ok(serde_json::json!({
                 "total_tasks": row.get::<_, i32>(0)?,
                 "completed_tasks": row.get::<_, i32>(1)?,
-                "pending_tasks": row.get::<_, i32>(2)?
+                "pending_tasks": row.get::<_, i32>(2)?,
+                "total_milestones": row.get::<_, i32>(3)?,
+                "completed_milestones": row.get::<_, i32>(4)?
             }))
         }
     ).map_err(|e| e.to_string())?;
@@ -181,28 +359,77 @@ pub async fn get_project_stats(id: String, db: State<'_, Database>) -> Result<se
     Ok(stats)
 }
 
+/// 修改项目状态，不允许的状态变化返回错误。`note` 记录变更原因
 #[tauri::command]
-pub async fn archive_project(id: String, db: State<'_, Database>) -> Result<(), String> {
+pub async fn set_project_status(
+    id: String,
+    status: String,
+    note: Option<String>,
+    db: State<'_, Database>
+) -> Result<ProjectStatusChange, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let record = transition_project(&tx, "set_project_status", &id, &status, note)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(record)
+}
+
+#[tauri::command]
+pub async fn get_project_status_history(project_id: String, db: State<'_, Database>) -> Result<Vec<ProjectStatusChange>, String> {
     let conn = db.get_connection().map_err(|e| e.to_string())?;
-    let now = Utc::now();
     
-    conn.execute(
-        "UPDATE projects SET is_archived = 1, updated_at = ? WHERE id = ?",
-        params![now.to_rfc3339(), id]
+    let mut stmt = conn.prepare(
+        "SELECT id, project_id, from_status, to_status, note, changed_at
+         FROM project_status_history WHERE project_id = ? ORDER BY changed_at"
     ).map_err(|e| e.to_string())?;
     
+    let change_iter = stmt.query_map(params![project_id], |row| {
+        Ok(ProjectStatusChange {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            from_status: row.get(2)?,
+            to_status: row.get(3)?,
+            note: row.get(4)?,
+            changed_at: row.get::<_, String>(5)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(5, "changed_at".to_string(), rusqlite::types::Type::Text))?,
+        })
+    }).map_err(|e| e.to_string())?;
+    
+    let mut changes = Vec::new();
+    for change in change_iter {
+        changes.push(change.map_err(|e| e.to_string())?);
+    }
+    
+    Ok(changes)
+}
+
+#[tauri::command]
+pub async fn archive_project(id: String, db: State<'_, Database>) -> Result<(), String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    transition_project(&tx, "archive_project", &id, PROJECT_STATUS_ARCHIVED, None)?;
+    tx.commit().map_err(|e| e.to_string())?;
     Ok(())
 }
 
+/// 取消归档，恢复到归档前的状态
 #[tauri::command]
 pub async fn unarchive_project(id: String, db: State<'_, Database>) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-    let now = Utc::now();
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
     
-    conn.execute(
-        "UPDATE projects SET is_archived = 0, updated_at = ? WHERE id = ?",
-        params![now.to_rfc3339(), id]
-    ).map_err(|e| e.to_string())?;
+    let previous: Option<String> = tx.query_row(
+        "SELECT from_status FROM project_status_history
+         WHERE project_id = ? AND to_status = ?
+         ORDER BY changed_at DESC LIMIT 1",
+        params![id, PROJECT_STATUS_ARCHIVED],
+        |row| row.get(0)
+    ).optional().map_err(|e| e.to_string())?.flatten();
     
+    let status = previous
+        .filter(|s| s != PROJECT_STATUS_ARCHIVED && validate_project_status(s).is_ok())
+        .unwrap_or_else(|| PROJECT_STATUS_ACTIVE.to_string());
+    
+    transition_project(&tx, "unarchive_project", &id, &status, None)?;
+    tx.commit().map_err(|e| e.to_string())?;
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,95 @@
+use crate::models::TaskReminder;
+use crate::db::{self, Database};
+use crate::commands::focus_mode::reminders_silenced;
+use tauri::{AppHandle, Emitter, Manager, State};
+use rusqlite::Connection;
+use chrono::{DateTime, Utc};
+use std::time::Duration as StdDuration;
+
+pub const REMINDER_EVENT: &str = "reminder://due";
+
+// 上次检查提醒的时间，之后到期的提醒还没有发送
+const REMINDERS_CHECKED_SETTING: &str = "reminders_checked_at";
+
+const REMINDER_CHECK_SECS: u64 = 30;
+
+fn checked_at(conn: &Connection) -> Result<Option<DateTime<Utc>>, String> {
+    Ok(db::get_setting(conn, REMINDERS_CHECKED_SETTING)
+        .map_err(|e| e.to_string())?
+        .and_then(|v| v.parse().ok()))
+}
+
+/// 上次检查之后到期、还没有发送的提醒
+pub fn pending_reminders(conn: &Connection, now: DateTime<Utc>) -> Result<Vec<TaskReminder>, String> {
+    let since = match checked_at(conn)? {
+        Some(since) => since,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT id, title, remind_at FROM tasks
+         WHERE remind_at IS NOT NULL AND is_completed = 0 AND deleted_at IS NULL"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+    }).map_err(|e| e.to_string())?;
+
+    let mut reminders = Vec::new();
+    for row in rows {
+        let (task_id, title, remind_at) = row.map_err(|e| e.to_string())?;
+        let remind_at: DateTime<Utc> = match remind_at.parse() {
+            Ok(remind_at) => remind_at,
+            Err(_) => continue,
+        };
+        if remind_at > since && remind_at <= now {
+            let deferred = (now - remind_at).num_seconds() > REMINDER_CHECK_SECS as i64;
+            reminders.push(TaskReminder { task_id, title, remind_at, deferred });
+        }
+    }
+    reminders.sort_by_key(|r| r.remind_at);
+
+    Ok(reminders)
+}
+
+/// 发送到期的提醒。专注模式静默提醒时不发送，也不推进检查时间，
+/// 这样专注结束后会一次补发期间到期的提醒
+pub fn deliver_due_reminders(app: &AppHandle, conn: &Connection) -> Result<usize, String> {
+    if reminders_silenced(conn)? {
+        return Ok(0);
+    }
+
+    let now = Utc::now();
+    let reminders = pending_reminders(conn, now)?;
+    if !reminders.is_empty() {
+        app.emit(REMINDER_EVENT, &reminders).map_err(|e| e.to_string())?;
+    }
+    db::set_setting(conn, REMINDERS_CHECKED_SETTING, &now.to_rfc3339()).map_err(|e| e.to_string())?;
+
+    Ok(reminders.len())
+}
+
+/// 定期检查任务提醒。第一次运行时从当前时间开始，不补发安装前的提醒
+pub fn spawn_reminder_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            {
+                let database = app.state::<Database>();
+                let result = database.get_connection()
+                    .map_err(|e| e.to_string())
+                    .and_then(|conn| deliver_due_reminders(&app, &conn));
+                if let Err(e) = result {
+                    log::warn!("Failed to deliver task reminders: {}", e);
+                }
+            }
+            tokio::time::sleep(StdDuration::from_secs(REMINDER_CHECK_SECS)).await;
+        }
+    });
+}
+
+/// 还没有发送的提醒，专注期间可以用来显示被延后的数量
+#[tauri::command]
+pub async fn get_pending_reminders(db: State<'_, Database>) -> Result<Vec<TaskReminder>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    pending_reminders(&conn, Utc::now())
+}
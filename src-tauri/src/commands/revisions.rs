@@ -0,0 +1,415 @@
+use crate::models::{DiffChunk, Note, NoteRevision};
+use crate::db::Database;
+use crate::commands::activity::{entity_title, log_activity};
+use crate::commands::journal::{id_key, ChangeSet};
+use tauri::State;
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+// 自动修订的节流时间（秒），避免自动保存产生大量修订
+pub const REVISION_THROTTLE_SECS: i64 = 300;
+// 默认保留策略：每篇笔记最多保留的修订数与最长保留天数
+pub const DEFAULT_MAX_REVISIONS_PER_NOTE: i64 = 100;
+pub const DEFAULT_MAX_REVISION_AGE_DAYS: i64 = 90;
+// 差异计算允许的最大编辑距离，超过时整体替换，限制大文本比较的耗时和内存
+const MAX_DIFF_EDITS: usize = 1000;
+
+/// 将笔记当前的标题和内容保存为一条修订。
+///
+/// `force` 为 false 时受节流限制：节流期间除第一条修订外只再保留一条，之后的保存更新这一条；
+/// 内容与最近一条修订相同时不会重复保存。
+/// 返回是否实际写入了修订。
+pub fn save_note_revision(conn: &Connection, note_id: &str, force: bool) -> Result<bool, rusqlite::Error> {
+    let current: Option<(String, String)> = conn.query_row(
+        "SELECT title, content FROM notes WHERE id = ?",
+        params![note_id],
+        |row| Ok((row.get(0)?, row.get(1)?))
+    ).optional()?;
+
+    let (title, content) = match current {
+        Some(current) => current,
+        None => return Ok(false),
+    };
+
+    let recent: Vec<(String, String, String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT id, title, content, created_at FROM note_revisions
+             WHERE note_id = ?
+             ORDER BY created_at DESC LIMIT 2"
+        )?;
+        let rows = stmt.query_map(params![note_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows
+    };
+
+    let now = Utc::now();
+    let within_throttle = |created_at: &str| {
+        created_at.parse::<DateTime<Utc>>()
+            .is_ok_and(|at| now - at < Duration::seconds(REVISION_THROTTLE_SECS))
+    };
+
+    if let Some((latest_id, latest_title, latest_content, latest_at)) = recent.first() {
+        if *latest_title == title && *latest_content == content {
+            return Ok(false);
+        }
+
+        // 节流期间不再新增修订，但始终保留覆盖前的最新内容
+        // （例如误粘贴覆盖前刚做的修改），期间的第一条修订保持不变
+        let rolling = recent.get(1).is_some_and(|(_, _, _, previous_at)| within_throttle(previous_at));
+        if !force && within_throttle(latest_at) && rolling {
+            conn.execute(
+                "UPDATE note_revisions SET title = ?, content = ?, created_at = ? WHERE id = ?",
+                params![title, content, now.to_rfc3339(), latest_id]
+            )?;
+            return Ok(true);
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO note_revisions (id, note_id, title, content, created_at) VALUES (?, ?, ?, ?, ?)",
+        params![Uuid::new_v4().to_string(), note_id, title, content, now.to_rfc3339()]
+    )?;
+
+    prune_revisions(conn, Some(note_id), Some(DEFAULT_MAX_REVISION_AGE_DAYS), Some(DEFAULT_MAX_REVISIONS_PER_NOTE))?;
+
+    Ok(true)
+}
+
+/// 按保留天数和数量清理修订，`note_id` 为 None 时作用于所有笔记。
+pub fn prune_revisions(
+    conn: &Connection,
+    note_id: Option<&str>,
+    max_age_days: Option<i64>,
+    max_count: Option<i64>,
+) -> Result<usize, rusqlite::Error> {
+    let mut deleted = 0;
+
+    if let Some(days) = max_age_days {
+        let cutoff = (Utc::now() - Duration::days(days)).to_rfc3339();
+        deleted += conn.execute(
+            "DELETE FROM note_revisions WHERE created_at < ? AND (?2 IS NULL OR note_id = ?2)",
+            params![cutoff, note_id]
+        )?;
+    }
+
+    if let Some(count) = max_count {
+        deleted += conn.execute(
+            "DELETE FROM note_revisions WHERE id IN (
+                SELECT id FROM (
+                    SELECT id, ROW_NUMBER() OVER (PARTITION BY note_id ORDER BY created_at DESC) AS rn
+                    FROM note_revisions
+                    WHERE ?2 IS NULL OR note_id = ?2
+                ) WHERE rn > ?1
+            )",
+            params![count, note_id]
+        )?;
+    }
+
+    Ok(deleted)
+}
+
+fn load_revision(conn: &Connection, id: &str) -> Result<NoteRevision, rusqlite::Error> {
+    conn.query_row(
+        "SELECT id, note_id, title, content, created_at FROM note_revisions WHERE id = ?",
+        params![id],
+        |row| {
+            Ok(NoteRevision {
+                id: row.get(0)?,
+                note_id: row.get(1)?,
+                title: row.get(2)?,
+                content: row.get(3)?,
+                created_at: row.get::<_, String>(4)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(4, "created_at".to_string(), rusqlite::types::Type::Text))?,
+            })
+        }
+    )
+}
+
+/// 将文本切分为行（保留换行符）
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+/// 将文本切分为单词与空白交替的片段，拼接后与原文一致
+fn split_words(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut last_is_space: Option<bool> = None;
+
+    for (i, c) in text.char_indices() {
+        let is_space = c.is_whitespace();
+        if let Some(prev) = last_is_space {
+            if prev != is_space {
+                tokens.push(&text[start..i]);
+                start = i;
+            }
+        }
+        last_is_space = Some(is_space);
+    }
+
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+
+    tokens
+}
+
+fn push_chunk(chunks: &mut Vec<DiffChunk>, kind: &str, text: &str) {
+    if let Some(last) = chunks.last_mut() {
+        if last.kind == kind {
+            last.text.push_str(text);
+            return;
+        }
+    }
+    chunks.push(DiffChunk {
+        kind: kind.to_string(),
+        text: text.to_string(),
+    });
+}
+
+/// Myers 差异算法的前向搜索，记录每一步开始前各对角线到达的位置（只保留 k ∈ [-d, d]）。
+/// 编辑距离超过 `MAX_DIFF_EDITS` 时返回 None
+fn myers_trace(old: &[&str], new: &[&str]) -> Option<Vec<Vec<usize>>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max_d = (n + m).min(MAX_DIFF_EDITS as isize);
+    let offset = max_d + 1;
+    let mut v = vec![0usize; 2 * max_d as usize + 3];
+    let mut trace = Vec::new();
+
+    for d in 0..=max_d {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+
+        for k in (-d..=d).step_by(2) {
+            let index = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            } as isize;
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index] = x as usize;
+
+            if x >= n && y >= m {
+                return Some(trace);
+            }
+        }
+    }
+
+    None
+}
+
+/// 基于 Myers 算法的差异计算，耗时和内存为 O((n+m)·D)。
+/// 改动过多时不再逐个比较，整体作为删除和插入
+fn diff_tokens(old: &[&str], new: &[&str]) -> Vec<DiffChunk> {
+    let mut chunks = Vec::new();
+
+    // 先去掉公共前缀和后缀，缩小比较的规模
+    let prefix = old.iter().zip(new.iter()).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    for token in &old[..prefix] {
+        push_chunk(&mut chunks, "equal", token);
+    }
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    match myers_trace(old_mid, new_mid) {
+        Some(trace) => {
+            // 从终点回溯得到倒序的编辑序列
+            let mut edits = Vec::new();
+            let (mut x, mut y) = (old_mid.len() as isize, new_mid.len() as isize);
+            for (d, v) in trace.iter().enumerate().rev() {
+                let d = d as isize;
+                if d == 0 {
+                    while x > 0 {
+                        edits.push(("equal", old_mid[x as usize - 1]));
+                        x -= 1;
+                    }
+                    break;
+                }
+
+                let at = |k: isize| v[(k + d) as usize] as isize;
+                let k = x - y;
+                let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) { k + 1 } else { k - 1 };
+                let prev_x = at(prev_k);
+                let prev_y = prev_x - prev_k;
+
+                while x > prev_x && y > prev_y {
+                    edits.push(("equal", old_mid[x as usize - 1]));
+                    x -= 1;
+                    y -= 1;
+                }
+                if x == prev_x {
+                    edits.push(("insert", new_mid[y as usize - 1]));
+                } else {
+                    edits.push(("delete", old_mid[x as usize - 1]));
+                }
+                x = prev_x;
+                y = prev_y;
+            }
+
+            for (kind, token) in edits.into_iter().rev() {
+                push_chunk(&mut chunks, kind, token);
+            }
+        }
+        None => {
+            for token in old_mid {
+                push_chunk(&mut chunks, "delete", token);
+            }
+            for token in new_mid {
+                push_chunk(&mut chunks, "insert", token);
+            }
+        }
+    }
+
+    for token in &old[old.len() - suffix..] {
+        push_chunk(&mut chunks, "equal", token);
+    }
+
+    chunks
+}
+
+#[tauri::command]
+pub async fn get_note_revisions(note_id: String, db: State<'_, Database>) -> Result<Vec<NoteRevision>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, note_id, title, content, created_at
+         FROM note_revisions
+         WHERE note_id = ?
+         ORDER BY created_at DESC"
+    ).map_err(|e| e.to_string())?;
+
+    let revision_iter = stmt.query_map(params![note_id], |row| {
+        Ok(NoteRevision {
+            id: row.get(0)?,
+            note_id: row.get(1)?,
+            title: row.get(2)?,
+            content: row.get(3)?,
+            created_at: row.get::<_, String>(4)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(4, "created_at".to_string(), rusqlite::types::Type::Text))?,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    let mut revisions = Vec::new();
+    for revision in revision_iter {
+        revisions.push(revision.map_err(|e| e.to_string())?);
+    }
+
+    Ok(revisions)
+}
+
+#[tauri::command]
+pub async fn get_note_revision(id: String, db: State<'_, Database>) -> Result<NoteRevision, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    load_revision(&conn, &id).map_err(|e| e.to_string())
+}
+
+/// 比较两条修订的内容；`to_id` 为空时与笔记的当前内容比较。
+/// `granularity` 可选 "line"（默认）或 "word"。
+#[tauri::command]
+pub async fn diff_note_revisions(
+    from_id: String,
+    to_id: Option<String>,
+    granularity: Option<String>,
+    db: State<'_, Database>
+) -> Result<Vec<DiffChunk>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let from = load_revision(&conn, &from_id).map_err(|e| e.to_string())?;
+
+    let to_content: String = match to_id {
+        Some(to_id) => load_revision(&conn, &to_id).map_err(|e| e.to_string())?.content,
+        None => conn.query_row(
+            "SELECT content FROM notes WHERE id = ?",
+            params![from.note_id],
+            |row| row.get(0)
+        ).map_err(|e| e.to_string())?,
+    };
+
+    let chunks = match granularity.as_deref().unwrap_or("line") {
+        "line" => diff_tokens(&split_lines(&from.content), &split_lines(&to_content)),
+        "word" => diff_tokens(&split_words(&from.content), &split_words(&to_content)),
+        other => return Err(format!("Invalid granularity: {}", other)),
+    };
+
+    Ok(chunks)
+}
+
+#[tauri::command]
+pub async fn restore_note_revision(id: String, db: State<'_, Database>) -> Result<Note, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    let revision = load_revision(&tx, &id).map_err(|e| e.to_string())?;
+
+    let deleted_at: Option<String> = tx.query_row(
+        "SELECT deleted_at FROM notes WHERE id = ?",
+        params![revision.note_id],
+        |row| row.get(0)
+    ).optional().map_err(|e| e.to_string())?.ok_or("Note not found")?;
+    if deleted_at.is_some() {
+        return Err("Note is in trash".to_string());
+    }
+
+    // 恢复前先保存当前内容，恢复操作本身也可以被撤回
+    save_note_revision(&tx, &revision.note_id, true).map_err(|e| e.to_string())?;
+
+    let mut change = ChangeSet::new("restore_note_revision");
+    change.track(&tx, "notes", id_key(&revision.note_id)).map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "UPDATE notes SET title = ?, content = ?, updated_at = ? WHERE id = ?",
+        params![revision.title, revision.content, now.to_rfc3339(), revision.note_id]
+    ).map_err(|e| e.to_string())?;
+
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    let summary = format!(
+        "Note \"{}\" restored to revision from {}",
+        entity_title(&tx, "note", &revision.note_id).map_err(|e| e.to_string())?,
+        revision.created_at.to_rfc3339()
+    );
+    log_activity(&tx, "note", &revision.note_id, "restored", &summary).map_err(|e| e.to_string())?;
+
+    let note = tx.query_row(
+        "SELECT id, title, content, created_at, updated_at, project_id
+         FROM notes
+         WHERE id = ?",
+        params![revision.note_id],
+        |row| {
+            Ok(Note {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                content: row.get(2)?,
+                created_at: row.get::<_, String>(3)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(3, "created_at".to_string(), rusqlite::types::Type::Text))?,
+                updated_at: row.get::<_, String>(4)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(4, "updated_at".to_string(), rusqlite::types::Type::Text))?,
+                project_id: row.get(5)?,
+            })
+        }
+    ).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(note)
+}
+
+#[tauri::command]
+pub async fn prune_note_revisions(
+    note_id: Option<String>,
+    max_age_days: Option<i64>,
+    max_count: Option<i64>,
+    db: State<'_, Database>
+) -> Result<usize, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    prune_revisions(&conn, note_id.as_deref(), max_age_days, max_count)
+        .map_err(|e| e.to_string())
+}
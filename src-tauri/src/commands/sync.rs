@@ -0,0 +1,568 @@
+use crate::models::{FolderSyncReport, FolderSyncStatus, Note};
+use crate::db::{self, Database, DbPool};
+use crate::commands::projects::find_or_create_project;
+use crate::commands::tags::link_tag_to_note;
+use crate::commands::trash::soft_delete_note;
+use crate::commands::vault::{
+    collect_markdown_files, import_parsed_note, load_vault_note, load_vault_notes, parse_markdown_file,
+    path_case_key, render_markdown_file, sanitize_file_name, short_id, ImportOutcome,
+};
+use tauri::State;
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{Local, Utc};
+use notify::{RecursiveMode, Watcher};
+use uuid::Uuid;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration as StdDuration;
+
+// 同步目录的设置键
+pub const FOLDER_SYNC_SETTING: &str = "folder_sync_directory";
+
+// 文件事件的防抖时间，以及检查应用内修改的间隔
+const DEBOUNCE_MILLIS: u64 = 500;
+const POLL_SECS: u64 = 2;
+
+enum SyncSignal {
+    FileChanged,
+    Stop,
+}
+
+struct SyncWorker {
+    directory: PathBuf,
+    sender: Sender<SyncSignal>,
+    // 监听器被释放时停止监听
+    _watcher: notify::RecommendedWatcher,
+}
+
+/// 目录同步服务，作为 Tauri 状态管理
+#[derive(Default)]
+pub struct FolderSync {
+    worker: Mutex<Option<SyncWorker>>,
+    last_report: Arc<Mutex<Option<FolderSyncReport>>>,
+}
+
+impl FolderSync {
+    /// 开始监听目录，已在同步其他目录时先停止
+    pub fn start(&self, pool: DbPool, directory: PathBuf) -> Result<(), String> {
+        self.stop();
+
+        let (sender, receiver) = mpsc::channel();
+
+        let event_sender = sender.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if event.is_ok() {
+                let _ = event_sender.send(SyncSignal::FileChanged);
+            }
+        }).map_err(|e| e.to_string())?;
+        watcher.watch(&directory, RecursiveMode::Recursive).map_err(|e| e.to_string())?;
+
+        let root = directory.clone();
+        let last_report = self.last_report.clone();
+
+        thread::spawn(move || {
+            let mut fingerprint = String::new();
+
+            loop {
+                match receiver.recv_timeout(StdDuration::from_secs(POLL_SECS)) {
+                    Ok(SyncSignal::Stop) | Err(RecvTimeoutError::Disconnected) => return,
+                    Ok(SyncSignal::FileChanged) => {
+                        // 合并短时间内的连续事件，例如编辑器保存时的多次写入
+                        loop {
+                            match receiver.recv_timeout(StdDuration::from_millis(DEBOUNCE_MILLIS)) {
+                                Ok(SyncSignal::FileChanged) => continue,
+                                Ok(SyncSignal::Stop) | Err(RecvTimeoutError::Disconnected) => return,
+                                Err(RecvTimeoutError::Timeout) => break,
+                            }
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {
+                        let current = pool.get().ok()
+                            .and_then(|conn| database_fingerprint(&conn).ok())
+                            .unwrap_or_default();
+                        if current == fingerprint {
+                            continue;
+                        }
+                    }
+                }
+
+                let result = pool.get()
+                    .map_err(|e| e.to_string())
+                    .and_then(|mut conn| sync_folder(&mut conn, &root));
+
+                match result {
+                    Ok(report) => {
+                        if let Ok(mut last) = last_report.lock() {
+                            *last = Some(report);
+                        }
+                    }
+                    Err(e) => log::warn!("Folder sync failed: {}", e),
+                }
+
+                // 同步写回的文件会再次触发事件，但内容哈希一致，不会产生新的变更
+                fingerprint = pool.get().ok()
+                    .and_then(|conn| database_fingerprint(&conn).ok())
+                    .unwrap_or_default();
+            }
+        });
+
+        *self.worker.lock().map_err(|e| e.to_string())? = Some(SyncWorker {
+            directory,
+            sender,
+            _watcher: watcher,
+        });
+
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if let Ok(mut worker) = self.worker.lock() {
+            if let Some(worker) = worker.take() {
+                let _ = worker.sender.send(SyncSignal::Stop);
+            }
+        }
+    }
+
+    pub fn directory(&self) -> Option<PathBuf> {
+        self.worker.lock().ok()?.as_ref().map(|worker| worker.directory.clone())
+    }
+}
+
+/// 笔记数据的摘要，变化时说明应用内有修改需要写回磁盘
+fn database_fingerprint(conn: &Connection) -> Result<String, rusqlite::Error> {
+    conn.query_row(
+        "SELECT COUNT(*) || '|' || COALESCE(MAX(updated_at), '') || '|' || COALESCE(MAX(deleted_at), '')
+                || '|' || (SELECT COUNT(*) FROM note_tags)
+                || '|' || (SELECT COALESCE(group_concat(name), '') FROM tags)
+                || '|' || (SELECT COALESCE(group_concat(name), '') FROM projects)
+         FROM notes",
+        [],
+        |row| row.get(0)
+    )
+}
+
+/// FNV-1a 哈希，结果在不同版本间保持稳定
+pub fn content_hash(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+fn path_key(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// 笔记在同步目录中的理想路径
+fn desired_path(root: &Path, project: Option<&str>, note: &Note) -> PathBuf {
+    let dir = match project {
+        Some(project) => root.join(sanitize_file_name(project)),
+        None => root.to_path_buf(),
+    };
+    dir.join(format!("{}.md", sanitize_file_name(&note.title)))
+}
+
+/// 为笔记选择一个未被占用（不区分大小写）的路径，优先保留当前路径
+fn choose_path(root: &Path, project: Option<&str>, note: &Note, current: Option<&Path>, used: &HashSet<String>) -> PathBuf {
+    let desired = desired_path(root, project, note);
+    if Some(desired.as_path()) == current || !used.contains(&path_case_key(&desired)) {
+        return desired;
+    }
+    if let Some(current) = current {
+        return current.to_path_buf();
+    }
+    let stem = desired.file_stem().and_then(|stem| stem.to_str()).unwrap_or("untitled").to_string();
+    desired.with_file_name(format!("{} ({}).md", stem, short_id(&note.id)))
+}
+
+/// 同步过程中待执行的文件操作，在数据库事务提交后按顺序执行
+enum FileOp {
+    Write { note_id: String, path: PathBuf, content: String },
+    Remove(PathBuf),
+}
+
+/// 记录同步状态，并安排在事务提交后写入文件
+fn write_note_file(conn: &Connection, note_id: &str, path: &Path, rendered: &str, ops: &mut Vec<FileOp>) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO sync_files (note_id, path, content_hash, synced_at) VALUES (?, ?, ?, ?)
+         ON CONFLICT(note_id) DO UPDATE SET path = excluded.path, content_hash = excluded.content_hash, synced_at = excluded.synced_at",
+        params![note_id, path_key(path), content_hash(rendered), Utc::now().to_rfc3339()]
+    ).map_err(|e| e.to_string())?;
+
+    ops.push(FileOp::Write { note_id: note_id.to_string(), path: path.to_path_buf(), content: rendered.to_string() });
+    Ok(())
+}
+
+/// 执行文件操作。写入失败时删除该笔记的同步记录，下次同步时重新比较，避免把旧文件当作磁盘上的修改
+fn apply_file_ops(conn: &Connection, ops: Vec<FileOp>) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for op in ops {
+        match op {
+            FileOp::Write { note_id, path, content } => {
+                let result = match path.parent() {
+                    Some(parent) => fs::create_dir_all(parent),
+                    None => Ok(()),
+                }.and_then(|_| fs::write(&path, content));
+                if let Err(e) = result {
+                    conn.execute("DELETE FROM sync_files WHERE note_id = ?", params![note_id]).map_err(|e| e.to_string())?;
+                    errors.push(format!("{}: {}", path_key(&path), e));
+                }
+            }
+            FileOp::Remove(path) => {
+                if let Err(e) = fs::remove_file(&path) {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        errors.push(format!("{}: {}", path_key(&path), e));
+                    }
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Failed to update synced files: {}", errors.join("; ")))
+    }
+}
+
+/// 导入文件后按数据库中的内容重写文件，补全 front matter
+fn import_and_normalize(
+    conn: &Connection,
+    root: &Path,
+    path: &Path,
+    text: &str,
+    keep_id: bool,
+    used: &mut HashSet<String>,
+    ops: &mut Vec<FileOp>,
+) -> Result<String, String> {
+    let (mut front_matter, body) = parse_markdown_file(text);
+    if !keep_id {
+        front_matter.id = None;
+    }
+
+    let id = match import_parsed_note(conn, root, path, front_matter, body, true)? {
+        ImportOutcome::Created(id) | ImportOutcome::Updated(id) | ImportOutcome::Unchanged(id) => id,
+        ImportOutcome::Conflict(_, reason) => return Err(reason),
+    };
+
+    if let Some((note, project, tags)) = load_vault_note(conn, &id).map_err(|e| e.to_string())? {
+        used.insert(path_case_key(path));
+        write_note_file(conn, &id, path, &render_markdown_file(&note, project.as_deref(), &tags), ops)?;
+    }
+
+    Ok(id)
+}
+
+/// 双方都修改时，将应用内的版本保存为一篇新的冲突副本笔记
+fn create_conflict_copy(conn: &Connection, note: &Note, tags: &[String]) -> Result<Note, String> {
+    let now = Utc::now();
+    let copy = Note {
+        id: Uuid::new_v4().to_string(),
+        title: format!("{} (conflict {})", note.title, Local::now().format("%Y-%m-%d %H%M%S")),
+        content: note.content.clone(),
+        created_at: now,
+        updated_at: now,
+        project_id: note.project_id.clone(),
+    };
+
+    conn.execute(
+        "INSERT INTO notes (id, title, content, created_at, updated_at, project_id) VALUES (?, ?, ?, ?, ?, ?)",
+        params![copy.id, copy.title, copy.content, now.to_rfc3339(), now.to_rfc3339(), copy.project_id]
+    ).map_err(|e| e.to_string())?;
+
+    for tag in tags {
+        let tag_id: Option<String> = conn.query_row("SELECT id FROM tags WHERE name = ?", params![tag], |row| row.get(0))
+            .optional().map_err(|e| e.to_string())?;
+        if let Some(tag_id) = tag_id {
+            link_tag_to_note(conn, &copy.id, &tag_id).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(copy)
+}
+
+/// 对比目录与数据库并双向同步。
+///
+/// `sync_files` 记录每篇笔记上次同步时的文件路径和内容哈希，
+/// 据此判断修改发生在哪一侧、文件是否被移动或删除。
+pub fn sync_folder(conn: &mut Connection, root: &Path) -> Result<FolderSyncReport, String> {
+    let mut report = FolderSyncReport::default();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    // 扫描目录中的文件，按 front matter 中的 id 归类
+    let mut files = Vec::new();
+    collect_markdown_files(root, &mut files)?;
+
+    let mut used: HashSet<String> = files.iter().map(|path| path_case_key(path)).collect();
+    let mut by_id: HashMap<String, (PathBuf, String)> = HashMap::new();
+    let mut untracked: Vec<(PathBuf, String)> = Vec::new();
+    // 读取失败的文件（例如正被其他程序占用），本次同步跳过对应的笔记，不能当作已删除
+    let mut unreadable: HashSet<String> = HashSet::new();
+    let mut ops: Vec<FileOp> = Vec::new();
+
+    for path in files {
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => {
+                unreadable.insert(path_key(&path));
+                continue;
+            }
+        };
+        match parse_markdown_file(&text).0.id {
+            // 复制出来的文件会带有相同的 id，只保留第一个，其余作为新笔记
+            Some(id) if !by_id.contains_key(&id) => {
+                by_id.insert(id, (path, text));
+            }
+            _ => untracked.push((path, text)),
+        }
+    }
+
+    let mut records: HashMap<String, (String, String)> = HashMap::new();
+    {
+        let mut stmt = tx.prepare("SELECT note_id, path, content_hash FROM sync_files").map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let (note_id, path, hash) = row.map_err(|e| e.to_string())?;
+            records.insert(note_id, (path, hash));
+        }
+    }
+
+    let notes = load_vault_notes(&tx).map_err(|e| e.to_string())?;
+    let now = Utc::now().to_rfc3339();
+
+    for (mut note, mut project, tags) in notes {
+        let record = records.remove(&note.id);
+        if record.as_ref().is_some_and(|(path, _)| unreadable.contains(path)) {
+            by_id.remove(&note.id);
+            continue;
+        }
+        let file = by_id.remove(&note.id);
+        let rendered = render_markdown_file(&note, project.as_deref(), &tags);
+        let note_hash = content_hash(&rendered);
+
+        match (file, record) {
+            // 应用内新建的笔记
+            (None, None) => {
+                let path = choose_path(root, project.as_deref(), &note, None, &used);
+                used.insert(path_case_key(&path));
+                write_note_file(&tx, &note.id, &path, &rendered, &mut ops)?;
+                report.written += 1;
+            }
+            // 文件在磁盘上被删除
+            (None, Some((path, hash))) => {
+                if hash == note_hash {
+                    soft_delete_note(&tx, &note.id, &now).map_err(|e| e.to_string())?;
+                    tx.execute("DELETE FROM sync_files WHERE note_id = ?", params![note.id]).map_err(|e| e.to_string())?;
+                    report.trashed_notes += 1;
+                } else {
+                    // 应用内也有修改，以应用内的版本为准重新写出
+                    let path = choose_path(root, project.as_deref(), &note, Some(Path::new(&path)), &used);
+                    used.insert(path_case_key(&path));
+                    write_note_file(&tx, &note.id, &path, &rendered, &mut ops)?;
+                    report.written += 1;
+                }
+            }
+            (Some((path, text)), record) => {
+                let file_hash = content_hash(&text);
+                let (file_changed, mut note_changed, moved_from) = match &record {
+                    Some((recorded_path, hash)) => (
+                        *hash != file_hash,
+                        *hash != note_hash,
+                        Some(PathBuf::from(recorded_path)).filter(|recorded| *recorded != path),
+                    ),
+                    None => {
+                        // 没有同步记录（例如先导出再开启同步），按修改时间判断
+                        let differs = text != rendered;
+                        let file_updated_at = parse_markdown_file(&text).0.updated_at;
+                        (differs, differs && file_updated_at.is_some_and(|at| note.updated_at > at), None)
+                    }
+                };
+
+                if let Some(old_path) = &moved_from {
+                    report.renamed += 1;
+
+                    // 文件被移动到其他子目录时，按目录调整笔记所属项目
+                    if old_path.parent() != path.parent() && !file_changed {
+                        let folder = path.parent()
+                            .filter(|parent| *parent != root)
+                            .and_then(|parent| parent.file_name())
+                            .and_then(|name| name.to_str())
+                            .map(|name| name.to_string());
+                        let project_id = match &folder {
+                            Some(name) => Some(find_or_create_project(&tx, name).map_err(|e| e.to_string())?),
+                            None => None,
+                        };
+                        tx.execute(
+                            "UPDATE notes SET project_id = ?, updated_at = ? WHERE id = ?",
+                            params![project_id, now, note.id]
+                        ).map_err(|e| e.to_string())?;
+                        note.project_id = project_id;
+                        project = folder;
+                        note_changed = true;
+                    }
+                }
+
+                match (file_changed, note_changed) {
+                    (false, false) => {
+                        if moved_from.is_some() {
+                            tx.execute(
+                                "UPDATE sync_files SET path = ? WHERE note_id = ?",
+                                params![path_key(&path), note.id]
+                            ).map_err(|e| e.to_string())?;
+                        }
+                    }
+                    (true, false) => {
+                        import_and_normalize(&tx, root, &path, &text, true, &mut used, &mut ops)?;
+                        report.imported += 1;
+                    }
+                    (false, true) => {
+                        let (note, project, tags) = load_vault_note(&tx, &note.id).map_err(|e| e.to_string())?
+                            .unwrap_or((note, project, tags));
+                        let target = choose_path(root, project.as_deref(), &note, Some(&path), &used);
+                        if target != path {
+                            ops.push(FileOp::Remove(path.clone()));
+                            used.remove(&path_case_key(&path));
+                            used.insert(path_case_key(&target));
+                        }
+                        write_note_file(&tx, &note.id, &target, &render_markdown_file(&note, project.as_deref(), &tags), &mut ops)?;
+                        report.written += 1;
+                    }
+                    (true, true) => {
+                        let copy = create_conflict_copy(&tx, &note, &tags)?;
+                        let copy_path = choose_path(root, project.as_deref(), &copy, None, &used);
+                        used.insert(path_case_key(&copy_path));
+                        write_note_file(&tx, &copy.id, &copy_path, &render_markdown_file(&copy, project.as_deref(), &tags), &mut ops)?;
+
+                        import_and_normalize(&tx, root, &path, &text, true, &mut used, &mut ops)?;
+                        report.imported += 1;
+                        report.conflicts.push(path_key(&copy_path));
+                    }
+                }
+            }
+        }
+    }
+
+    // 剩余带 id 的文件：笔记已在应用内删除，或是来自其他设备的新笔记
+    for (id, (path, text)) in by_id {
+        let trashed: bool = tx.query_row(
+            "SELECT COUNT(*) > 0 FROM notes WHERE id = ? AND deleted_at IS NOT NULL",
+            params![id],
+            |row| row.get(0)
+        ).map_err(|e| e.to_string())?;
+        let record = records.remove(&id);
+
+        if trashed {
+            if record.is_some_and(|(_, hash)| hash == content_hash(&text)) {
+                ops.push(FileOp::Remove(path));
+                tx.execute("DELETE FROM sync_files WHERE note_id = ?", params![id]).map_err(|e| e.to_string())?;
+                report.deleted_files += 1;
+                continue;
+            }
+            // 笔记删除后文件又被修改，恢复笔记并导入文件内容
+            tx.execute("UPDATE notes SET deleted_at = NULL WHERE id = ?", params![id]).map_err(|e| e.to_string())?;
+        }
+
+        import_and_normalize(&tx, root, &path, &text, true, &mut used, &mut ops)?;
+        report.imported += 1;
+    }
+
+    // 没有 id 的文件作为新笔记导入
+    for (path, text) in untracked {
+        import_and_normalize(&tx, root, &path, &text, false, &mut used, &mut ops)?;
+        report.imported += 1;
+    }
+
+    // 清理笔记和文件都已不存在的记录
+    for (note_id, (path, _)) in &records {
+        if unreadable.contains(path) {
+            continue;
+        }
+        tx.execute("DELETE FROM sync_files WHERE note_id = ?", params![note_id]).map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    apply_file_ops(conn, ops)?;
+
+    report.synced_at = Some(Utc::now());
+    Ok(report)
+}
+
+/// 启动时恢复上次开启的目录同步
+pub fn resume_folder_sync(database: &Database, folder_sync: &FolderSync) -> Result<(), String> {
+    let conn = database.get_connection().map_err(|e| e.to_string())?;
+    if let Some(directory) = db::get_setting(&conn, FOLDER_SYNC_SETTING).map_err(|e| e.to_string())? {
+        folder_sync.start(database.pool.clone(), PathBuf::from(directory))?;
+    }
+    Ok(())
+}
+
+/// 开启与指定目录的双向同步，立即执行一次完整同步
+#[tauri::command]
+pub async fn enable_folder_sync(
+    directory: String,
+    db: State<'_, Database>,
+    folder_sync: State<'_, FolderSync>
+) -> Result<FolderSyncReport, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let root = PathBuf::from(&directory);
+    fs::create_dir_all(&root).map_err(|e| e.to_string())?;
+
+    // 切换到新目录时，旧目录的同步记录不再适用
+    let previous = db::get_setting(&conn, FOLDER_SYNC_SETTING).map_err(|e| e.to_string())?;
+    if previous.as_deref() != Some(directory.as_str()) {
+        conn.execute("DELETE FROM sync_files", []).map_err(|e| e.to_string())?;
+    }
+    db::set_setting(&conn, FOLDER_SYNC_SETTING, &directory).map_err(|e| e.to_string())?;
+
+    let report = sync_folder(&mut conn, &root)?;
+    folder_sync.start(db.pool.clone(), root)?;
+
+    if let Ok(mut last) = folder_sync.last_report.lock() {
+        *last = Some(report.clone());
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn disable_folder_sync(db: State<'_, Database>, folder_sync: State<'_, FolderSync>) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    folder_sync.stop();
+    conn.execute("DELETE FROM settings WHERE key = ?", params![FOLDER_SYNC_SETTING]).map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM sync_files", []).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn sync_folder_now(db: State<'_, Database>, folder_sync: State<'_, FolderSync>) -> Result<FolderSyncReport, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let root = folder_sync.directory().ok_or("Folder sync is not enabled")?;
+    let report = sync_folder(&mut conn, &root)?;
+
+    if let Ok(mut last) = folder_sync.last_report.lock() {
+        *last = Some(report.clone());
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn get_folder_sync_status(folder_sync: State<'_, FolderSync>) -> Result<FolderSyncStatus, String> {
+    let last_report = folder_sync.last_report.lock().map_err(|e| e.to_string())?.clone();
+
+    Ok(FolderSyncStatus {
+        directory: folder_sync.directory().map(|dir| path_key(&dir)),
+        last_synced_at: last_report.as_ref().and_then(|report| report.synced_at),
+        last_report,
+    })
+}
@@ -1,10 +1,41 @@
 use crate::models::Tag;
 use crate::db::Database;
+use crate::commands::journal::{id_key, note_tag_key, task_tag_key, ChangeSet};
+use crate::commands::activity::{entity_title, log_activity};
 use tauri::State;
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+/// 为笔记添加标签关联，关联已存在时忽略
+pub fn link_tag_to_note(conn: &Connection, note_id: &str, tag_id: &str) -> Result<usize, rusqlite::Error> {
+    conn.execute(
+        "INSERT OR IGNORE INTO note_tags (note_id, tag_id) VALUES (?, ?)",
+        params![note_id, tag_id]
+    )
+}
+
+/// 按名称查找标签，不存在时使用默认颜色创建，返回标签 id
+pub fn find_or_create_tag(conn: &Connection, name: &str) -> Result<String, rusqlite::Error> {
+    let existing: Option<String> = conn.query_row(
+        "SELECT id FROM tags WHERE name = ?",
+        params![name],
+        |row| row.get(0)
+    ).optional()?;
+
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO tags (id, name, color) VALUES (?, ?, ?)",
+        params![id, name, "#6B7280"]
+    )?;
+
+    Ok(id)
+}
+
 #[tauri::command]
 pub async fn get_all_tags(db: State<'_, Database>) -> Result<Vec<Tag>, String> {
     let conn = db.get_connection().map_err(|e| e.to_string())?;
@@ -38,17 +69,25 @@ pub async fn create_tag(
     color: Option<String>,
     db: State<'_, Database>
 ) -> Result<Tag, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
     
     let id = Uuid::new_v4().to_string();
     let now = Utc::now();
     let color = color.unwrap_or_else(|| "#6B7280".to_string());
     
-    conn.execute(
+    let mut change = ChangeSet::new("create_tag");
+    change.track(&tx, "tags", id_key(&id)).map_err(|e| e.to_string())?;
+    
+    tx.execute(
         "INSERT INTO tags (id, name, color, created_at) VALUES (?, ?, ?, ?)",
         params![id, name, color, now.to_rfc3339()]
     ).map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    log_activity(&tx, "tag", &id, "created", &format!("Tag \"{}\" created", name)).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(Tag {
         id,
         name,
@@ -64,7 +103,15 @@ pub async fn update_tag(
     color: Option<String>,
     db: State<'_, Database>
 ) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    
+    let old_name = entity_title(&tx, "tag", &id).map_err(|e| e.to_string())?;
+    let (action, summary) = match &name {
+        Some(new_name) if *new_name != old_name => ("renamed", format!("Tag \"{}\" renamed to \"{}\"", old_name, new_name)),
+        _ => ("updated", format!("Tag \"{}\" updated", old_name)),
+    };
+    let tag_id = id.clone();
     
     let mut query_parts = Vec::new();
     let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
@@ -83,6 +130,9 @@ pub async fn update_tag(
         return Ok(());
     }
     
+    let mut change = ChangeSet::new("update_tag");
+    change.track(&tx, "tags", id_key(&id)).map_err(|e| e.to_string())?;
+    
     params_vec.push(Box::new(id));
     
     let query = format!(
@@ -90,48 +140,113 @@ pub async fn update_tag(
         query_parts.join(", ")
     );
     
-    conn.execute(&query, rusqlite::params_from_iter(params_vec.iter().map(|p| p.as_ref())))
+    tx.execute(&query, rusqlite::params_from_iter(params_vec.iter().map(|p| p.as_ref())))
         .map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    log_activity(&tx, "tag", &tag_id, action, &summary).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(())
 }
 
 #[tauri::command]
 pub async fn delete_tag(id: String, db: State<'_, Database>) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    
+    let mut change = ChangeSet::new("delete_tag");
+    let note_ids: Vec<String> = {
+        let mut stmt = tx.prepare("SELECT note_id FROM note_tags WHERE tag_id = ?")
+            .map_err(|e| e.to_string())?;
+        let ids = stmt.query_map(params![id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| e.to_string())?;
+        ids
+    };
+    for note_id in &note_ids {
+        change.track(&tx, "note_tags", note_tag_key(note_id, &id)).map_err(|e| e.to_string())?;
+    }
+    let task_ids: Vec<String> = {
+        let mut stmt = tx.prepare("SELECT task_id FROM task_tags WHERE tag_id = ?")
+            .map_err(|e| e.to_string())?;
+        let ids = stmt.query_map(params![id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| e.to_string())?;
+        ids
+    };
+    for task_id in &task_ids {
+        change.track(&tx, "task_tags", task_tag_key(task_id, &id)).map_err(|e| e.to_string())?;
+    }
+    change.track(&tx, "tags", id_key(&id)).map_err(|e| e.to_string())?;
+    
+    let tag_name = entity_title(&tx, "tag", &id).map_err(|e| e.to_string())?;
     
     // 删除标签与笔记的关联
-    conn.execute("DELETE FROM note_tags WHERE tag_id = ?", params![id])
+    tx.execute("DELETE FROM note_tags WHERE tag_id = ?", params![id])
+        .map_err(|e| e.to_string())?;
+    
+    // 删除标签与任务的关联
+    tx.execute("DELETE FROM task_tags WHERE tag_id = ?", params![id])
         .map_err(|e| e.to_string())?;
     
     // 删除标签
-    conn.execute("DELETE FROM tags WHERE id = ?", params![id])
+    tx.execute("DELETE FROM tags WHERE id = ?", params![id])
         .map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    log_activity(&tx, "tag", &id, "deleted", &format!("Tag \"{}\" deleted", tag_name)).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(())
 }
 
 #[tauri::command]
 pub async fn add_tag_to_note(note_id: String, tag_id: String, db: State<'_, Database>) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
     
-    conn.execute(
-        "INSERT OR IGNORE INTO note_tags (note_id, tag_id) VALUES (?, ?)",
-        params![note_id, tag_id]
-    ).map_err(|e| e.to_string())?;
+    let mut change = ChangeSet::new("add_tag_to_note");
+    change.track(&tx, "note_tags", note_tag_key(&note_id, &tag_id)).map_err(|e| e.to_string())?;
+    
+    link_tag_to_note(&tx, &note_id, &tag_id).map_err(|e| e.to_string())?;
+    
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    let summary = format!(
+        "Tag \"{}\" added to note \"{}\"",
+        entity_title(&tx, "tag", &tag_id).map_err(|e| e.to_string())?,
+        entity_title(&tx, "note", &note_id).map_err(|e| e.to_string())?
+    );
+    log_activity(&tx, "note", &note_id, "tagged", &summary).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
     
     Ok(())
 }
 
 #[tauri::command]
 pub async fn remove_tag_from_note(note_id: String, tag_id: String, db: State<'_, Database>) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
     
-    conn.execute(
+    let mut change = ChangeSet::new("remove_tag_from_note");
+    change.track(&tx, "note_tags", note_tag_key(&note_id, &tag_id)).map_err(|e| e.to_string())?;
+    
+    tx.execute(
         "DELETE FROM note_tags WHERE note_id = ? AND tag_id = ?",
         params![note_id, tag_id]
     ).map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    let summary = format!(
+        "Tag \"{}\" removed from note \"{}\"",
+        entity_title(&tx, "tag", &tag_id).map_err(|e| e.to_string())?,
+        entity_title(&tx, "note", &note_id).map_err(|e| e.to_string())?
+    );
+    log_activity(&tx, "note", &note_id, "untagged", &summary).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(())
 }
 
@@ -169,7 +284,9 @@ pub async fn get_notes_by_tag(tag_id: String, db: State<'_, Database>) -> Result
     let conn = db.get_connection().map_err(|e| e.to_string())?;
     
     let mut stmt = conn.prepare(
-        "SELECT note_id FROM note_tags WHERE tag_id = ?"
+        "SELECT nt.note_id FROM note_tags nt
+         INNER JOIN notes n ON n.id = nt.note_id
+         WHERE nt.tag_id = ? AND n.deleted_at IS NULL"
     ).map_err(|e| e.to_string())?;
     
     let note_id_iter = stmt.query_map(params![tag_id], |row| {
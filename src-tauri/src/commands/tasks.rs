@@ -1,7 +1,11 @@
 use crate::models::Task;
 use crate::db::Database;
+use crate::commands::trash::{soft_delete_task, task_subtree_ids};
+use crate::commands::journal::{id_key, ChangeSet};
+use crate::commands::activity::{entity_title, log_activity};
+use crate::commands::milestones::check_task_milestone;
 use tauri::State;
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
@@ -11,8 +15,10 @@ pub async fn get_all_tasks(db: State<'_, Database>) -> Result<Vec<Task>, String>
     
     let mut stmt = conn.prepare(
         "SELECT id, title, description, is_completed, priority, due_date, remind_at, 
-                created_at, updated_at, project_id, parent_id, position
+                created_at, updated_at, project_id, parent_id, position, milestone_id,
+                start_date, estimate_minutes, completed_at, recurrence
          FROM tasks 
+         WHERE deleted_at IS NULL
          ORDER BY position ASC, created_at DESC"
     ).map_err(|e| e.to_string())?;
     
@@ -30,6 +36,11 @@ pub async fn get_all_tasks(db: State<'_, Database>) -> Result<Vec<Task>, String>
             project_id: row.get(9)?,
             parent_id: row.get(10)?,
             position: row.get(11)?,
+            milestone_id: row.get(12)?,
+            start_date: row.get::<_, Option<String>>(13)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(13, "start_date".to_string(), rusqlite::types::Type::Text))?,
+            estimate_minutes: row.get(14)?,
+            completed_at: row.get::<_, Option<String>>(15)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(15, "completed_at".to_string(), rusqlite::types::Type::Text))?,
+            recurrence: row.get(16)?,
         })
     }).map_err(|e| e.to_string())?;
     
@@ -49,16 +60,24 @@ pub async fn create_task(
     due_date: Option<String>,
     project_id: Option<String>,
     parent_id: Option<String>,
+    milestone_id: Option<String>,
+    start_date: Option<String>,
+    estimate_minutes: Option<i32>,
     db: State<'_, Database>
 ) -> Result<Task, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    
+    if let Some(milestone_id) = &milestone_id {
+        check_task_milestone(&tx, project_id.as_deref(), milestone_id)?;
+    }
     
     let id = Uuid::new_v4().to_string();
     let now = Utc::now();
     let priority = priority.unwrap_or(3);
     
     // 获取下一个位置
-    let position: i32 = conn.query_row(
+    let position: i32 = tx.query_row(
         "SELECT COALESCE(MAX(position), 0) + 1 FROM tasks WHERE parent_id IS ?",
         params![parent_id],
         |row| row.get(0)
@@ -70,18 +89,34 @@ pub async fn create_task(
         None
     };
     
-    conn.execute(
+    let start_date_parsed: Option<DateTime<Utc>> = if let Some(date_str) = &start_date {
+        Some(date_str.parse().map_err(|_| "Invalid start_date format")?)
+    } else {
+        None
+    };
+    let estimate_minutes = estimate_minutes.filter(|m| *m > 0);
+    
+    let mut change = ChangeSet::new("create_task");
+    change.track(&tx, "tasks", id_key(&id)).map_err(|e| e.to_string())?;
+    
+    tx.execute(
         "INSERT INTO tasks (id, title, description, is_completed, priority, due_date, remind_at, 
-                           created_at, updated_at, project_id, parent_id, position) 
-         VALUES (?, ?, ?, 0, ?, ?, NULL, ?, ?, ?, ?, ?)",
+                           created_at, updated_at, project_id, parent_id, position, milestone_id,
+                           start_date, estimate_minutes) 
+         VALUES (?, ?, ?, 0, ?, ?, NULL, ?, ?, ?, ?, ?, ?, ?, ?)",
         params![
             id, title, description, priority, 
             due_date_parsed.map(|d| d.to_rfc3339()),
             now.to_rfc3339(), now.to_rfc3339(), 
-            project_id, parent_id, position
+            project_id, parent_id, position, milestone_id,
+            start_date_parsed.map(|d| d.to_rfc3339()), estimate_minutes
         ]
     ).map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    log_activity(&tx, "task", &id, "created", &format!("Task \"{}\" created", title)).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(Task {
         id,
         title,
@@ -95,6 +130,11 @@ pub async fn create_task(
         project_id,
         parent_id,
         position,
+        milestone_id,
+        start_date: start_date_parsed,
+        estimate_minutes,
+        completed_at: None,
+        recurrence: None,
     })
 }
 
@@ -107,11 +147,35 @@ pub async fn update_task(
     priority: Option<i32>,
     due_date: Option<String>,
     project_id: Option<String>,
+    remind_at: Option<String>,
+    start_date: Option<String>,
+    estimate_minutes: Option<i32>,
     db: State<'_, Database>
 ) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
     let now = Utc::now();
     
+    let mut change = ChangeSet::new("update_task");
+    change.track(&tx, "tasks", id_key(&id)).map_err(|e| e.to_string())?;
+    
+    // 只有完成状态或所属项目确实变化时才单独记录
+    let (was_completed, current_project): (bool, Option<String>) = tx.query_row(
+        "SELECT is_completed, project_id FROM tasks WHERE id = ?",
+        params![id],
+        |row| Ok((row.get::<_, i32>(0)? != 0, row.get(1)?))
+    ).optional().map_err(|e| e.to_string())?.unwrap_or_default();
+    let (action, verb) = match (is_completed, &project_id) {
+        (Some(true), _) if !was_completed => ("completed", "completed".to_string()),
+        (Some(false), _) if was_completed => ("reopened", "reopened".to_string()),
+        (_, Some(project_id)) if current_project.as_ref() != Some(project_id) => ("moved", format!(
+            "moved to project \"{}\"",
+            entity_title(&tx, "project", project_id).map_err(|e| e.to_string())?
+        )),
+        _ => ("updated", "updated".to_string()),
+    };
+    let task_id = id.clone();
+    
     let mut query_parts = Vec::new();
     let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
     
@@ -126,6 +190,10 @@ pub async fn update_task(
     }
     
     if let Some(is_completed) = is_completed {
+        // 已完成的任务再次标记为完成时保留原来的完成时间
+        query_parts.push("completed_at = CASE WHEN ? = 0 THEN NULL ELSE COALESCE(completed_at, ?) END");
+        params_vec.push(Box::new(if is_completed { 1 } else { 0 }));
+        params_vec.push(Box::new(now.to_rfc3339()));
         query_parts.push("is_completed = ?");
         params_vec.push(Box::new(if is_completed { 1 } else { 0 }));
     }
@@ -145,7 +213,38 @@ pub async fn update_task(
         params_vec.push(Box::new(due_date_parsed.map(|d| d.to_rfc3339())));
     }
     
+    // 空字符串表示取消提醒
+    if let Some(remind_at) = remind_at {
+        query_parts.push("remind_at = ?");
+        let remind_at_parsed: Option<DateTime<Utc>> = if remind_at.is_empty() {
+            None
+        } else {
+            Some(remind_at.parse().map_err(|_| "Invalid remind_at format")?)
+        };
+        params_vec.push(Box::new(remind_at_parsed.map(|d| d.to_rfc3339())));
+    }
+    
+    // 空字符串表示取消开始日期
+    if let Some(start_date) = start_date {
+        query_parts.push("start_date = ?");
+        let start_date_parsed: Option<DateTime<Utc>> = if start_date.is_empty() {
+            None
+        } else {
+            Some(start_date.parse().map_err(|_| "Invalid start_date format")?)
+        };
+        params_vec.push(Box::new(start_date_parsed.map(|d| d.to_rfc3339())));
+    }
+    
+    // 0 或负数表示取消预计用时
+    if let Some(estimate_minutes) = estimate_minutes {
+        query_parts.push("estimate_minutes = ?");
+        params_vec.push(Box::new(Some(estimate_minutes).filter(|m| *m > 0)));
+    }
+    
     if let Some(project_id) = project_id {
+        // 移到其他项目时原项目的里程碑不再适用
+        query_parts.push("milestone_id = CASE WHEN project_id IS ? THEN milestone_id ELSE NULL END");
+        params_vec.push(Box::new(project_id.clone()));
         query_parts.push("project_id = ?");
         params_vec.push(Box::new(project_id));
     }
@@ -159,32 +258,55 @@ pub async fn update_task(
         query_parts.join(", ")
     );
     
-    conn.execute(&query, rusqlite::params_from_iter(params_vec.iter().map(|p| p.as_ref())))
+    tx.execute(&query, rusqlite::params_from_iter(params_vec.iter().map(|p| p.as_ref())))
         .map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    let summary = format!("Task \"{}\" {}", entity_title(&tx, "task", &task_id).map_err(|e| e.to_string())?, verb);
+    log_activity(&tx, "task", &task_id, action, &summary).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(())
 }
 
 #[tauri::command]
 pub async fn delete_task(id: String, db: State<'_, Database>) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    
+    let mut change = ChangeSet::new("delete_task");
+    let ids = task_subtree_ids(&tx, &id).map_err(|e| e.to_string())?;
+    change.track_ids(&tx, "tasks", &ids).map_err(|e| e.to_string())?;
     
-    conn.execute("DELETE FROM tasks WHERE id = ?", params![id])
+    // 任务连同子任务一起移入回收站
+    soft_delete_task(&tx, &id, &now.to_rfc3339())
         .map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    log_activity(&tx, "task", &id, "deleted", &format!("Task \"{}\" moved to trash", entity_title(&tx, "task", &id).map_err(|e| e.to_string())?)).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(())
 }
 
 #[tauri::command]
 pub async fn update_task_position(id: String, new_position: i32, db: State<'_, Database>) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
     let now = Utc::now();
     
-    conn.execute(
+    let mut change = ChangeSet::new("update_task_position");
+    change.track(&tx, "tasks", id_key(&id)).map_err(|e| e.to_string())?;
+    
+    tx.execute(
         "UPDATE tasks SET position = ?, updated_at = ? WHERE id = ?",
         params![new_position, now.to_rfc3339(), id]
     ).map_err(|e| e.to_string())?;
     
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    
     Ok(())
 }
 
@@ -194,9 +316,10 @@ pub async fn get_tasks_by_project(project_id: String, db: State<'_, Database>) -
     
     let mut stmt = conn.prepare(
         "SELECT id, title, description, is_completed, priority, due_date, remind_at, 
-                created_at, updated_at, project_id, parent_id, position
+                created_at, updated_at, project_id, parent_id, position, milestone_id,
+                start_date, estimate_minutes, completed_at, recurrence
          FROM tasks 
-         WHERE project_id = ?
+         WHERE project_id = ? AND deleted_at IS NULL
          ORDER BY position ASC, created_at DESC"
     ).map_err(|e| e.to_string())?;
     
@@ -214,6 +337,11 @@ pub async fn get_tasks_by_project(project_id: String, db: State<'_, Database>) -
             project_id: row.get(9)?,
             parent_id: row.get(10)?,
             position: row.get(11)?,
+            milestone_id: row.get(12)?,
+            start_date: row.get::<_, Option<String>>(13)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(13, "start_date".to_string(), rusqlite::types::Type::Text))?,
+            estimate_minutes: row.get(14)?,
+            completed_at: row.get::<_, Option<String>>(15)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(15, "completed_at".to_string(), rusqlite::types::Type::Text))?,
+            recurrence: row.get(16)?,
         })
     }).map_err(|e| e.to_string())?;
     
@@ -0,0 +1,490 @@
+use crate::models::{ProjectTimeline, TaskDependency, TimelineMilestone, TimelineTask};
+use crate::db::Database;
+use crate::commands::projects::{project_from_row, PROJECT_COLUMNS};
+use crate::commands::milestones::{milestone_from_row, MILESTONE_COLUMNS};
+use crate::commands::calendar::{default_work_hours, local_midnight};
+use crate::commands::journal::{dependency_key, id_key, ChangeSet};
+use crate::commands::activity::{entity_title, log_activity};
+use tauri::State;
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use std::collections::{HashMap, VecDeque};
+
+// 时间线任务条的来源
+pub const SCHEDULE_FIXED: &str = "fixed";
+pub const SCHEDULE_AUTO: &str = "auto";
+pub const SCHEDULE_UNSCHEDULED: &str = "unscheduled";
+
+// 项目下的任务连同子任务，子任务不一定设置了 project_id
+const PROJECT_TASK_TREE: &str = "WITH RECURSIVE tree(id) AS (
+        SELECT id FROM tasks WHERE project_id = ?1 AND deleted_at IS NULL
+        UNION
+        SELECT t.id FROM tasks t INNER JOIN tree ON t.parent_id = tree.id WHERE t.deleted_at IS NULL
+    )";
+
+type WorkHours = (NaiveTime, NaiveTime);
+
+/// 排程用到的任务字段
+struct ScheduleTask {
+    id: String,
+    title: String,
+    parent_id: Option<String>,
+    milestone_id: Option<String>,
+    is_completed: bool,
+    priority: i32,
+    due_date: Option<DateTime<Utc>>,
+    start_date: Option<DateTime<Utc>>,
+    estimate_minutes: Option<i32>,
+}
+
+fn to_utc(naive: NaiveDateTime) -> DateTime<Utc> {
+    Local.from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| Local.from_local_datetime(&(naive + Duration::hours(1))).earliest())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&naive))
+}
+
+/// 从 `from` 起按每天的工作时间累计 `minutes` 分钟，`from` 不在工作时间内时从下一段工作时间开始
+fn add_work_minutes(from: DateTime<Utc>, minutes: i64, (work_start, work_end): WorkHours) -> DateTime<Utc> {
+    if work_end <= work_start {
+        return from + Duration::minutes(minutes);
+    }
+
+    let mut cursor = from.with_timezone(&Local).naive_local();
+    let mut remaining = Duration::minutes(minutes.max(0));
+    loop {
+        let date = cursor.date();
+        if cursor.time() < work_start {
+            cursor = date.and_time(work_start);
+        } else if cursor.time() >= work_end {
+            cursor = (date + Duration::days(1)).and_time(work_start);
+            continue;
+        }
+        let available = date.and_time(work_end) - cursor;
+        if remaining <= available {
+            return to_utc(cursor + remaining);
+        }
+        remaining = remaining - available;
+        cursor = (date + Duration::days(1)).and_time(work_start);
+    }
+}
+
+/// 从 `to` 往前按每天的工作时间倒推 `minutes` 分钟
+fn sub_work_minutes(to: DateTime<Utc>, minutes: i64, (work_start, work_end): WorkHours) -> DateTime<Utc> {
+    if work_end <= work_start {
+        return to - Duration::minutes(minutes);
+    }
+
+    let mut cursor = to.with_timezone(&Local).naive_local();
+    let mut remaining = Duration::minutes(minutes.max(0));
+    loop {
+        let date = cursor.date();
+        if cursor.time() > work_end {
+            cursor = date.and_time(work_end);
+        } else if cursor.time() <= work_start {
+            cursor = (date - Duration::days(1)).and_time(work_end);
+            continue;
+        }
+        let available = cursor - date.and_time(work_start);
+        if remaining <= available {
+            return to_utc(cursor - remaining);
+        }
+        remaining = remaining - available;
+        cursor = (date - Duration::days(1)).and_time(work_end);
+    }
+}
+
+fn load_schedule_tasks(conn: &Connection, project_id: &str) -> Result<Vec<ScheduleTask>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "{} SELECT id, title, parent_id, milestone_id, is_completed, priority, due_date, start_date, estimate_minutes
+            FROM tasks WHERE id IN (SELECT id FROM tree)
+            ORDER BY position, created_at",
+        PROJECT_TASK_TREE
+    ))?;
+
+    let rows = stmt.query_map(params![project_id], |row| {
+        Ok(ScheduleTask {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            parent_id: row.get(2)?,
+            milestone_id: row.get(3)?,
+            is_completed: row.get::<_, i32>(4)? != 0,
+            priority: row.get(5)?,
+            due_date: row.get::<_, Option<String>>(6)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(6, "due_date".to_string(), rusqlite::types::Type::Text))?,
+            start_date: row.get::<_, Option<String>>(7)?.map(|s| s.parse()).transpose().map_err(|_| rusqlite::Error::InvalidColumnType(7, "start_date".to_string(), rusqlite::types::Type::Text))?,
+            estimate_minutes: row.get::<_, Option<i32>>(8)?.filter(|m| *m > 0),
+        })
+    })?;
+
+    let mut tasks = Vec::new();
+    for row in rows {
+        tasks.push(row?);
+    }
+    Ok(tasks)
+}
+
+/// 项目内任务之间的依赖，指向项目外任务的依赖不参与排程
+fn load_project_dependencies(conn: &Connection, project_id: &str) -> Result<Vec<TaskDependency>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "{} SELECT task_id, depends_on_id, created_at FROM task_dependencies
+            WHERE task_id IN (SELECT id FROM tree) AND depends_on_id IN (SELECT id FROM tree)
+            ORDER BY created_at",
+        PROJECT_TASK_TREE
+    ))?;
+
+    let rows = stmt.query_map(params![project_id], |row| {
+        Ok(TaskDependency {
+            task_id: row.get(0)?,
+            depends_on_id: row.get(1)?,
+            created_at: row.get::<_, String>(2)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(2, "created_at".to_string(), rusqlite::types::Type::Text))?,
+        })
+    })?;
+
+    let mut dependencies = Vec::new();
+    for row in rows {
+        dependencies.push(row?);
+    }
+    Ok(dependencies)
+}
+
+/// 按依赖排序，同一层按任务原有顺序。存在环时剩余任务按原顺序排在最后
+fn topological_order(count: usize, preds: &[Vec<usize>], succs: &[Vec<usize>]) -> Vec<usize> {
+    let mut indegree: Vec<usize> = preds.iter().map(|p| p.len()).collect();
+    let mut queue: VecDeque<usize> = (0..count).filter(|i| indegree[*i] == 0).collect();
+    let mut visited = vec![false; count];
+    let mut order = Vec::with_capacity(count);
+
+    while let Some(i) = queue.pop_front() {
+        visited[i] = true;
+        order.push(i);
+        for &s in &succs[i] {
+            indegree[s] -= 1;
+            if indegree[s] == 0 {
+                queue.push_back(s);
+            }
+        }
+    }
+    order.extend((0..count).filter(|i| !visited[*i]));
+    order
+}
+
+/// 有日期的任务按日期显示：只有截止日期时按预计用时倒推开始时间，只有开始日期时按预计用时推算结束时间
+fn fixed_span(task: &ScheduleTask, hours: WorkHours) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    match (task.start_date, task.due_date, task.estimate_minutes) {
+        (Some(start), Some(due), _) => Some((start, due.max(start))),
+        (None, Some(due), Some(minutes)) => Some((sub_work_minutes(due, minutes as i64, hours), due)),
+        (None, Some(due), None) => Some((due, due)),
+        (Some(start), None, Some(minutes)) => Some((start, add_work_minutes(start, minutes as i64, hours))),
+        (Some(start), None, None) => Some((start, start)),
+        (None, None, _) => None,
+    }
+}
+
+fn min_time(a: Option<DateTime<Utc>>, b: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// 计算项目时间线。
+///
+/// `auto_schedule` 为 true 时，没有日期但有预计用时的未完成任务从 `anchor` 起按依赖顺序
+/// 逐个排在工作时间内。松弛时间按关键路径倒推：任务最晚结束时间取自身截止日期、里程碑截止日期、
+/// 项目截止日期和后续任务最晚开始时间中最早的一个，都没有时取整个项目的结束时间。
+fn build_timeline(conn: &Connection, project_id: &str, auto_schedule: bool, anchor: DateTime<Utc>) -> Result<ProjectTimeline, String> {
+    let project = conn.query_row(
+        &format!("SELECT {} FROM projects WHERE id = ? AND deleted_at IS NULL", PROJECT_COLUMNS),
+        params![project_id],
+        project_from_row
+    ).optional().map_err(|e| e.to_string())?.ok_or("Project not found")?;
+
+    let hours = default_work_hours(conn)?;
+    let tasks = load_schedule_tasks(conn, project_id).map_err(|e| e.to_string())?;
+    let dependencies = load_project_dependencies(conn, project_id).map_err(|e| e.to_string())?;
+
+    let index: HashMap<&str, usize> = tasks.iter().enumerate().map(|(i, t)| (t.id.as_str(), i)).collect();
+    let mut preds = vec![Vec::new(); tasks.len()];
+    let mut succs = vec![Vec::new(); tasks.len()];
+    for dependency in &dependencies {
+        if let (Some(&task), Some(&depends_on)) = (index.get(dependency.task_id.as_str()), index.get(dependency.depends_on_id.as_str())) {
+            preds[task].push(depends_on);
+            succs[depends_on].push(task);
+        }
+    }
+    let order = topological_order(tasks.len(), &preds, &succs);
+
+    let mut spans: Vec<Option<(DateTime<Utc>, DateTime<Utc>)>> = tasks.iter().map(|t| fixed_span(t, hours)).collect();
+    let mut schedules: Vec<&str> = spans.iter()
+        .map(|span| if span.is_some() { SCHEDULE_FIXED } else { SCHEDULE_UNSCHEDULED })
+        .collect();
+
+    if auto_schedule {
+        let mut cursor = add_work_minutes(anchor, 0, hours);
+        for &i in &order {
+            let task = &tasks[i];
+            let Some(minutes) = task.estimate_minutes else { continue };
+            if spans[i].is_some() || task.is_completed {
+                continue;
+            }
+            let ready = preds[i].iter().filter_map(|&p| spans[p].map(|(_, end)| end)).max();
+            let start = add_work_minutes(ready.map_or(cursor, |r| r.max(cursor)), 0, hours);
+            let end = add_work_minutes(start, minutes as i64, hours);
+            spans[i] = Some((start, end));
+            schedules[i] = SCHEDULE_AUTO;
+            cursor = end;
+        }
+    }
+
+    let milestones = {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM milestones WHERE project_id = ? ORDER BY position, due_date",
+            MILESTONE_COLUMNS
+        )).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![project_id], milestone_from_row).map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+    };
+    let milestone_due: HashMap<&str, DateTime<Utc>> = milestones.iter()
+        .filter(|m| m.completed_at.is_none())
+        .filter_map(|m| m.due_date.map(|due| (m.id.as_str(), due)))
+        .collect();
+
+    let project_end = spans.iter().flatten().map(|(_, end)| *end).max();
+    let mut late_start: Vec<Option<DateTime<Utc>>> = vec![None; tasks.len()];
+    let mut slack: Vec<Option<i64>> = vec![None; tasks.len()];
+    for &i in order.iter().rev() {
+        let task = &tasks[i];
+        let Some((start, end)) = spans[i] else { continue };
+        if task.is_completed {
+            continue;
+        }
+
+        let mut late_finish = min_time(task.due_date, project.due_date);
+        if let Some(due) = task.milestone_id.as_deref().and_then(|id| milestone_due.get(id)) {
+            late_finish = min_time(late_finish, Some(*due));
+        }
+        for &s in &succs[i] {
+            late_finish = min_time(late_finish, late_start[s]);
+        }
+        let late_finish = late_finish.or(project_end).unwrap_or(end);
+
+        late_start[i] = Some(late_finish - (end - start));
+        slack[i] = Some((late_finish - end).num_minutes());
+    }
+
+    let now = Utc::now();
+    let mut timeline_tasks: Vec<(usize, TimelineTask)> = tasks.iter().enumerate().map(|(i, task)| {
+        let starts_before_dependencies = spans[i].is_some_and(|(start, _)| {
+            preds[i].iter().any(|&p| !tasks[p].is_completed && spans[p].is_some_and(|(_, end)| end > start))
+        });
+        (i, TimelineTask {
+            task_id: task.id.clone(),
+            title: task.title.clone(),
+            parent_id: task.parent_id.clone(),
+            milestone_id: task.milestone_id.clone(),
+            is_completed: task.is_completed,
+            priority: task.priority,
+            starts_at: spans[i].map(|(start, _)| start),
+            ends_at: spans[i].map(|(_, end)| end),
+            schedule: schedules[i].to_string(),
+            estimate_minutes: task.estimate_minutes,
+            depends_on: preds[i].iter().map(|&p| tasks[p].id.clone()).collect(),
+            slack_minutes: slack[i],
+            is_critical: slack[i].is_some_and(|s| s <= 0),
+            is_overdue: !task.is_completed && task.due_date.is_some_and(|due| due < now),
+            starts_before_dependencies,
+        })
+    }).collect();
+    timeline_tasks.sort_by_key(|(i, t)| (t.starts_at.is_none(), t.starts_at, *i));
+
+    let timeline_milestones = milestones.into_iter().map(|milestone| {
+        let forecast_at = tasks.iter().enumerate()
+            .filter(|(_, t)| t.milestone_id.as_deref() == Some(milestone.id.as_str()))
+            .filter_map(|(i, _)| spans[i].map(|(_, end)| end))
+            .max();
+        let slack_minutes = match (milestone.due_date, forecast_at) {
+            (Some(due), Some(forecast)) if milestone.completed_at.is_none() => Some((due - forecast).num_minutes()),
+            _ => None,
+        };
+        TimelineMilestone {
+            is_overdue: milestone.completed_at.is_none() && milestone.due_date.is_some_and(|due| due < now),
+            milestone_id: milestone.id,
+            name: milestone.name,
+            due_date: milestone.due_date,
+            completed_at: milestone.completed_at,
+            forecast_at,
+            slack_minutes,
+        }
+    }).collect();
+
+    Ok(ProjectTimeline {
+        project_id: project.id,
+        starts_at: spans.iter().flatten().map(|(start, _)| *start).min(),
+        ends_at: project_end,
+        due_date: project.due_date,
+        tasks: timeline_tasks.into_iter().map(|(_, t)| t).collect(),
+        milestones: timeline_milestones,
+        dependencies,
+    })
+}
+
+/// 自动排程的起点：指定日期（本地零点）和当前时间中较晚的一个
+fn schedule_anchor(start_date: Option<String>) -> Result<DateTime<Utc>, String> {
+    let now = Utc::now();
+    match start_date {
+        Some(date) => {
+            let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|_| "Invalid start_date format, expected YYYY-MM-DD")?;
+            Ok(local_midnight(date)?.max(now))
+        }
+        None => Ok(now),
+    }
+}
+
+/// 项目时间线：任务条、依赖、里程碑和松弛时间，前端只需按时间绘制。
+/// `auto_schedule` 为 true 时为有预计用时但没有日期的任务计算排程，不写入数据库
+#[tauri::command]
+pub async fn get_project_timeline(
+    project_id: String,
+    auto_schedule: Option<bool>,
+    start_date: Option<String>,
+    db: State<'_, Database>
+) -> Result<ProjectTimeline, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let anchor = schedule_anchor(start_date)?;
+    build_timeline(&conn, &project_id, auto_schedule.unwrap_or(false), anchor)
+}
+
+/// 把自动排程的结果写入任务的开始日期，结束时间之后仍由预计用时推算
+#[tauri::command]
+pub async fn apply_project_schedule(
+    project_id: String,
+    start_date: Option<String>,
+    db: State<'_, Database>
+) -> Result<ProjectTimeline, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let anchor = schedule_anchor(start_date)?;
+    let now = Utc::now();
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let timeline = build_timeline(&tx, &project_id, true, anchor)?;
+
+    let mut change = ChangeSet::new("apply_project_schedule");
+    let mut scheduled = 0;
+    for task in timeline.tasks.iter().filter(|t| t.schedule == SCHEDULE_AUTO) {
+        change.track(&tx, "tasks", id_key(&task.task_id)).map_err(|e| e.to_string())?;
+        tx.execute(
+            "UPDATE tasks SET start_date = ?, updated_at = ? WHERE id = ?",
+            params![task.starts_at.map(|d| d.to_rfc3339()), now.to_rfc3339(), task.task_id]
+        ).map_err(|e| e.to_string())?;
+        scheduled += 1;
+    }
+    change.commit(&tx).map_err(|e| e.to_string())?;
+
+    if scheduled > 0 {
+        let summary = format!("Scheduled {} task(s) in project \"{}\"", scheduled, entity_title(&tx, "project", &project_id).map_err(|e| e.to_string())?);
+        log_activity(&tx, "project", &project_id, "updated", &summary).map_err(|e| e.to_string())?;
+    }
+
+    let timeline = build_timeline(&tx, &project_id, false, anchor)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(timeline)
+}
+
+/// 添加完成-开始依赖：`task_id` 在 `depends_on_id` 完成后才能开始
+#[tauri::command]
+pub async fn add_task_dependency(
+    task_id: String,
+    depends_on_id: String,
+    db: State<'_, Database>
+) -> Result<TaskDependency, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    if task_id == depends_on_id {
+        return Err("A task cannot depend on itself".to_string());
+    }
+    for id in [&task_id, &depends_on_id] {
+        let found: bool = tx.query_row(
+            "SELECT COUNT(*) > 0 FROM tasks WHERE id = ? AND deleted_at IS NULL",
+            params![id],
+            |row| row.get(0)
+        ).map_err(|e| e.to_string())?;
+        if !found {
+            return Err("Task not found".to_string());
+        }
+    }
+
+    // 前置任务已经（间接）依赖当前任务时会形成环
+    let creates_cycle: bool = tx.query_row(
+        "WITH RECURSIVE upstream(id) AS (
+            SELECT depends_on_id FROM task_dependencies WHERE task_id = ?1
+            UNION
+            SELECT d.depends_on_id FROM task_dependencies d INNER JOIN upstream u ON d.task_id = u.id
+         )
+         SELECT COUNT(*) > 0 FROM upstream WHERE id = ?2",
+        params![depends_on_id, task_id],
+        |row| row.get(0)
+    ).map_err(|e| e.to_string())?;
+    if creates_cycle {
+        return Err("Dependency would create a cycle".to_string());
+    }
+
+    let now = Utc::now();
+    let mut change = ChangeSet::new("add_task_dependency");
+    change.track(&tx, "task_dependencies", dependency_key(&task_id, &depends_on_id)).map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "INSERT OR IGNORE INTO task_dependencies (task_id, depends_on_id, created_at) VALUES (?, ?, ?)",
+        params![task_id, depends_on_id, now.to_rfc3339()]
+    ).map_err(|e| e.to_string())?;
+
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    let summary = format!(
+        "Task \"{}\" now depends on \"{}\"",
+        entity_title(&tx, "task", &task_id).map_err(|e| e.to_string())?,
+        entity_title(&tx, "task", &depends_on_id).map_err(|e| e.to_string())?
+    );
+    log_activity(&tx, "task", &task_id, "updated", &summary).map_err(|e| e.to_string())?;
+
+    let created_at: String = tx.query_row(
+        "SELECT created_at FROM task_dependencies WHERE task_id = ? AND depends_on_id = ?",
+        params![task_id, depends_on_id],
+        |row| row.get(0)
+    ).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(TaskDependency {
+        task_id,
+        depends_on_id,
+        created_at: created_at.parse().unwrap_or(now),
+    })
+}
+
+#[tauri::command]
+pub async fn remove_task_dependency(
+    task_id: String,
+    depends_on_id: String,
+    db: State<'_, Database>
+) -> Result<(), String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let mut change = ChangeSet::new("remove_task_dependency");
+    change.track(&tx, "task_dependencies", dependency_key(&task_id, &depends_on_id)).map_err(|e| e.to_string())?;
+
+    let removed = tx.execute(
+        "DELETE FROM task_dependencies WHERE task_id = ? AND depends_on_id = ?",
+        params![task_id, depends_on_id]
+    ).map_err(|e| e.to_string())?;
+    if removed == 0 {
+        return Err("Dependency not found".to_string());
+    }
+
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
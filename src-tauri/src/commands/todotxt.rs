@@ -0,0 +1,635 @@
+use crate::models::{ImportReport, TodoTxtSyncReport};
+use crate::db::{self, Database};
+use crate::commands::activity::log_activity;
+use crate::commands::importers::{insert_imported_task, ImportedTask, DEFAULT_PRIORITY, LOWEST_PRIORITY};
+use crate::commands::projects::find_or_create_project;
+use crate::commands::tags::find_or_create_tag;
+use crate::commands::sync::content_hash;
+use crate::commands::trash::soft_delete_task;
+use tauri::{AppHandle, Manager, State};
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::Duration as StdDuration;
+
+// 同步模式的设置键：文件路径、上次同步时的文件哈希，以及各任务 id 和对应行的哈希（`id:hash`，逗号分隔）
+pub const TODO_TXT_SYNC_SETTING: &str = "todo_txt_sync_path";
+const TODO_TXT_HASH_SETTING: &str = "todo_txt_sync_hash";
+const TODO_TXT_IDS_SETTING: &str = "todo_txt_sync_ids";
+
+// 同步模式下检查文件和数据库变化的间隔
+const TODO_TXT_POLL_SECS: u64 = 3;
+
+/// todo.txt 中的一行任务
+#[derive(Debug, Default)]
+struct TodoLine {
+    completed: bool,
+    priority: Option<char>,
+    completion_date: Option<NaiveDate>,
+    creation_date: Option<NaiveDate>,
+    title: String,
+    projects: Vec<String>,
+    contexts: Vec<String>,
+    due: Option<NaiveDate>,
+    id: Option<String>,
+    parent: Option<String>,
+}
+
+/// 导出所需的任务数据
+struct TodoTask {
+    id: String,
+    title: String,
+    is_completed: bool,
+    priority: i32,
+    due_date: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    parent_id: Option<String>,
+    project: Option<String>,
+    contexts: Vec<String>,
+}
+
+fn parse_date(token: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(token, "%Y-%m-%d").ok()
+}
+
+fn local_date(dt: &DateTime<Utc>) -> NaiveDate {
+    dt.with_timezone(&Local).date_naive()
+}
+
+/// 截止日期按本地时间的零点保存
+fn date_to_utc(date: NaiveDate) -> Option<DateTime<Utc>> {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// (A)、(B) 对应优先级 1、2，没有优先级对应默认优先级，其他字母对应最低优先级
+fn priority_from_letter(letter: Option<char>) -> i32 {
+    match letter {
+        Some('A') => 1,
+        Some('B') => 2,
+        Some(_) => LOWEST_PRIORITY,
+        None => DEFAULT_PRIORITY,
+    }
+}
+
+/// 默认优先级和最低优先级（无优先级）都不写字母
+fn priority_to_letter(priority: i32) -> Option<char> {
+    match priority {
+        1 => Some('A'),
+        2 => Some('B'),
+        _ => None,
+    }
+}
+
+/// 导出时使用的优先级字母：文件中原有的字母与任务优先级一致时保留，避免 (D) 等字母被改写
+fn task_letter(task: &TodoTask, file_letters: &HashMap<String, char>) -> Option<char> {
+    file_letters.get(&task.id)
+        .copied()
+        .filter(|letter| priority_from_letter(Some(*letter)) == task.priority)
+        .or_else(|| priority_to_letter(task.priority))
+}
+
+/// 文件中各任务行的优先级字母，按任务 id 索引
+fn file_priority_letters(text: &str) -> HashMap<String, char> {
+    text.lines()
+        .filter_map(parse_todo_line)
+        .filter_map(|line| Some((line.id?, line.priority?)))
+        .collect()
+}
+
+/// todo.txt 的项目和上下文不能包含空格，用连字符代替
+fn todo_token(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+fn parse_priority_token(token: &str) -> Option<char> {
+    let inner = token.strip_prefix('(')?.strip_suffix(')')?;
+    let mut chars = inner.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_uppercase() => Some(c),
+        _ => None,
+    }
+}
+
+fn parse_todo_line(line: &str) -> Option<TodoLine> {
+    let mut tokens = line.split_whitespace().peekable();
+    tokens.peek()?;
+
+    let mut todo = TodoLine::default();
+
+    if tokens.peek() == Some(&"x") {
+        tokens.next();
+        todo.completed = true;
+        // 完成日期在前，创建日期在后
+        todo.completion_date = tokens.peek().and_then(|t| parse_date(t));
+        if todo.completion_date.is_some() {
+            tokens.next();
+            todo.creation_date = tokens.peek().and_then(|t| parse_date(t));
+            if todo.creation_date.is_some() {
+                tokens.next();
+            }
+        }
+    } else {
+        todo.priority = tokens.peek().and_then(|t| parse_priority_token(t));
+        if todo.priority.is_some() {
+            tokens.next();
+        }
+        todo.creation_date = tokens.peek().and_then(|t| parse_date(t));
+        if todo.creation_date.is_some() {
+            tokens.next();
+        }
+    }
+
+    let mut words = Vec::new();
+    for token in tokens {
+        if let Some(project) = token.strip_prefix('+').filter(|p| !p.is_empty()) {
+            todo.projects.push(project.to_string());
+        } else if let Some(context) = token.strip_prefix('@').filter(|c| !c.is_empty()) {
+            todo.contexts.push(context.to_string());
+        } else if let Some(date) = token.strip_prefix("due:").and_then(parse_date) {
+            todo.due = Some(date);
+        } else if let Some(id) = token.strip_prefix("id:").filter(|i| !i.is_empty()) {
+            todo.id = Some(id.to_string());
+        } else if let Some(parent) = token.strip_prefix("parent:").filter(|p| !p.is_empty()) {
+            todo.parent = Some(parent.to_string());
+        } else if let Some(priority) = token.strip_prefix("pri:").and_then(|p| p.chars().next()) {
+            // 已完成任务按惯例把优先级保存为 pri: 标签
+            todo.priority = Some(priority.to_ascii_uppercase());
+        } else {
+            words.push(token);
+        }
+    }
+    todo.title = words.join(" ");
+
+    Some(todo)
+}
+
+fn format_todo_line(task: &TodoTask, priority: Option<char>) -> String {
+    let mut parts = Vec::new();
+
+    if task.is_completed {
+        parts.push("x".to_string());
+        if let Some(completed_at) = &task.completed_at {
+            parts.push(local_date(completed_at).to_string());
+        }
+    } else if let Some(letter) = priority {
+        parts.push(format!("({})", letter));
+    }
+    parts.push(local_date(&task.created_at).to_string());
+    parts.push(task.title.clone());
+
+    if let Some(project) = &task.project {
+        parts.push(format!("+{}", todo_token(project)));
+    }
+    for context in &task.contexts {
+        parts.push(format!("@{}", todo_token(context)));
+    }
+    if let Some(due) = &task.due_date {
+        parts.push(format!("due:{}", local_date(due)));
+    }
+    if let (true, Some(letter)) = (task.is_completed, priority) {
+        parts.push(format!("pri:{}", letter));
+    }
+    if let Some(parent_id) = &task.parent_id {
+        parts.push(format!("parent:{}", parent_id));
+    }
+    parts.push(format!("id:{}", task.id));
+
+    parts.join(" ")
+}
+
+fn load_todo_tasks(
+    conn: &Connection,
+    id: Option<&str>,
+    project_id: Option<&str>,
+    include_completed: bool,
+) -> Result<Vec<TodoTask>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.title, t.is_completed, t.priority, t.due_date, t.created_at,
+                CASE WHEN t.is_completed = 1 THEN COALESCE(t.completed_at, t.updated_at) END,
+                t.parent_id, p.name
+         FROM tasks t
+         LEFT JOIN projects p ON p.id = t.project_id AND p.deleted_at IS NULL
+         WHERE t.deleted_at IS NULL AND (?1 IS NULL OR t.id = ?1) AND (?2 IS NULL OR t.project_id = ?2)
+           AND (?3 OR t.is_completed = 0)
+         ORDER BY t.is_completed, t.priority, t.position, t.created_at"
+    )?;
+
+    let task_iter = stmt.query_map(params![id, project_id, include_completed], |row| {
+        Ok(TodoTask {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            is_completed: row.get::<_, i32>(2)? != 0,
+            priority: row.get(3)?,
+            due_date: row.get::<_, Option<String>>(4)?.map(|s| s.parse()).transpose()
+                .map_err(|_| rusqlite::Error::InvalidColumnType(4, "due_date".to_string(), rusqlite::types::Type::Text))?,
+            created_at: row.get::<_, String>(5)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(5, "created_at".to_string(), rusqlite::types::Type::Text))?,
+            completed_at: row.get::<_, Option<String>>(6)?.map(|s| s.parse()).transpose()
+                .map_err(|_| rusqlite::Error::InvalidColumnType(6, "completed_at".to_string(), rusqlite::types::Type::Text))?,
+            parent_id: row.get(7)?,
+            project: row.get(8)?,
+            contexts: Vec::new(),
+        })
+    })?;
+
+    let mut tasks = Vec::new();
+    for task in task_iter {
+        let mut task = task?;
+        task.contexts = task_contexts(conn, &task.id)?;
+        tasks.push(task);
+    }
+
+    Ok(tasks)
+}
+
+fn task_contexts(conn: &Connection, task_id: &str) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT tg.name FROM tags tg
+         INNER JOIN task_tags tt ON tt.tag_id = tg.id
+         WHERE tt.task_id = ?
+         ORDER BY tg.name"
+    )?;
+    let contexts = stmt.query_map(params![task_id], |row| row.get(0))?.collect();
+    contexts
+}
+
+fn load_todo_task(conn: &Connection, id: &str) -> Result<Option<TodoTask>, rusqlite::Error> {
+    Ok(load_todo_tasks(conn, Some(id), None, true)?.into_iter().next())
+}
+
+/// 按 todo.txt 中的名称查找项目，连字符可以匹配名称中的空格
+fn resolve_project(conn: &Connection, token: &str) -> Result<String, rusqlite::Error> {
+    let existing: Option<String> = conn.query_row(
+        "SELECT id FROM projects
+         WHERE deleted_at IS NULL AND (name = ?1 OR REPLACE(name, ' ', '-') = ?1)
+         ORDER BY name = ?1 DESC LIMIT 1",
+        params![token],
+        |row| row.get(0)
+    ).optional()?;
+
+    match existing {
+        Some(id) => Ok(id),
+        None => find_or_create_project(conn, token),
+    }
+}
+
+fn resolve_tag(conn: &Connection, token: &str) -> Result<String, rusqlite::Error> {
+    let existing: Option<String> = conn.query_row(
+        "SELECT id FROM tags WHERE name = ?1 OR REPLACE(name, ' ', '-') = ?1 ORDER BY name = ?1 DESC LIMIT 1",
+        params![token],
+        |row| row.get(0)
+    ).optional()?;
+
+    match existing {
+        Some(id) => Ok(id),
+        None => find_or_create_tag(conn, token),
+    }
+}
+
+/// 用上下文替换任务的标签
+fn set_task_contexts(conn: &Connection, task_id: &str, contexts: &[String]) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM task_tags WHERE task_id = ?", params![task_id])?;
+    for context in contexts {
+        let tag_id = resolve_tag(conn, context)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO task_tags (task_id, tag_id) VALUES (?, ?)",
+            params![task_id, tag_id]
+        )?;
+    }
+    Ok(())
+}
+
+fn task_exists(conn: &Connection, id: &str) -> Result<bool, rusqlite::Error> {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM tasks WHERE id = ? AND deleted_at IS NULL",
+        params![id],
+        |row| row.get(0)
+    )
+}
+
+/// 按 todo.txt 的内容更新已有任务，没有变化时返回 false
+fn update_task_from_line(conn: &Connection, current: &TodoTask, line: &TodoLine) -> Result<bool, rusqlite::Error> {
+    // 没有字母的行对应默认或最低优先级，任务原本就是其中之一时保持不变
+    let priority = match line.priority {
+        None if priority_to_letter(current.priority).is_none() => current.priority,
+        letter => priority_from_letter(letter),
+    };
+    let due_changed = current.due_date.as_ref().map(local_date) != line.due;
+    let project = line.projects.first();
+    let project_changed = current.project.as_deref().map(todo_token) != project.map(|p| todo_token(p));
+
+    let mut current_contexts: Vec<String> = current.contexts.iter().map(|c| todo_token(c)).collect();
+    let mut line_contexts: Vec<String> = line.contexts.iter().map(|c| todo_token(c)).collect();
+    current_contexts.sort();
+    line_contexts.sort();
+
+    // 完成日期被修改时使用文件中的日期，否则保留原来的完成时间
+    let completion_changed = line.completed
+        && line.completion_date.is_some()
+        && current.completed_at.as_ref().map(local_date) != line.completion_date;
+
+    let changed = current.title != line.title
+        || current.is_completed != line.completed
+        || completion_changed
+        || current.priority != priority
+        || due_changed
+        || project_changed
+        || current_contexts != line_contexts;
+    if !changed {
+        return Ok(false);
+    }
+
+    // 只比较日期，日期未变时保留原来的具体时间
+    let due_date = if due_changed {
+        line.due.and_then(date_to_utc)
+    } else {
+        current.due_date
+    };
+    let project_id = match project {
+        Some(token) => Some(resolve_project(conn, token)?),
+        None => None,
+    };
+    let project_id = if project_changed {
+        project_id
+    } else {
+        conn.query_row("SELECT project_id FROM tasks WHERE id = ?", params![current.id], |row| row.get(0))?
+    };
+
+    let now = Utc::now();
+    let completed_at = if !line.completed {
+        None
+    } else if completion_changed {
+        line.completion_date.and_then(date_to_utc)
+    } else {
+        Some(current.completed_at.unwrap_or(now))
+    };
+    conn.execute(
+        "UPDATE tasks SET title = ?, is_completed = ?, priority = ?, due_date = ?, project_id = ?, updated_at = ?, completed_at = ?
+         WHERE id = ?",
+        params![
+            line.title, line.completed as i32, priority, due_date.map(|d| d.to_rfc3339()),
+            project_id, now.to_rfc3339(), completed_at.map(|d| d.to_rfc3339()), current.id
+        ]
+    )?;
+    set_task_contexts(conn, &current.id, &line.contexts)?;
+
+    let action = match (current.is_completed, line.completed) {
+        (false, true) => "completed",
+        (true, false) => "reopened",
+        _ => "updated",
+    };
+    log_activity(conn, "task", &current.id, action, &format!("Task \"{}\" {} from todo.txt", line.title, action))?;
+
+    Ok(true)
+}
+
+fn create_task_from_line(conn: &Connection, line: &TodoLine) -> Result<String, rusqlite::Error> {
+    let project_id = match line.projects.first() {
+        Some(token) => Some(resolve_project(conn, token)?),
+        None => None,
+    };
+    let parent_id = match &line.parent {
+        Some(parent) if task_exists(conn, parent)? => Some(parent.clone()),
+        _ => None,
+    };
+
+    let task = ImportedTask {
+        title: line.title.clone(),
+        description: None,
+        is_completed: line.completed,
+        priority: priority_from_letter(line.priority),
+        due_date: line.due.and_then(date_to_utc),
+        created_at: line.creation_date.and_then(date_to_utc).unwrap_or_else(Utc::now),
+        project_id,
+        parent_id,
+    };
+    let id = insert_imported_task(conn, &task, "todo.txt")?;
+    if let (true, Some(completed_at)) = (line.completed, line.completion_date.and_then(date_to_utc)) {
+        conn.execute("UPDATE tasks SET completed_at = ? WHERE id = ?", params![completed_at.to_rfc3339(), id])?;
+    }
+    set_task_contexts(conn, &id, &line.contexts)?;
+
+    Ok(id)
+}
+
+/// 导入 todo.txt 的各行：带有已存在 id 的行更新对应任务，其余新建任务。
+/// `baselines` 为上次同步时各任务所在行的哈希，与之相同的行自上次同步后没有修改，不覆盖应用内的修改
+fn import_todo_lines(
+    conn: &Connection,
+    text: &str,
+    baselines: &HashMap<String, String>,
+    report: &mut ImportReport,
+) -> Result<HashSet<String>, rusqlite::Error> {
+    let mut seen = HashSet::new();
+
+    for (index, raw) in text.lines().enumerate() {
+        let Some(line) = parse_todo_line(raw) else { continue };
+        if line.title.is_empty() {
+            report.skip(format!("line {}", index + 1), "task has no description");
+            continue;
+        }
+        if line.projects.len() > 1 {
+            report.skip(format!("line {}", index + 1), format!("only the first project is kept (+{})", line.projects.join(" +")));
+        }
+
+        let current = match &line.id {
+            Some(id) => load_todo_task(conn, id)?,
+            None => None,
+        };
+
+        match current {
+            Some(current) if baselines.get(&current.id) == Some(&content_hash(raw.trim())) => {
+                seen.insert(current.id);
+            }
+            Some(current) => {
+                if update_task_from_line(conn, &current, &line)? {
+                    report.tasks_updated += 1;
+                }
+                seen.insert(current.id);
+            }
+            None => {
+                let id = create_task_from_line(conn, &line)?;
+                report.tasks_created += 1;
+                seen.insert(id);
+            }
+        }
+    }
+
+    Ok(seen)
+}
+
+/// 解析同步记录中的任务 id 和行哈希，旧版本只记录了 id，此时没有基准
+fn parse_sync_baselines(value: &str) -> HashMap<String, Option<String>> {
+    value.split(',')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once(':') {
+            Some((id, hash)) => (id.to_string(), Some(hash.to_string())),
+            None => (entry.to_string(), None),
+        })
+        .collect()
+}
+
+fn render_todo_txt(tasks: &[TodoTask], file_letters: &HashMap<String, char>) -> String {
+    let mut text = tasks.iter()
+        .map(|task| format_todo_line(task, task_letter(task, file_letters)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+/// 双向同步 todo.txt 文件。
+///
+/// 文件自上次同步后有修改时先导入：只应用自上次同步后修改过的行、新建没有 id 的任务，
+/// 上次同步时存在但已从文件中删除的任务移入回收站。然后按数据库内容重写文件。
+pub fn sync_todo_txt(conn: &mut Connection, path: &Path) -> Result<TodoTxtSyncReport, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut report = TodoTxtSyncReport::default();
+
+    let text = if path.exists() {
+        fs::read_to_string(path).map_err(|e| e.to_string())?
+    } else {
+        String::new()
+    };
+    let last_hash = db::get_setting(&tx, TODO_TXT_HASH_SETTING).map_err(|e| e.to_string())?;
+
+    if path.exists() && last_hash.as_deref() != Some(content_hash(&text).as_str()) {
+        // 首次同步时没有记录，所有行都会导入，也不会删除任何任务
+        let known = parse_sync_baselines(
+            &db::get_setting(&tx, TODO_TXT_IDS_SETTING).map_err(|e| e.to_string())?.unwrap_or_default()
+        );
+        let baselines: HashMap<String, String> = known.iter()
+            .filter_map(|(id, hash)| hash.clone().map(|hash| (id.clone(), hash)))
+            .collect();
+
+        let mut import = ImportReport::new("todo.txt");
+        let seen = import_todo_lines(&tx, &text, &baselines, &mut import).map_err(|e| e.to_string())?;
+        report.created = import.tasks_created;
+        report.updated = import.tasks_updated;
+
+        let now = Utc::now().to_rfc3339();
+        for id in known.keys().filter(|id| !seen.contains(*id)) {
+            if soft_delete_task(&tx, id, &now).map_err(|e| e.to_string())? > 0 {
+                log_activity(&tx, "task", id, "deleted", "Task removed from todo.txt").map_err(|e| e.to_string())?;
+                report.deleted += 1;
+            }
+        }
+    }
+
+    let tasks = load_todo_tasks(&tx, None, None, true).map_err(|e| e.to_string())?;
+    let file_letters = file_priority_letters(&text);
+    let rendered = render_todo_txt(&tasks, &file_letters);
+    if rendered != text {
+        fs::write(path, &rendered).map_err(|e| e.to_string())?;
+        report.exported = tasks.len();
+    }
+
+    let ids = tasks.iter()
+        .map(|task| format!("{}:{}", task.id, content_hash(&format_todo_line(task, task_letter(task, &file_letters)))))
+        .collect::<Vec<_>>()
+        .join(",");
+    db::set_setting(&tx, TODO_TXT_HASH_SETTING, &content_hash(&rendered)).map_err(|e| e.to_string())?;
+    db::set_setting(&tx, TODO_TXT_IDS_SETTING, &ids).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    report.synced_at = Some(Utc::now());
+    Ok(report)
+}
+
+/// 启动后台同步任务，开启同步模式后定期检查文件与数据库的变化
+pub fn spawn_todo_txt_sync(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(StdDuration::from_secs(TODO_TXT_POLL_SECS)).await;
+
+            let database = app.state::<Database>();
+            let Ok(mut conn) = database.get_connection() else { continue };
+            let path = match db::get_setting(&conn, TODO_TXT_SYNC_SETTING) {
+                Ok(Some(path)) => path,
+                _ => continue,
+            };
+
+            match sync_todo_txt(&mut conn, Path::new(&path)) {
+                Ok(report) if report.created + report.updated + report.deleted > 0 => {
+                    log::info!("todo.txt sync: {} created, {} updated, {} deleted", report.created, report.updated, report.deleted);
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("todo.txt sync failed: {}", e),
+            }
+        }
+    });
+}
+
+/// 导出任务为 todo.txt 文件，返回导出的任务数
+#[tauri::command]
+pub async fn export_todo_txt(
+    path: String,
+    project_id: Option<String>,
+    include_completed: Option<bool>,
+    db: State<'_, Database>
+) -> Result<usize, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let tasks = load_todo_tasks(&conn, None, project_id.as_deref(), include_completed.unwrap_or(true))
+        .map_err(|e| e.to_string())?;
+    fs::write(&path, render_todo_txt(&tasks, &HashMap::new())).map_err(|e| e.to_string())?;
+
+    Ok(tasks.len())
+}
+
+#[tauri::command]
+pub async fn import_todo_txt(path: String, db: State<'_, Database>) -> Result<ImportReport, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let text = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut report = ImportReport::new("todo.txt");
+    import_todo_lines(&tx, &text, &HashMap::new(), &mut report).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(report)
+}
+
+/// 开启 todo.txt 同步模式，立即执行一次同步
+#[tauri::command]
+pub async fn enable_todo_txt_sync(path: String, db: State<'_, Database>) -> Result<TodoTxtSyncReport, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    // 切换到新文件时清除旧文件的同步记录
+    let previous = db::get_setting(&conn, TODO_TXT_SYNC_SETTING).map_err(|e| e.to_string())?;
+    if previous.as_deref() != Some(path.as_str()) {
+        db::set_setting(&conn, TODO_TXT_HASH_SETTING, "").map_err(|e| e.to_string())?;
+        db::set_setting(&conn, TODO_TXT_IDS_SETTING, "").map_err(|e| e.to_string())?;
+    }
+    db::set_setting(&conn, TODO_TXT_SYNC_SETTING, &path).map_err(|e| e.to_string())?;
+
+    sync_todo_txt(&mut conn, Path::new(&path))
+}
+
+#[tauri::command]
+pub async fn disable_todo_txt_sync(db: State<'_, Database>) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "DELETE FROM settings WHERE key IN (?, ?, ?)",
+        params![TODO_TXT_SYNC_SETTING, TODO_TXT_HASH_SETTING, TODO_TXT_IDS_SETTING]
+    ).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn sync_todo_txt_now(db: State<'_, Database>) -> Result<TodoTxtSyncReport, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let path = db::get_setting(&conn, TODO_TXT_SYNC_SETTING).map_err(|e| e.to_string())?
+        .ok_or("todo.txt sync is not enabled")?;
+
+    sync_todo_txt(&mut conn, Path::new(&path))
+}
@@ -0,0 +1,463 @@
+use crate::models::TrashItem;
+use crate::db::{self, Database};
+use crate::commands::activity::{entity_title, log_activity};
+use crate::commands::journal::{id_key, ChangeSet};
+use tauri::State;
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{Duration, Utc};
+
+// 回收站自动清空天数的设置键与默认值
+pub const TRASH_RETENTION_SETTING: &str = "trash_retention_days";
+pub const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+
+// 项目下的任务连同子任务，子任务不一定设置了 project_id
+const PROJECT_TASK_TREE: &str = "WITH RECURSIVE tree(id) AS (
+        SELECT id FROM tasks WHERE project_id = ?1
+        UNION
+        SELECT t.id FROM tasks t INNER JOIN tree ON t.parent_id = tree.id
+    )";
+
+/// 将笔记移入回收站，标签关联保留以便恢复
+pub fn soft_delete_note(conn: &Connection, id: &str, deleted_at: &str) -> Result<usize, rusqlite::Error> {
+    conn.execute(
+        "UPDATE notes SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL",
+        params![deleted_at, id]
+    )
+}
+
+/// 将任务及其所有子任务移入回收站，整棵子树使用同一个删除时间
+pub fn soft_delete_task(conn: &Connection, id: &str, deleted_at: &str) -> Result<usize, rusqlite::Error> {
+    conn.execute(
+        "WITH RECURSIVE subtree(id) AS (
+            SELECT ?1
+            UNION ALL
+            SELECT t.id FROM tasks t INNER JOIN subtree s ON t.parent_id = s.id
+         )
+         UPDATE tasks SET deleted_at = ?2
+         WHERE id IN (SELECT id FROM subtree) AND deleted_at IS NULL",
+        params![id, deleted_at]
+    )
+}
+
+/// 返回任务及其所有子任务的 id
+pub fn task_subtree_ids(conn: &Connection, id: &str) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE subtree(id) AS (
+            SELECT ?1
+            UNION ALL
+            SELECT t.id FROM tasks t INNER JOIN subtree s ON t.parent_id = s.id
+         )
+         SELECT id FROM subtree"
+    )?;
+    let ids = stmt.query_map(params![id], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(ids)
+}
+
+/// 返回项目任务树中 `deleted_at` 等于给定值的任务 id，`None` 表示未删除的任务
+pub fn project_task_ids(conn: &Connection, project_id: &str, deleted_at: Option<&str>) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "{} SELECT id FROM tasks WHERE id IN (SELECT id FROM tree) AND deleted_at IS ?2",
+        PROJECT_TASK_TREE
+    ))?;
+    let ids = stmt.query_map(params![project_id, deleted_at], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(ids)
+}
+
+/// 返回项目下 `deleted_at` 等于给定值的笔记 id，`None` 表示未删除的笔记
+pub fn project_note_ids(conn: &Connection, project_id: &str, deleted_at: Option<&str>) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT id FROM notes WHERE project_id = ? AND deleted_at IS ?")?;
+    let ids = stmt.query_map(params![project_id, deleted_at], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(ids)
+}
+
+/// 将项目及其下的任务（包括子任务）和笔记移入回收站，使用同一个删除时间
+pub fn soft_delete_project(conn: &Connection, id: &str, deleted_at: &str) -> Result<usize, rusqlite::Error> {
+    conn.execute(
+        &format!(
+            "{} UPDATE tasks SET deleted_at = ?2 WHERE id IN (SELECT id FROM tree) AND deleted_at IS NULL",
+            PROJECT_TASK_TREE
+        ),
+        params![id, deleted_at]
+    )?;
+    conn.execute(
+        "UPDATE notes SET deleted_at = ? WHERE project_id = ? AND deleted_at IS NULL",
+        params![deleted_at, id]
+    )?;
+    conn.execute(
+        "UPDATE projects SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL",
+        params![deleted_at, id]
+    )
+}
+
+pub fn restore_note(conn: &Connection, id: &str) -> Result<usize, rusqlite::Error> {
+    conn.execute("UPDATE notes SET deleted_at = NULL WHERE id = ?", params![id])
+}
+
+/// 恢复任务以及与它一起被删除的子任务；父任务仍在回收站时将其提升为顶层任务
+pub fn restore_task(conn: &Connection, id: &str) -> Result<usize, rusqlite::Error> {
+    let deleted_at: Option<String> = conn.query_row(
+        "SELECT deleted_at FROM tasks WHERE id = ?",
+        params![id],
+        |row| row.get(0)
+    ).optional()?.flatten();
+
+    let deleted_at = match deleted_at {
+        Some(deleted_at) => deleted_at,
+        None => return Ok(0),
+    };
+
+    let restored = conn.execute(
+        "WITH RECURSIVE subtree(id) AS (
+            SELECT ?1
+            UNION ALL
+            SELECT t.id FROM tasks t INNER JOIN subtree s ON t.parent_id = s.id
+         )
+         UPDATE tasks SET deleted_at = NULL
+         WHERE id IN (SELECT id FROM subtree) AND deleted_at = ?2",
+        params![id, deleted_at]
+    )?;
+
+    conn.execute(
+        "UPDATE tasks SET parent_id = NULL
+         WHERE id = ? AND parent_id IN (SELECT id FROM tasks WHERE deleted_at IS NOT NULL)",
+        params![id]
+    )?;
+
+    Ok(restored)
+}
+
+/// 恢复项目以及与它一起被删除的任务和笔记
+pub fn restore_project(conn: &Connection, id: &str) -> Result<usize, rusqlite::Error> {
+    let deleted_at: Option<String> = conn.query_row(
+        "SELECT deleted_at FROM projects WHERE id = ?",
+        params![id],
+        |row| row.get(0)
+    ).optional()?.flatten();
+
+    let deleted_at = match deleted_at {
+        Some(deleted_at) => deleted_at,
+        None => return Ok(0),
+    };
+
+    conn.execute(
+        &format!(
+            "{} UPDATE tasks SET deleted_at = NULL WHERE id IN (SELECT id FROM tree) AND deleted_at = ?2",
+            PROJECT_TASK_TREE
+        ),
+        params![id, deleted_at]
+    )?;
+    conn.execute(
+        "UPDATE notes SET deleted_at = NULL WHERE project_id = ? AND deleted_at = ?",
+        params![id, deleted_at]
+    )?;
+    conn.execute("UPDATE projects SET deleted_at = NULL WHERE id = ?", params![id])
+}
+
+/// 条目所属的项目仍在回收站时返回项目名称
+fn trashed_project_name(conn: &Connection, entity_type: &str, id: &str) -> Result<Option<String>, rusqlite::Error> {
+    let table = match entity_type {
+        "note" => "notes",
+        "task" => "tasks",
+        _ => return Ok(None),
+    };
+    conn.query_row(
+        &format!(
+            "SELECT p.name FROM {} e INNER JOIN projects p ON p.id = e.project_id
+             WHERE e.id = ? AND p.deleted_at IS NOT NULL",
+            table
+        ),
+        params![id],
+        |row| row.get(0)
+    ).optional()
+}
+
+/// 永久删除回收站中的笔记及其标签关联和修订
+pub fn purge_note(conn: &Connection, id: &str) -> Result<usize, rusqlite::Error> {
+    conn.execute("DELETE FROM note_tags WHERE note_id = ?", params![id])?;
+    conn.execute("DELETE FROM note_revisions WHERE note_id = ?", params![id])?;
+    conn.execute("DELETE FROM notes WHERE id = ? AND deleted_at IS NOT NULL", params![id])
+}
+
+/// 删除任务及其标签、看板位置和依赖关系
+fn delete_task_rows(conn: &Connection, task_id: &str) -> Result<usize, rusqlite::Error> {
+    conn.execute("DELETE FROM task_tags WHERE task_id = ?", params![task_id])?;
+    conn.execute("DELETE FROM column_tasks WHERE task_id = ?", params![task_id])?;
+    conn.execute("DELETE FROM task_dependencies WHERE task_id = ?1 OR depends_on_id = ?1", params![task_id])?;
+    conn.execute("DELETE FROM tasks WHERE id = ?", params![task_id])
+}
+
+/// 永久删除回收站中的任务及其子任务
+pub fn purge_task(conn: &Connection, id: &str) -> Result<usize, rusqlite::Error> {
+    let subtree: Vec<String> = {
+        let mut stmt = conn.prepare(
+            "WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM tasks WHERE id = ?1 AND deleted_at IS NOT NULL
+                UNION ALL
+                SELECT t.id FROM tasks t INNER JOIN subtree s ON t.parent_id = s.id
+             )
+             SELECT id FROM subtree"
+        )?;
+        let ids = stmt.query_map(params![id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        ids
+    };
+
+    let mut purged = 0;
+    for task_id in &subtree {
+        purged += delete_task_rows(conn, task_id)?;
+    }
+    Ok(purged)
+}
+
+/// 永久删除回收站中的项目及其在回收站中的任务和笔记，以及看板列、里程碑、状态记录、快照和任务依赖
+pub fn purge_project(conn: &Connection, id: &str) -> Result<usize, rusqlite::Error> {
+    let purged = conn.execute("DELETE FROM projects WHERE id = ? AND deleted_at IS NOT NULL", params![id])?;
+    if purged > 0 {
+        let task_ids: Vec<String> = {
+            let mut stmt = conn.prepare(&format!(
+                "{} SELECT id FROM tasks WHERE id IN (SELECT id FROM tree) AND deleted_at IS NOT NULL",
+                PROJECT_TASK_TREE
+            ))?;
+            let ids = stmt.query_map(params![id], |row| row.get(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            ids
+        };
+        for task_id in &task_ids {
+            delete_task_rows(conn, task_id)?;
+        }
+        let note_ids: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT id FROM notes WHERE project_id = ? AND deleted_at IS NOT NULL")?;
+            let ids = stmt.query_map(params![id], |row| row.get(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            ids
+        };
+        for note_id in &note_ids {
+            purge_note(conn, note_id)?;
+        }
+        conn.execute(
+            "DELETE FROM column_tasks WHERE column_id IN (SELECT id FROM kanban_columns WHERE project_id = ?)",
+            params![id]
+        )?;
+        conn.execute("DELETE FROM kanban_columns WHERE project_id = ?", params![id])?;
+        conn.execute("DELETE FROM milestones WHERE project_id = ?", params![id])?;
+        conn.execute("DELETE FROM project_status_history WHERE project_id = ?", params![id])?;
+        conn.execute("DELETE FROM project_snapshots WHERE project_id = ?", params![id])?;
+    }
+    Ok(purged)
+}
+
+/// 永久删除删除时间早于 `cutoff` 的回收站条目，返回删除的顶层条目数
+pub fn purge_trash_before(conn: &Connection, cutoff: &str) -> Result<usize, rusqlite::Error> {
+    let mut purged = 0;
+
+    let collect = |sql: &str| -> Result<Vec<String>, rusqlite::Error> {
+        let mut stmt = conn.prepare(sql)?;
+        let ids = stmt.query_map(params![cutoff], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    };
+
+    let project_ids = collect("SELECT id FROM projects WHERE deleted_at IS NOT NULL AND deleted_at < ?")?;
+    let task_ids = collect("SELECT id FROM tasks WHERE deleted_at IS NOT NULL AND deleted_at < ?")?;
+    let note_ids = collect("SELECT id FROM notes WHERE deleted_at IS NOT NULL AND deleted_at < ?")?;
+
+    for id in &project_ids {
+        purged += purge_project(conn, id)?;
+    }
+    for id in &task_ids {
+        purged += purge_task(conn, id)?;
+    }
+    for id in &note_ids {
+        purged += purge_note(conn, id)?;
+    }
+
+    Ok(purged)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub fn trash_retention_days(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    Ok(db::get_setting(conn, TRASH_RETENTION_SETTING)?
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS))
+}
+
+/// 按配置的保留天数自动清空回收站，启动时调用
+pub fn purge_expired_trash(conn: &Connection) -> Result<usize, rusqlite::Error> {
+    let days = trash_retention_days(conn)?;
+    if days <= 0 {
+        return Ok(0);
+    }
+    let cutoff = (Utc::now() - Duration::days(days)).to_rfc3339();
+    purge_trash_before(conn, &cutoff)
+}
+
+#[tauri::command]
+pub async fn get_trash(db: State<'_, Database>) -> Result<Vec<TrashItem>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    // 子任务与父任务一起删除时只列出父任务，随项目一起删除的任务和笔记只列出项目
+    let mut stmt = conn.prepare(
+        "SELECT 'note' AS entity_type, n.id, n.title, n.project_id, n.deleted_at
+         FROM notes n
+         WHERE n.deleted_at IS NOT NULL
+           AND NOT EXISTS (
+               SELECT 1 FROM projects pr
+               WHERE pr.id = n.project_id AND pr.deleted_at = n.deleted_at
+           )
+         UNION ALL
+         SELECT 'task', t.id, t.title, t.project_id, t.deleted_at
+         FROM tasks t
+         WHERE t.deleted_at IS NOT NULL
+           AND NOT EXISTS (
+               SELECT 1 FROM tasks p
+               WHERE p.id = t.parent_id AND p.deleted_at = t.deleted_at
+           )
+           AND NOT EXISTS (
+               SELECT 1 FROM projects pr
+               WHERE pr.id = t.project_id AND pr.deleted_at = t.deleted_at
+           )
+         UNION ALL
+         SELECT 'project', id, name, NULL, deleted_at
+         FROM projects WHERE deleted_at IS NOT NULL
+         ORDER BY deleted_at DESC"
+    ).map_err(|e| e.to_string())?;
+
+    let item_iter = stmt.query_map([], |row| {
+        Ok(TrashItem {
+            entity_type: row.get(0)?,
+            id: row.get(1)?,
+            title: row.get(2)?,
+            project_id: row.get(3)?,
+            deleted_at: row.get::<_, String>(4)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(4, "deleted_at".to_string(), rusqlite::types::Type::Text))?,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    let mut items = Vec::new();
+    for item in item_iter {
+        items.push(item.map_err(|e| e.to_string())?);
+    }
+
+    Ok(items)
+}
+
+/// 从回收站恢复条目，恢复操作记录到撤销日志中
+#[tauri::command]
+pub async fn restore_from_trash(entity_type: String, id: String, db: State<'_, Database>) -> Result<(), String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    // 所属项目仍在回收站时需要先恢复项目
+    if let Some(project) = trashed_project_name(&tx, &entity_type, &id).map_err(|e| e.to_string())? {
+        return Err(format!("Project \"{}\" is in trash, restore it first", project));
+    }
+
+    let mut change = ChangeSet::new("restore_from_trash");
+    let restored = match entity_type.as_str() {
+        "note" => {
+            change.track(&tx, "notes", id_key(&id)).map_err(|e| e.to_string())?;
+            restore_note(&tx, &id)
+        }
+        "task" => {
+            let task_ids = task_subtree_ids(&tx, &id).map_err(|e| e.to_string())?;
+            change.track_ids(&tx, "tasks", &task_ids).map_err(|e| e.to_string())?;
+            restore_task(&tx, &id)
+        }
+        "project" => {
+            let deleted_at: Option<String> = tx.query_row(
+                "SELECT deleted_at FROM projects WHERE id = ?",
+                params![id],
+                |row| row.get(0)
+            ).optional().map_err(|e| e.to_string())?.flatten();
+            if let Some(deleted_at) = deleted_at.as_deref() {
+                let task_ids = project_task_ids(&tx, &id, Some(deleted_at)).map_err(|e| e.to_string())?;
+                let note_ids = project_note_ids(&tx, &id, Some(deleted_at)).map_err(|e| e.to_string())?;
+                change.track_ids(&tx, "tasks", &task_ids).map_err(|e| e.to_string())?;
+                change.track_ids(&tx, "notes", &note_ids).map_err(|e| e.to_string())?;
+            }
+            change.track(&tx, "projects", id_key(&id)).map_err(|e| e.to_string())?;
+            restore_project(&tx, &id)
+        }
+        other => return Err(format!("Unknown entity type: {}", other)),
+    }.map_err(|e| e.to_string())?;
+
+    if restored == 0 {
+        return Err(format!("{} {} is not in trash", entity_type, id));
+    }
+    change.commit(&tx).map_err(|e| e.to_string())?;
+    
+    let summary = format!("{} \"{}\" restored from trash", capitalize(&entity_type), entity_title(&tx, &entity_type, &id).map_err(|e| e.to_string())?);
+    log_activity(&tx, &entity_type, &id, "restored", &summary).map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// 永久删除回收站中的条目。关联的修订、看板等数据不在撤销日志中，永久删除无法撤销
+#[tauri::command]
+pub async fn purge_from_trash(entity_type: String, id: String, db: State<'_, Database>) -> Result<(), String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let title = entity_title(&tx, &entity_type, &id).map_err(|e| e.to_string())?;
+
+    let purged = match entity_type.as_str() {
+        "note" => purge_note(&tx, &id),
+        "task" => purge_task(&tx, &id),
+        "project" => purge_project(&tx, &id),
+        other => return Err(format!("Unknown entity type: {}", other)),
+    }.map_err(|e| e.to_string())?;
+
+    if purged == 0 {
+        return Err(format!("{} {} is not in trash", entity_type, id));
+    }
+
+    let summary = format!("{} \"{}\" permanently deleted", capitalize(&entity_type), title);
+    log_activity(&tx, &entity_type, &id, "purged", &summary).map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn empty_trash(db: State<'_, Database>) -> Result<usize, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    // 删除时间早于当前时刻的所有条目
+    let purged = purge_trash_before(&tx, &Utc::now().to_rfc3339()).map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(purged)
+}
+
+#[tauri::command]
+pub async fn get_trash_retention_days(db: State<'_, Database>) -> Result<i64, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    trash_retention_days(&conn).map_err(|e| e.to_string())
+}
+
+/// 设置回收站自动清空天数，0 表示不自动清空
+#[tauri::command]
+pub async fn set_trash_retention_days(days: i64, db: State<'_, Database>) -> Result<(), String> {
+    if days < 0 {
+        return Err("Retention days must not be negative".to_string());
+    }
+
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    db::set_setting(&conn, TRASH_RETENTION_SETTING, &days.to_string()).map_err(|e| e.to_string())
+}
@@ -0,0 +1,508 @@
+use crate::models::{Note, VaultConflict, VaultExportReport, VaultImportReport};
+use crate::db::Database;
+use crate::commands::activity::log_activity;
+use crate::commands::projects::find_or_create_project;
+use crate::commands::revisions::save_note_revision;
+use crate::commands::tags::{find_or_create_tag, link_tag_to_note};
+use tauri::State;
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Markdown 文件头部 YAML front matter 中与笔记相关的字段
+#[derive(Debug, Clone, Default)]
+pub struct FrontMatter {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub project: Option<String>,
+    pub project_id: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// 单个文件导入的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportOutcome {
+    Created(String),
+    Updated(String),
+    Unchanged(String),
+    Conflict(Option<String>, String),
+}
+
+/// JSON 字符串同时也是合法的 YAML 双引号字符串
+fn yaml_quote(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+}
+
+fn yaml_unquote(raw: &str) -> String {
+    let raw = raw.trim();
+    if raw.len() >= 2 && raw.starts_with('"') && raw.ends_with('"') {
+        serde_json::from_str(raw).unwrap_or_else(|_| raw[1..raw.len() - 1].to_string())
+    } else if raw.len() >= 2 && raw.starts_with('\'') && raw.ends_with('\'') {
+        raw[1..raw.len() - 1].replace("''", "'")
+    } else {
+        raw.to_string()
+    }
+}
+
+/// 解析 `[a, "b c"]` 形式的行内列表
+fn parse_flow_list(raw: &str) -> Vec<String> {
+    let inner = raw.trim().trim_start_matches('[').trim_end_matches(']');
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+
+    for c in inner.chars() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (Some(q), c) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (None, ',') => {
+                items.push(yaml_unquote(&current));
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    items.push(yaml_unquote(&current));
+
+    items.into_iter().filter(|item| !item.is_empty()).collect()
+}
+
+/// 将 front matter 与笔记正文拆开；没有 front matter 时整个文件都是正文
+pub fn parse_markdown_file(text: &str) -> (FrontMatter, String) {
+    let mut front_matter = FrontMatter::default();
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+
+    let rest = match text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) {
+        Some(rest) => rest,
+        None => return (front_matter, text.to_string()),
+    };
+
+    let (header, body) = match rest.find("\n---") {
+        Some(end) => {
+            let body = &rest[end + 4..];
+            let body = body.strip_prefix("\r\n").or_else(|| body.strip_prefix('\n')).unwrap_or(body);
+            // 导出时在 front matter 后留有一个空行
+            let body = body.strip_prefix("\r\n").or_else(|| body.strip_prefix('\n')).unwrap_or(body);
+            (&rest[..end], body)
+        }
+        None => return (front_matter, text.to_string()),
+    };
+
+    let mut list_key: Option<String> = None;
+    let mut list_items: Vec<String> = Vec::new();
+
+    for line in header.lines() {
+        if let Some(item) = line.trim_start().strip_prefix("- ") {
+            if list_key.is_some() {
+                list_items.push(yaml_unquote(item));
+            }
+            continue;
+        }
+
+        if let Some(key) = list_key.take() {
+            if key == "tags" {
+                front_matter.tags = Some(std::mem::take(&mut list_items));
+            }
+        }
+
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+
+        if value.is_empty() {
+            list_key = Some(key.to_string());
+            list_items.clear();
+            continue;
+        }
+
+        match key {
+            "id" => front_matter.id = Some(yaml_unquote(value)),
+            "title" => front_matter.title = Some(yaml_unquote(value)),
+            "project" => front_matter.project = Some(yaml_unquote(value)),
+            "project_id" => front_matter.project_id = Some(yaml_unquote(value)),
+            "tags" => front_matter.tags = Some(if value.starts_with('[') {
+                parse_flow_list(value)
+            } else {
+                value.split(',').map(yaml_unquote).filter(|tag| !tag.is_empty()).collect()
+            }),
+            "created_at" | "created" => front_matter.created_at = yaml_unquote(value).parse().ok(),
+            "updated_at" | "updated" => front_matter.updated_at = yaml_unquote(value).parse().ok(),
+            _ => {}
+        }
+    }
+
+    if let Some(key) = list_key {
+        if key == "tags" {
+            front_matter.tags = Some(list_items);
+        }
+    }
+
+    (front_matter, body.to_string())
+}
+
+/// 生成带 front matter 的 Markdown 文件内容
+pub fn render_markdown_file(note: &Note, project: Option<&str>, tags: &[String]) -> String {
+    let mut output = String::from("---\n");
+    output.push_str(&format!("id: {}\n", note.id));
+    output.push_str(&format!("title: {}\n", yaml_quote(&note.title)));
+    if let Some(project) = project {
+        output.push_str(&format!("project: {}\n", yaml_quote(project)));
+    }
+    if let Some(project_id) = &note.project_id {
+        output.push_str(&format!("project_id: {}\n", project_id));
+    }
+    let tags: Vec<String> = tags.iter().map(|tag| yaml_quote(tag)).collect();
+    output.push_str(&format!("tags: [{}]\n", tags.join(", ")));
+    output.push_str(&format!("created_at: {}\n", note.created_at.to_rfc3339()));
+    output.push_str(&format!("updated_at: {}\n", note.updated_at.to_rfc3339()));
+    output.push_str("---\n\n");
+    output.push_str(&note.content);
+    output
+}
+
+/// 将标题转换为可用作文件名的字符串
+pub fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if c.is_control() || "/\\:*?\"<>|".contains(c) { '_' } else { c })
+        .collect();
+    let cleaned = cleaned.trim().trim_matches('.').trim();
+    let truncated: String = cleaned.chars().take(100).collect();
+
+    if truncated.is_empty() {
+        "untitled".to_string()
+    } else {
+        truncated
+    }
+}
+
+/// 笔记 id 的前 8 个字符，用于区分重名文件。按字符截取，导入的 id 可能包含多字节字符
+pub fn short_id(id: &str) -> String {
+    id.chars().take(8).collect()
+}
+
+/// 判断路径是否重名时使用的键。macOS 和 Windows 默认的文件系统不区分大小写，统一转为小写比较
+pub fn path_case_key(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+/// 计算笔记在目录中的路径：项目笔记放在以项目命名的子目录，重名（不区分大小写）时追加 id 前缀
+pub fn note_file_path(root: &Path, project: Option<&str>, note: &Note, used: &mut HashSet<String>) -> PathBuf {
+    let dir = match project {
+        Some(project) => root.join(sanitize_file_name(project)),
+        None => root.to_path_buf(),
+    };
+
+    let base = sanitize_file_name(&note.title);
+    let mut path = dir.join(format!("{}.md", base));
+    if used.contains(&path_case_key(&path)) {
+        path = dir.join(format!("{} ({}).md", base, short_id(&note.id)));
+    }
+    used.insert(path_case_key(&path));
+    path
+}
+
+/// 读取所有未删除的笔记及其项目名称和标签
+pub fn load_vault_notes(conn: &Connection) -> Result<Vec<(Note, Option<String>, Vec<String>)>, rusqlite::Error> {
+    query_vault_notes(conn, None)
+}
+
+/// 读取单篇未删除的笔记及其项目名称和标签
+pub fn load_vault_note(conn: &Connection, id: &str) -> Result<Option<(Note, Option<String>, Vec<String>)>, rusqlite::Error> {
+    Ok(query_vault_notes(conn, Some(id))?.into_iter().next())
+}
+
+fn query_vault_notes(conn: &Connection, id: Option<&str>) -> Result<Vec<(Note, Option<String>, Vec<String>)>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT n.id, n.title, n.content, n.created_at, n.updated_at, n.project_id, p.name
+         FROM notes n
+         LEFT JOIN projects p ON p.id = n.project_id AND p.deleted_at IS NULL
+         WHERE n.deleted_at IS NULL AND (?1 IS NULL OR n.id = ?1)
+         ORDER BY n.created_at ASC"
+    )?;
+
+    let rows = stmt.query_map(params![id], |row| {
+        Ok((
+            Note {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                content: row.get(2)?,
+                created_at: row.get::<_, String>(3)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(3, "created_at".to_string(), rusqlite::types::Type::Text))?,
+                updated_at: row.get::<_, String>(4)?.parse().map_err(|_| rusqlite::Error::InvalidColumnType(4, "updated_at".to_string(), rusqlite::types::Type::Text))?,
+                project_id: row.get(5)?,
+            },
+            row.get::<_, Option<String>>(6)?,
+        ))
+    })?.collect::<Result<Vec<_>, _>>()?;
+
+    let mut tag_stmt = conn.prepare(
+        "SELECT t.name FROM tags t
+         INNER JOIN note_tags nt ON t.id = nt.tag_id
+         WHERE nt.note_id = ?
+         ORDER BY t.name ASC"
+    )?;
+
+    let mut notes = Vec::new();
+    for (note, project) in rows {
+        let tags = tag_stmt.query_map(params![note.id], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        notes.push((note, project, tags));
+    }
+
+    Ok(notes)
+}
+
+/// 递归收集目录中的 Markdown 文件，跳过隐藏目录（如 .git、.obsidian）
+pub fn collect_markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let hidden = path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.'));
+
+        if hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect_markdown_files(&path, files)?;
+        } else if path.extension().and_then(|ext| ext.to_str()) == Some("md") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// 按 front matter 的 id 创建或更新笔记。
+///
+/// 应用内的修改时间晚于文件记录的 `updated_at` 时视为冲突，除非 `overwrite` 为 true。
+/// 未写明项目时，使用文件所在的子目录名作为项目。
+pub fn import_note_file(
+    conn: &Connection,
+    root: &Path,
+    path: &Path,
+    text: &str,
+    overwrite: bool,
+) -> Result<ImportOutcome, String> {
+    let (front_matter, body) = parse_markdown_file(text);
+    import_parsed_note(conn, root, path, front_matter, body, overwrite)
+}
+
+/// 与 `import_note_file` 相同，但使用已解析的 front matter
+pub fn import_parsed_note(
+    conn: &Connection,
+    root: &Path,
+    path: &Path,
+    front_matter: FrontMatter,
+    body: String,
+    overwrite: bool,
+) -> Result<ImportOutcome, String> {
+    let title = front_matter.title.clone().unwrap_or_else(|| {
+        path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("untitled").to_string()
+    });
+
+    let file_modified: DateTime<Utc> = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map(DateTime::<Utc>::from)
+        .unwrap_or_else(|_| Utc::now());
+
+    let folder = path.parent()
+        .filter(|parent| *parent != root)
+        .and_then(|parent| parent.file_name())
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string());
+
+    let known_project_id: Option<String> = match &front_matter.project_id {
+        Some(project_id) => conn.query_row(
+            "SELECT id FROM projects WHERE id = ? AND deleted_at IS NULL",
+            params![project_id],
+            |row| row.get(0)
+        ).optional().map_err(|e| e.to_string())?,
+        None => None,
+    };
+
+    let project_id = match (known_project_id, front_matter.project.clone().or(folder)) {
+        (Some(project_id), _) => Some(project_id),
+        (None, Some(name)) => Some(find_or_create_project(conn, &name).map_err(|e| e.to_string())?),
+        (None, None) => None,
+    };
+
+    let existing: Option<(String, String, Option<String>, String, Option<String>)> = match &front_matter.id {
+        Some(id) => conn.query_row(
+            "SELECT title, content, project_id, updated_at, deleted_at FROM notes WHERE id = ?",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        ).optional().map_err(|e| e.to_string())?,
+        None => None,
+    };
+
+    let now = Utc::now();
+
+    let outcome = match existing {
+        Some((_, _, _, _, Some(_))) => {
+            return Ok(ImportOutcome::Conflict(front_matter.id, "Note is in trash".to_string()));
+        }
+        Some((db_title, db_content, db_project_id, db_updated_at, None)) => {
+            let id = front_matter.id.clone().unwrap_or_default();
+
+            if db_title == title && db_content == body && db_project_id == project_id {
+                ImportOutcome::Unchanged(id)
+            } else {
+                let db_updated_at: DateTime<Utc> = db_updated_at.parse().unwrap_or(now);
+                let file_updated_at = front_matter.updated_at.unwrap_or(file_modified);
+
+                if !overwrite && db_updated_at > file_updated_at {
+                    return Ok(ImportOutcome::Conflict(
+                        Some(id),
+                        "Note was modified in the app after this file was written".to_string()
+                    ));
+                }
+
+                save_note_revision(conn, &id, true).map_err(|e| e.to_string())?;
+                conn.execute(
+                    "UPDATE notes SET title = ?, content = ?, project_id = ?, updated_at = ? WHERE id = ?",
+                    params![title, body, project_id, now.to_rfc3339(), id]
+                ).map_err(|e| e.to_string())?;
+
+                log_activity(conn, "note", &id, "imported", &format!("Note \"{}\" updated from Markdown", title))
+                    .map_err(|e| e.to_string())?;
+
+                ImportOutcome::Updated(id)
+            }
+        }
+        None => {
+            let id = front_matter.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+            let created_at = front_matter.created_at.unwrap_or(file_modified);
+            let updated_at = front_matter.updated_at.unwrap_or(file_modified);
+
+            conn.execute(
+                "INSERT INTO notes (id, title, content, created_at, updated_at, project_id)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+                params![id, title, body, created_at.to_rfc3339(), updated_at.to_rfc3339(), project_id]
+            ).map_err(|e| e.to_string())?;
+
+            log_activity(conn, "note", &id, "imported", &format!("Note \"{}\" imported from Markdown", title))
+                .map_err(|e| e.to_string())?;
+
+            ImportOutcome::Created(id)
+        }
+    };
+
+    // front matter 中写明了标签时，以文件中的标签为准
+    if let (Some(tags), ImportOutcome::Created(id) | ImportOutcome::Updated(id) | ImportOutcome::Unchanged(id)) = (&front_matter.tags, &outcome) {
+        let mut tag_ids = Vec::new();
+        for tag in tags {
+            let tag_id = find_or_create_tag(conn, tag).map_err(|e| e.to_string())?;
+            link_tag_to_note(conn, id, &tag_id).map_err(|e| e.to_string())?;
+            tag_ids.push(tag_id);
+        }
+
+        let placeholders = vec!["?"; tag_ids.len()].join(", ");
+        let query = if tag_ids.is_empty() {
+            "DELETE FROM note_tags WHERE note_id = ?".to_string()
+        } else {
+            format!("DELETE FROM note_tags WHERE note_id = ? AND tag_id NOT IN ({})", placeholders)
+        };
+        let mut values = vec![id.clone()];
+        values.extend(tag_ids);
+        conn.execute(&query, rusqlite::params_from_iter(values)).map_err(|e| e.to_string())?;
+    }
+
+    Ok(outcome)
+}
+
+/// 将所有笔记导出为 Markdown 文件，返回写入的文件路径与笔记 id
+pub fn export_vault(conn: &Connection, root: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    let notes = load_vault_notes(conn).map_err(|e| e.to_string())?;
+    let mut used = HashSet::new();
+    let mut written = Vec::new();
+
+    for (note, project, tags) in notes {
+        let path = note_file_path(root, project.as_deref(), &note, &mut used);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&path, render_markdown_file(&note, project.as_deref(), &tags)).map_err(|e| e.to_string())?;
+        written.push((path, note.id));
+    }
+
+    Ok(written)
+}
+
+#[tauri::command]
+pub async fn export_markdown_vault(directory: String, db: State<'_, Database>) -> Result<VaultExportReport, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let written = export_vault(&conn, Path::new(&directory))?;
+
+    Ok(VaultExportReport {
+        directory,
+        exported: written.len(),
+    })
+}
+
+/// 从目录导入 Markdown 文件。冲突的文件会被跳过并在报告中列出，
+/// `overwrite` 为 true 时以文件内容为准。
+#[tauri::command]
+pub async fn import_markdown_vault(
+    directory: String,
+    overwrite: Option<bool>,
+    db: State<'_, Database>
+) -> Result<VaultImportReport, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let root = PathBuf::from(&directory);
+
+    let mut files = Vec::new();
+    collect_markdown_files(&root, &mut files)?;
+
+    let mut report = VaultImportReport {
+        created: 0,
+        updated: 0,
+        unchanged: 0,
+        conflicts: Vec::new(),
+    };
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    for path in files {
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                report.conflicts.push(VaultConflict {
+                    path: path.to_string_lossy().to_string(),
+                    note_id: None,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        match import_note_file(&tx, &root, &path, &text, overwrite.unwrap_or(false))? {
+            ImportOutcome::Created(_) => report.created += 1,
+            ImportOutcome::Updated(_) => report.updated += 1,
+            ImportOutcome::Unchanged(_) => report.unchanged += 1,
+            ImportOutcome::Conflict(note_id, reason) => report.conflicts.push(VaultConflict {
+                path: path.to_string_lossy().to_string(),
+                note_id,
+                reason,
+            }),
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(report)
+}
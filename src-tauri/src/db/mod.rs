@@ -1,6 +1,7 @@
 use rusqlite::{Connection, Result, params};
 use r2d2::{Pool, PooledConnection};
 use r2d2_sqlite::SqliteConnectionManager;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use anyhow::Error;
 
@@ -9,6 +10,7 @@ pub type DbConnection = PooledConnection<SqliteConnectionManager>;
 
 pub struct Database {
     pub pool: DbPool,
+    pub path: PathBuf,
 }
 
 impl Database {
@@ -20,7 +22,12 @@ impl Database {
         let conn = pool.get()?;
         init_database(&conn)?;
         
-        Ok(Database { pool })
+        Ok(Database { pool, path: PathBuf::from(database_url) })
+    }
+
+    /// 自动备份所在目录，位于数据库文件旁的 backups 子目录
+    pub fn backup_dir(&self) -> PathBuf {
+        self.path.parent().unwrap_or_else(|| Path::new(".")).join("backups")
     }
 
     pub fn get_connection(&self) -> Result<DbConnection, Error> {
@@ -37,7 +44,8 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
             content TEXT NOT NULL,
             created_at TEXT NOT NULL,
             updated_at TEXT NOT NULL,
-            project_id TEXT
+            project_id TEXT,
+            deleted_at TEXT
         )",
         [],
     )?;
@@ -79,6 +87,12 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
             project_id TEXT,
             parent_id TEXT,
             position INTEGER NOT NULL,
+            deleted_at TEXT,
+            recurrence TEXT, -- iCalendar RRULE，例如 FREQ=WEEKLY;BYDAY=MO
+            milestone_id TEXT,
+            start_date TEXT,
+            estimate_minutes INTEGER, -- 预计用时（分钟）
+            completed_at TEXT,
             FOREIGN KEY (parent_id) REFERENCES tasks(id) ON DELETE CASCADE
         )",
         [],
@@ -94,6 +108,11 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
             is_completed INTEGER NOT NULL DEFAULT 0,
             task_id TEXT,
             notes TEXT,
+            phase TEXT NOT NULL DEFAULT 'work', -- work / short_break / long_break
+            paused_seconds INTEGER NOT NULL DEFAULT 0,
+            abandoned INTEGER NOT NULL DEFAULT 0,
+            abandon_reason TEXT,
+            heartbeat_at TEXT, -- 进行中会话最近一次心跳，用于崩溃后恢复
             FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL
         )",
         [],
@@ -105,11 +124,12 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
             id TEXT PRIMARY KEY,
             name TEXT NOT NULL,
             description TEXT,
-            status TEXT NOT NULL DEFAULT 'active',
+            status TEXT NOT NULL DEFAULT 'active', -- planning / active / on_hold / completed / archived
             created_at TEXT NOT NULL,
             updated_at TEXT NOT NULL,
             due_date TEXT,
-            color TEXT NOT NULL DEFAULT '#3b82f6'
+            color TEXT NOT NULL DEFAULT '#3b82f6',
+            deleted_at TEXT
         )",
         [],
     )?;
@@ -139,6 +159,242 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
         [],
     )?;
 
+    // 旧版本数据库升级：补充软删除字段
+    add_column_if_missing(conn, "notes", "deleted_at", "TEXT")?;
+    add_column_if_missing(conn, "tasks", "deleted_at", "TEXT")?;
+    add_column_if_missing(conn, "projects", "deleted_at", "TEXT")?;
+
+    // 旧版本数据库升级：任务重复规则
+    add_column_if_missing(conn, "tasks", "recurrence", "TEXT")?;
+
+    // 旧版本数据库升级：番茄钟阶段类型，区分专注与休息
+    add_column_if_missing(conn, "pomodoro_sessions", "phase", "TEXT NOT NULL DEFAULT 'work'")?;
+
+    // 旧版本数据库升级：暂停时长与放弃记录
+    add_column_if_missing(conn, "pomodoro_sessions", "paused_seconds", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "pomodoro_sessions", "abandoned", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "pomodoro_sessions", "abandon_reason", "TEXT")?;
+
+    // 旧版本数据库升级：进行中会话的心跳时间
+    add_column_if_missing(conn, "pomodoro_sessions", "heartbeat_at", "TEXT")?;
+
+    // 旧版本数据库升级：任务所属里程碑
+    add_column_if_missing(conn, "tasks", "milestone_id", "TEXT")?;
+
+    // 旧版本数据库升级：任务开始日期和预计用时，用于项目时间线
+    add_column_if_missing(conn, "tasks", "start_date", "TEXT")?;
+    add_column_if_missing(conn, "tasks", "estimate_minutes", "INTEGER")?;
+
+    // 旧版本数据库升级：项目状态原为自由文本，无法识别的状态归为 active
+    conn.execute(
+        "UPDATE projects SET status = 'active'
+         WHERE status NOT IN ('planning', 'active', 'on_hold', 'completed', 'archived')",
+        [],
+    )?;
+
+    // 创建应用设置表（键值对）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // 创建笔记修订历史表
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS note_revisions (
+            id TEXT PRIMARY KEY,
+            note_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // 创建撤销/重做日志表
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS undo_journal (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            label TEXT NOT NULL,
+            changes TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            undone INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+
+    // 创建活动日志表（只追加）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS activity_log (
+            id TEXT PRIMARY KEY,
+            entity_type TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            project_id TEXT,
+            action TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // 创建目录同步状态表：记录每篇笔记上次同步时的文件路径和内容哈希
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_files (
+            note_id TEXT PRIMARY KEY,
+            path TEXT NOT NULL,
+            content_hash TEXT NOT NULL,
+            synced_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // 创建任务-标签关联表（todo.txt 的 @上下文）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS task_tags (
+            task_id TEXT NOT NULL,
+            tag_id TEXT NOT NULL,
+            PRIMARY KEY (task_id, tag_id),
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // 创建外部日历表：导入的 .ics 文件及其事件，事件只读
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS calendar_sources (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            path TEXT NOT NULL UNIQUE,
+            subscribed INTEGER NOT NULL DEFAULT 0,
+            content_hash TEXT,
+            imported_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS calendar_events (
+            id TEXT PRIMARY KEY,
+            source_id TEXT NOT NULL,
+            uid TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            description TEXT,
+            location TEXT,
+            starts_at TEXT NOT NULL,
+            ends_at TEXT NOT NULL,
+            all_day INTEGER NOT NULL DEFAULT 0,
+            transparent INTEGER NOT NULL DEFAULT 0,
+            recurrence TEXT,
+            exdates TEXT,
+            FOREIGN KEY (source_id) REFERENCES calendar_sources(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // 创建番茄钟中断记录表：暂停以及内部、外部打断
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pomodoro_interruptions (
+            id TEXT PRIMARY KEY,
+            session_id TEXT NOT NULL,
+            kind TEXT NOT NULL, -- pause / internal / external
+            reason TEXT,
+            started_at TEXT NOT NULL,
+            ended_at TEXT,
+            duration INTEGER NOT NULL DEFAULT 0, -- 暂停的秒数，未暂停的打断为 0
+            FOREIGN KEY (session_id) REFERENCES pomodoro_sessions(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // 创建项目状态变更记录表
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS project_status_history (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            from_status TEXT, -- 创建项目时为空
+            to_status TEXT NOT NULL,
+            note TEXT,
+            changed_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // 创建项目里程碑表：带日期的检查点，任务通过 milestone_id 关联
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS milestones (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            due_date TEXT,
+            completed_at TEXT,
+            position INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // 创建项目模板表：content 为 JSON，日期保存为相对开始日期的偏移
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS project_templates (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            description TEXT,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // 创建任务依赖表：task_id 在 depends_on_id 完成后才能开始
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS task_dependencies (
+            task_id TEXT NOT NULL,
+            depends_on_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (task_id, depends_on_id),
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+            FOREIGN KEY (depends_on_id) REFERENCES tasks(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // 旧版本数据库升级：任务完成时间，已完成的任务取活动日志中最后一次完成的时间
+    // 需要在活动日志表创建之后执行
+    if add_column_if_missing(conn, "tasks", "completed_at", "TEXT")? {
+        conn.execute(
+            "UPDATE tasks SET completed_at = COALESCE(
+                (SELECT MAX(a.created_at) FROM activity_log a
+                 WHERE a.entity_type = 'task' AND a.entity_id = tasks.id AND a.action = 'completed'),
+                updated_at
+             )
+             WHERE is_completed = 1",
+            [],
+        )?;
+    }
+
+    // 创建项目每日快照表：记录当天结束时（或最后一次记录时）的任务数，用于燃尽图
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS project_snapshots (
+            project_id TEXT NOT NULL,
+            date TEXT NOT NULL, -- 本地日期 YYYY-MM-DD
+            total_tasks INTEGER NOT NULL,
+            completed_tasks INTEGER NOT NULL,
+            recorded_at TEXT NOT NULL,
+            PRIMARY KEY (project_id, date),
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
     // 创建索引以提高查询性能
     conn.execute("CREATE INDEX IF NOT EXISTS idx_notes_project_id ON notes(project_id)", [])?;
     conn.execute("CREATE INDEX IF NOT EXISTS idx_notes_created_at ON notes(created_at)", [])?;
@@ -147,6 +403,53 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
     conn.execute("CREATE INDEX IF NOT EXISTS idx_tasks_position ON tasks(position)", [])?;
     conn.execute("CREATE INDEX IF NOT EXISTS idx_pomodoro_task_id ON pomodoro_sessions(task_id)", [])?;
     conn.execute("CREATE INDEX IF NOT EXISTS idx_kanban_columns_project_id ON kanban_columns(project_id)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_notes_deleted_at ON notes(deleted_at)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_tasks_deleted_at ON tasks(deleted_at)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_projects_deleted_at ON projects(deleted_at)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_note_revisions_note_id ON note_revisions(note_id, created_at)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_activity_log_entity ON activity_log(entity_type, entity_id)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_activity_log_project_id ON activity_log(project_id)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_activity_log_created_at ON activity_log(created_at)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_task_tags_tag_id ON task_tags(tag_id)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_calendar_events_source_id ON calendar_events(source_id)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_calendar_events_starts_at ON calendar_events(starts_at)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_pomodoro_interruptions_session_id ON pomodoro_interruptions(session_id)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_project_status_history_project_id ON project_status_history(project_id, changed_at)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_milestones_project_id ON milestones(project_id)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_tasks_milestone_id ON tasks(milestone_id)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_task_dependencies_depends_on_id ON task_dependencies(depends_on_id)", [])?;
 
+    Ok(())
+}
+
+/// 表中不存在该列时追加（用于升级旧版本创建的数据库），返回是否新添加了该列
+pub fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<bool, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+
+    Ok(!exists)
+}
+
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>, rusqlite::Error> {
+    match conn.query_row("SELECT value FROM settings WHERE key = ?", params![key], |row| row.get(0)) {
+        Ok(value) => Ok(Some(value)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?, ?)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
     Ok(())
 }
\ No newline at end of file
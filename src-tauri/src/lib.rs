@@ -3,6 +3,9 @@ mod models;
 mod commands;
 
 use db::Database;
+use commands::sync::FolderSync;
+use commands::pomodoro_timer::PomodoroTimer;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -16,9 +19,48 @@ pub fn run() {
         )?;
       }
       
-      // 初始化数据库
-      let database = Database::new().expect("Failed to initialize database");
+      // 初始化数据库，数据库文件位于应用数据目录
+      let data_dir = app.path().app_data_dir()?;
+      std::fs::create_dir_all(&data_dir)?;
+      let database_path = data_dir.join("notebook.db");
+      let database = Database::new(&database_path.to_string_lossy()).expect("Failed to initialize database");
+      
+      // 按保留天数自动清空回收站
+      if let Ok(conn) = database.get_connection() {
+        if let Err(e) = commands::trash::purge_expired_trash(&conn) {
+          log::warn!("Failed to purge expired trash: {}", e);
+        }
+      }
+      
       app.manage(database);
+      app.manage(FolderSync::default());
+      app.manage(PomodoroTimer::default());
+      
+      // 恢复上次开启的 Markdown 目录同步
+      if let Err(e) = commands::sync::resume_folder_sync(&app.state::<Database>(), &app.state::<FolderSync>()) {
+        log::warn!("Failed to resume folder sync: {}", e);
+      }
+      
+      // 启动定时自动备份
+      commands::backup::spawn_auto_backup(app.handle().clone());
+      
+      // 启动 todo.txt 同步，未开启同步模式时不做任何事
+      commands::todotxt::spawn_todo_txt_sync(app.handle().clone());
+      
+      // 定期刷新日历订阅文件
+      commands::ics::spawn_ics_subscription(app.handle().clone());
+      
+      // 定期重新导入订阅的外部日历
+      commands::calendar::spawn_calendar_refresh(app.handle().clone());
+      
+      // 恢复番茄钟计时状态并开始每秒计时
+      commands::pomodoro_timer::spawn_pomodoro_timer(app.handle().clone());
+      
+      // 检查任务提醒，专注模式静默期间延后到休息时发送
+      commands::reminders::spawn_reminder_scheduler(app.handle().clone());
+      
+      // 定期记录项目任务数快照，用于燃尽图和速度统计
+      commands::project_reports::spawn_project_snapshots(app.handle().clone());
       
       Ok(())
     })
@@ -38,14 +80,36 @@ pub fn run() {
       commands::delete_task,
       commands::update_task_position,
       commands::get_tasks_by_project,
+      commands::get_pending_reminders,
       
       // 番茄钟相关命令
       commands::get_all_pomodoro_sessions,
       commands::start_pomodoro_session,
+      commands::heartbeat_pomodoro_session,
       commands::complete_pomodoro_session,
       commands::cancel_pomodoro_session,
+      commands::get_session_interruptions,
+      commands::get_dangling_pomodoro_sessions,
+      commands::recover_pomodoro_session,
+      commands::recover_all_pomodoro_sessions,
       commands::get_pomodoro_stats,
       commands::get_sessions_by_task,
+      commands::get_focus_analytics,
+      commands::get_focus_goal_progress,
+      commands::get_focus_heatmap,
+      commands::set_focus_daily_goal,
+      commands::get_pomodoro_timer,
+      commands::start_pomodoro_timer,
+      commands::pause_pomodoro_timer,
+      commands::log_pomodoro_interruption,
+      commands::resume_pomodoro_timer,
+      commands::stop_pomodoro_timer,
+      commands::skip_pomodoro_phase,
+      commands::get_pomodoro_settings,
+      commands::update_pomodoro_settings,
+      commands::get_focus_mode_settings,
+      commands::get_focus_mode_state,
+      commands::update_focus_mode_settings,
       
       // 项目相关命令
       commands::get_all_projects,
@@ -56,6 +120,31 @@ pub fn run() {
       commands::get_project_stats,
       commands::archive_project,
       commands::unarchive_project,
+      commands::set_project_status,
+      commands::get_project_status_history,
+      
+      // 里程碑相关命令
+      commands::get_project_milestones,
+      commands::create_milestone,
+      commands::update_milestone,
+      commands::delete_milestone,
+      commands::set_task_milestone,
+      
+      // 项目模板相关命令
+      commands::save_project_as_template,
+      commands::get_project_templates,
+      commands::delete_project_template,
+      commands::create_project_from_template,
+      commands::duplicate_project,
+      
+      // 项目时间线相关命令
+      commands::get_project_timeline,
+      commands::apply_project_schedule,
+      commands::add_task_dependency,
+      commands::remove_task_dependency,
+      
+      // 项目报表相关命令
+      commands::get_project_burndown,
       
       // 标签相关命令
       commands::get_all_tags,
@@ -66,6 +155,92 @@ pub fn run() {
       commands::remove_tag_from_note,
       commands::get_tags_for_note,
       commands::get_notes_by_tag,
+      
+      // 笔记修订历史相关命令
+      commands::get_note_revisions,
+      commands::get_note_revision,
+      commands::diff_note_revisions,
+      commands::restore_note_revision,
+      commands::prune_note_revisions,
+      
+      // 回收站相关命令
+      commands::get_trash,
+      commands::restore_from_trash,
+      commands::purge_from_trash,
+      commands::empty_trash,
+      commands::get_trash_retention_days,
+      commands::set_trash_retention_days,
+      
+      // 撤销/重做相关命令
+      commands::undo,
+      commands::redo,
+      commands::get_undo_history,
+      
+      // 活动日志相关命令
+      commands::get_activity_log,
+      commands::export_activity_log,
+      
+      // 备份与恢复相关命令
+      commands::create_backup,
+      commands::list_backups,
+      commands::restore_backup,
+      commands::get_backup_settings,
+      commands::set_backup_settings,
+      
+      // Markdown 导入导出相关命令
+      commands::export_markdown_vault,
+      commands::import_markdown_vault,
+      
+      // Markdown 目录同步相关命令
+      commands::enable_folder_sync,
+      commands::disable_folder_sync,
+      commands::sync_folder_now,
+      commands::get_folder_sync_status,
+      
+      // JSON 数据导入导出相关命令
+      commands::export_data_json,
+      commands::import_data_json,
+      
+      // 第三方应用导入相关命令
+      commands::import_evernote,
+      commands::import_notion,
+      commands::import_todoist,
+      
+      // todo.txt 导入导出与同步相关命令
+      commands::export_todo_txt,
+      commands::import_todo_txt,
+      commands::enable_todo_txt_sync,
+      commands::disable_todo_txt_sync,
+      commands::sync_todo_txt_now,
+      
+      // iCalendar 导出相关命令
+      commands::export_ics,
+      commands::set_ics_subscription,
+      commands::set_task_recurrence,
+      
+      // 外部日历与议程相关命令
+      commands::import_calendar,
+      commands::get_calendars,
+      commands::refresh_calendars,
+      commands::remove_calendar,
+      commands::get_calendar_events,
+      commands::get_agenda,
+      commands::set_agenda_work_hours,
+      
+      // HTML 导出相关命令
+      commands::render_note_html,
+      commands::export_note_html,
+      commands::export_notes_html,
+      
+      // CSV 导出相关命令
+      commands::get_csv_columns,
+      commands::export_tasks_csv,
+      commands::export_project_stats_csv,
+      commands::export_pomodoro_csv,
+      
+      // OPML 大纲导入导出相关命令
+      commands::export_opml,
+      commands::import_opml,
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
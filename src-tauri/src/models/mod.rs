@@ -32,6 +32,16 @@ pub struct Task {
     pub project_id: Option<String>,
     pub parent_id: Option<String>,
     pub position: i32,
+    #[serde(default)]
+    pub milestone_id: Option<String>,
+    #[serde(default)]
+    pub start_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub estimate_minutes: Option<i32>, // 预计用时（分钟）
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub recurrence: Option<String>, // iCalendar RRULE
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +53,18 @@ pub struct PomodoroSession {
     pub is_completed: bool,
     pub task_id: Option<String>,
     pub notes: Option<String>,
+    #[serde(default = "default_session_phase")]
+    pub phase: String, // work / short_break / long_break
+    #[serde(default)]
+    pub paused_seconds: i32,
+    #[serde(default)]
+    pub abandoned: bool,
+    #[serde(default)]
+    pub abandon_reason: Option<String>,
+}
+
+fn default_session_phase() -> String {
+    "work".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,13 +72,142 @@ pub struct Project {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
-    pub status: String,
+    pub status: String, // planning / active / on_hold / completed / archived
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub due_date: Option<DateTime<Utc>>,
     pub color: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectStatusChange {
+    pub id: String,
+    pub project_id: String,
+    pub from_status: Option<String>, // 创建项目时为空
+    pub to_status: String,
+    pub note: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Milestone {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 里程碑及其关联任务的完成情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneProgress {
+    pub milestone: Milestone,
+    pub total_tasks: i64,
+    pub completed_tasks: i64,
+    pub progress: f64, // 0-1，没有任务时为 0
+    pub is_overdue: bool,
+}
+
+/// 模板中的任务。`key` 只在模板内部使用，用来表示父子关系和所属看板列、里程碑
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateTask {
+    pub key: String,
+    pub parent_key: Option<String>,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: i32,
+    pub position: i32,
+    #[serde(default)]
+    pub due_offset_days: Option<i64>, // 相对开始日期的天数
+    #[serde(default)]
+    pub due_time: Option<String>, // 本地时间 HH:MM:SS
+    #[serde(default)]
+    pub is_completed: bool,
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    #[serde(default)]
+    pub milestone_key: Option<String>,
+    #[serde(default)]
+    pub column_key: Option<String>,
+    #[serde(default)]
+    pub column_position: i32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub start_offset_days: Option<i64>,
+    #[serde(default)]
+    pub start_time: Option<String>,
+    #[serde(default)]
+    pub estimate_minutes: Option<i32>,
+    #[serde(default)]
+    pub depends_on: Vec<String>, // 前置任务的 key
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>, // 复制项目时保留原来的完成时间，模板中为空
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateColumn {
+    pub key: String,
+    pub name: String,
+    pub position: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateMilestone {
+    pub key: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub position: i32,
+    #[serde(default)]
+    pub due_offset_days: Option<i64>,
+    #[serde(default)]
+    pub due_time: Option<String>,
+    #[serde(default)]
+    pub is_completed: bool,
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateNote {
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTemplateContent {
+    pub description: Option<String>,
+    pub color: String,
+    #[serde(default)]
+    pub due_offset_days: Option<i64>,
+    #[serde(default)]
+    pub due_time: Option<String>,
+    #[serde(default)]
+    pub columns: Vec<TemplateColumn>,
+    #[serde(default)]
+    pub milestones: Vec<TemplateMilestone>,
+    #[serde(default)]
+    pub tasks: Vec<TemplateTask>,
+    #[serde(default)]
+    pub notes: Vec<TemplateNote>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub content: ProjectTemplateContent,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KanbanColumn {
     pub id: String,
@@ -76,4 +227,475 @@ pub struct ColumnTask {
     pub task_id: String,
     pub column_id: String,
     pub position: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteRevision {
+    pub id: String,
+    pub note_id: String,
+    pub title: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashItem {
+    pub entity_type: String, // note / task / project
+    pub id: String,
+    pub title: String,
+    pub project_id: Option<String>,
+    pub deleted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+    pub undone: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub project_id: Option<String>,
+    pub action: String,
+    pub summary: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSettings {
+    pub interval_hours: i64, // 0 表示关闭自动备份
+    pub keep: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultExportReport {
+    pub directory: String,
+    pub exported: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultConflict {
+    pub path: String,
+    pub note_id: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultImportReport {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub conflicts: Vec<VaultConflict>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FolderSyncReport {
+    pub written: usize,
+    pub imported: usize,
+    pub renamed: usize,
+    pub deleted_files: usize,
+    pub trashed_notes: usize,
+    pub conflicts: Vec<String>, // 冲突副本的文件路径
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderSyncStatus {
+    pub directory: Option<String>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_report: Option<FolderSyncReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataExport {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub notes: Vec<Note>,
+    pub tags: Vec<Tag>,
+    pub note_tags: Vec<NoteTag>,
+    pub tasks: Vec<Task>,
+    pub projects: Vec<Project>,
+    pub kanban_columns: Vec<KanbanColumn>,
+    pub column_tasks: Vec<ColumnTask>,
+    pub pomodoro_sessions: Vec<PomodoroSession>,
+    #[serde(default)]
+    pub task_tags: Vec<TaskTag>,
+    #[serde(default)]
+    pub pomodoro_interruptions: Vec<PomodoroInterruption>,
+    #[serde(default)]
+    pub milestones: Vec<Milestone>,
+    #[serde(default)]
+    pub project_status_history: Vec<ProjectStatusChange>,
+    #[serde(default)]
+    pub task_dependencies: Vec<TaskDependency>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportTableSummary {
+    pub table: String,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataImportSummary {
+    pub version: u32,
+    pub dry_run: bool,
+    pub tables: Vec<ImportTableSummary>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSkip {
+    pub item: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub source: String,
+    pub notes_created: usize,
+    pub tasks_created: usize,
+    pub tasks_updated: usize,
+    pub projects: Vec<String>, // 导入涉及的项目名称
+    pub tags: Vec<String>,
+    pub skipped: Vec<ImportSkip>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskTag {
+    pub task_id: String,
+    pub tag_id: String,
+}
+
+/// 完成-开始依赖：`task_id` 在 `depends_on_id` 完成后才能开始
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskDependency {
+    pub task_id: String,
+    pub depends_on_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 时间线上的一个任务条。没有日期也无法自动排程的任务 `starts_at`/`ends_at` 为空
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineTask {
+    pub task_id: String,
+    pub title: String,
+    pub parent_id: Option<String>,
+    pub milestone_id: Option<String>,
+    pub is_completed: bool,
+    pub priority: i32,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub schedule: String, // fixed / auto / unscheduled
+    pub estimate_minutes: Option<i32>,
+    pub depends_on: Vec<String>,
+    pub slack_minutes: Option<i64>, // 负数表示会拖延后续任务或截止日期
+    pub is_critical: bool,
+    pub is_overdue: bool,
+    pub starts_before_dependencies: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineMilestone {
+    pub milestone_id: String,
+    pub name: String,
+    pub due_date: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub forecast_at: Option<DateTime<Utc>>, // 关联任务中最晚的结束时间
+    pub slack_minutes: Option<i64>,
+    pub is_overdue: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTimeline {
+    pub project_id: String,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub tasks: Vec<TimelineTask>, // 按开始时间排序，未排程的任务在最后
+    pub milestones: Vec<TimelineMilestone>,
+    pub dependencies: Vec<TaskDependency>,
+}
+
+/// 某一天结束时项目的任务数，包括子任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurndownPoint {
+    pub date: String, // 本地日期 YYYY-MM-DD
+    pub total_tasks: i64,
+    pub completed_tasks: i64,
+    pub open_tasks: i64,
+    pub ideal_open_tasks: Option<f64>, // 从起始日到截止日期匀速完成时剩余的任务数
+    pub from_snapshot: bool, // false 表示由任务的创建、完成和删除时间推算
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyThroughput {
+    pub week_start: String, // 周一的本地日期
+    pub completed_tasks: i64, // 本周净完成数，重新打开的任务会抵消
+    pub added_tasks: i64, // 本周净增加的任务数
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectBurndown {
+    pub project_id: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub points: Vec<BurndownPoint>,
+    pub weekly_throughput: Vec<WeeklyThroughput>,
+    pub velocity_per_week: f64, // 最近几周的平均净完成数
+    pub open_tasks: i64,
+    pub due_date: Option<DateTime<Utc>>,
+    pub projected_completion: Option<String>, // 按当前速度完成剩余任务的日期，速度为 0 时为空
+    pub days_ahead_of_due: Option<i64>, // 预计完成日期早于截止日期的天数，负数表示会延期
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TodoTxtSyncReport {
+    pub created: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub exported: usize,
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarSource {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub subscribed: bool,
+    pub imported_at: DateTime<Utc>,
+    pub event_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub id: String,
+    pub source_id: String,
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub all_day: bool,
+    pub transparent: bool, // 不占用时间（TRANSP:TRANSPARENT）
+    pub recurrence: Option<String>,
+    pub exdates: Vec<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarImportReport {
+    pub source: CalendarSource,
+    pub imported: usize,
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaItem {
+    pub kind: String, // event / task / pomodoro
+    pub id: String,
+    pub title: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub all_day: bool,
+    pub is_completed: Option<bool>,
+    pub project_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreeSlot {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaDay {
+    pub date: String, // 本地日期 YYYY-MM-DD
+    pub items: Vec<AgendaItem>,
+    pub free_slots: Vec<FreeSlot>,
+    pub free_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HtmlExportReport {
+    pub path: String,
+    pub notes: usize,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvExportReport {
+    pub path: String,
+    pub rows: usize,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PomodoroInterruption {
+    pub id: String,
+    pub session_id: String,
+    pub kind: String, // pause / internal / external
+    pub reason: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub duration: i64, // 暂停的秒数
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PomodoroSettings {
+    pub work_minutes: i64,
+    pub short_break_minutes: i64,
+    pub long_break_minutes: i64,
+    pub long_break_interval: i64, // 每完成几次专注进入一次长休息
+    pub auto_start_breaks: bool,
+    pub auto_start_work: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PomodoroTimerState {
+    pub state: String, // work / short_break / long_break / paused / stopped
+    pub phase: String, // 当前阶段，停止时为下一个要开始的阶段
+    pub status: String, // running / paused / stopped
+    pub task_id: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>, // 阶段开始时分配，结束时作为会话记录的 id
+    #[serde(default)]
+    pub interruptions: Vec<PomodoroInterruption>, // 当前阶段尚未写入的中断
+    pub phase_started_at: Option<DateTime<Utc>>,
+    pub resumed_at: Option<DateTime<Utc>>, // 最近一次开始或继续计时的时间，暂停时为空
+    pub elapsed_seconds: i64, // resumed_at 之前已累计的秒数
+    pub duration_seconds: i64,
+    pub remaining_seconds: i64,
+    pub completed_work_sessions: i64, // 本轮已完成的专注次数，决定何时长休息
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PomodoroPhaseEvent {
+    pub previous_phase: String,
+    pub phase: String,
+    pub completed: bool, // false 表示阶段被跳过或中断
+    pub session_id: Option<String>,
+    pub state: PomodoroTimerState,
+}
+
+/// 应用异常退出后遗留的未结束会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DanglingPomodoroSession {
+    pub session: PomodoroSession,
+    pub heartbeat_at: Option<DateTime<Utc>>,
+    pub planned_end_at: DateTime<Utc>,
+    pub heartbeat_duration: i32, // 截断到最后一次心跳时的净时长（秒）
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusDay {
+    pub date: String, // 本地日期 YYYY-MM-DD
+    pub focus_minutes: i64,
+    pub sessions: i64, // 完成的番茄数
+    pub goal_met: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusBucket {
+    pub index: u32, // 星期：0 为周一；小时：0-23
+    pub focus_minutes: i64,
+    pub sessions: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusBreakdown {
+    pub id: Option<String>, // 未关联项目或标签时为空
+    pub name: String,
+    pub focus_minutes: i64,
+    pub sessions: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusAnalytics {
+    pub start_date: String,
+    pub end_date: String,
+    pub total_focus_minutes: i64,
+    pub completed_sessions: i64,
+    pub daily_goal_minutes: i64,
+    pub days: Vec<FocusDay>,
+    pub weekdays: Vec<FocusBucket>,
+    pub hours: Vec<FocusBucket>,
+    pub projects: Vec<FocusBreakdown>,
+    pub tags: Vec<FocusBreakdown>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusGoalProgress {
+    pub date: String,
+    pub goal_minutes: i64,
+    pub focus_minutes: i64,
+    pub completed_sessions: i64,
+    pub progress: f64, // 可能超过 1
+    pub goal_met: bool,
+    pub current_streak: i64,
+    pub longest_streak: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusHeatmapCell {
+    pub date: String,
+    pub weekday: u32, // 0 为周一
+    pub focus_minutes: i64,
+    pub level: u8, // 0-4
+}
+
+/// 专注模式：专注阶段开始和结束时执行的本地钩子
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FocusModeSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub start_command: Option<String>, // 专注开始时执行的 shell 命令
+    #[serde(default)]
+    pub end_command: Option<String>, // 专注结束时执行的 shell 命令
+    #[serde(default)]
+    pub blocklist_path: Option<String>, // hosts 格式的屏蔽文件
+    #[serde(default)]
+    pub blocked_sites: Vec<String>,
+    #[serde(default)]
+    pub silence_reminders: bool, // 专注期间暂停任务提醒，休息时补发
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusModeState {
+    pub active: bool,
+    pub since: Option<DateTime<Utc>>,
+    pub deferred_reminders: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskReminder {
+    pub task_id: String,
+    pub title: String,
+    pub remind_at: DateTime<Utc>,
+    pub deferred: bool, // 专注期间或应用关闭期间延后发送
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffChunk {
+    pub kind: String, // equal / insert / delete
+    pub text: String,
 }
\ No newline at end of file